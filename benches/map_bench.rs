@@ -21,7 +21,7 @@ fn map_random(c: &mut Criterion) {
     let mut values = Vec::<usize>::with_capacity(SIZE);
 
     while values.len() < SIZE {
-        let value = rng.gen_range(0_..(SIZE * 2));
+        let value = rng.gen_range(0..(SIZE * 2));
         if picked_values.contains(&value) {
             continue;
         } else {
@@ -31,7 +31,7 @@ fn map_random(c: &mut Criterion) {
     }
     drop(picked_values);
     // wonder why to_owned() doesn't work here
-    let values_reverse: Vec<usize> = values.iter().rev().map(|x| *x).collect();
+    let values_reverse: Vec<usize> = values.iter().rev().copied().collect();
 
     c.bench_function("map_random", |b| {
         b.iter({
@@ -60,7 +60,7 @@ fn map_in_order(c: &mut Criterion) {
                 for v in 0..=SIZE {
                     q.insert(v, v + 1);
                 }
-                for v in SIZE..0 {
+                for v in (0..=SIZE).rev() {
                     let _ = q.remove(&v);
                 }
             }
@@ -20,7 +20,7 @@ fn queue_random(c: &mut Criterion) {
     let mut values = Vec::<usize>::with_capacity(SIZE);
 
     while values.len() < SIZE {
-        let value = rng.gen_range(0_..(SIZE * 2));
+        let value = rng.gen_range(0..(SIZE * 2));
         if picked_values.contains(&value) {
             continue;
         } else {
@@ -1,7 +1,9 @@
 use rb_tree::RBQueue;
 
+type Cmp = dyn Fn(&i32, &i32) -> std::cmp::Ordering;
+
 struct ByReference {
-    queue: RBQueue<i32, &'static dyn Fn(&i32, &i32) -> std::cmp::Ordering>,
+    queue: RBQueue<i32, &'static Cmp>,
 }
 
 impl Default for ByReference {
@@ -13,7 +15,7 @@ impl Default for ByReference {
 }
 
 struct Boxing {
-    queue: RBQueue<i32, Box<dyn Fn(&i32, &i32) -> std::cmp::Ordering>>,
+    queue: RBQueue<i32, Box<Cmp>>,
 }
 
 impl Default for Boxing {
@@ -8,8 +8,9 @@ fn from_origin(p: &(f64, f64)) -> f64 {
 }
 
 fn main() {
-    // orders values by their distance from the origin
-    // or absolute size of x value on equal distance
+    // orders values by their distance from the origin,
+    // then by x value on equal distance, so that no two
+    // distinct points compare as equal
     let mut q = new_c_queue!(|l: &(f64, f64), r| {
         let l_dist = from_origin(l);
         let r_dist = from_origin(r);
@@ -24,9 +25,18 @@ fn main() {
     q.insert((-5.0, 0.0));
     q.insert((2.0, 3.0));
     q.insert((3.0, 2.0));
+    q.insert((0.5, 0.0));
+    q.insert((-0.5, 0.0));
 
     assert_eq!(
         q.ordered(),
-        [&(0.0, 0.0), &(2.0, 3.0), &(3.0, 2.0), &(-5.0, 0.0)]
+        [
+            &(0.0, 0.0),
+            &(-0.5, 0.0),
+            &(0.5, 0.0),
+            &(2.0, 3.0),
+            &(3.0, 2.0),
+            &(-5.0, 0.0)
+        ]
     );
 }
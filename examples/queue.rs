@@ -8,7 +8,7 @@ fn main() {
     let mut q1 = RBQueue::new(|l: &i64, r| l.cmp(r));
 
     // compare in the reverse order
-    let mut q2 = new_c_queue!(|l: &i64, r| (r - l));
+    let mut q2 = new_c_queue!(|l: &i64, r| r - l);
 
     q1.insert(1);
     q1.insert(2);
@@ -0,0 +1,498 @@
+use crate::node::{counting, Node, Refresh};
+use crate::rbbase;
+use crate::RBAugmentedMap;
+use std::fmt::{Debug, Formatter, Result};
+use std::iter::{ExactSizeIterator, FromIterator, FusedIterator};
+use std::ops::{Bound, RangeBounds};
+
+/// Describes the summary an RBAugmentedMap keeps for
+/// every subtree. Summaries form a monoid: `combine`
+/// must be associative and `empty` must be its identity,
+/// so that any run of consecutive entries can be summarised
+/// by combining the summaries of its parts.
+///
+/// None of these methods should panic, as they are called
+/// while the tree is being restructured.
+/// # Example:
+/// ```
+/// use rb_tree::augment::Augment;
+/// use rb_tree::RBAugmentedMap;
+///
+/// // the largest value and the number of flagged entries
+/// struct MaxAndFlagged;
+///
+/// impl Augment<u32, (i64, bool)> for MaxAndFlagged {
+///     type Summary = (Option<i64>, usize);
+///
+///     fn empty(&self) -> Self::Summary {
+///         (None, 0)
+///     }
+///
+///     fn single(&self, _: &u32, value: &(i64, bool)) -> Self::Summary {
+///         (Some(value.0), value.1 as usize)
+///     }
+///
+///     fn combine(&self, l: &Self::Summary, r: &Self::Summary) -> Self::Summary {
+///         (l.0.max(r.0), l.1 + r.1)
+///     }
+/// }
+///
+/// let mut m = RBAugmentedMap::new(MaxAndFlagged);
+/// m.insert(1, (7, true));
+/// m.insert(2, (-3, false));
+/// m.insert(3, (4, true));
+/// assert_eq!(m.aggregate(2..), (Some(4), 1));
+/// assert_eq!(m.summary(), (Some(7), 2));
+/// ```
+pub trait Augment<K, V> {
+    type Summary: Clone;
+
+    /// Returns the summary of no entries.
+    fn empty(&self) -> Self::Summary;
+
+    /// Returns the summary of a single entry.
+    fn single(&self, key: &K, value: &V) -> Self::Summary;
+
+    /// Combines the summary of some entries with the
+    /// summary of the entries that immediately follow them.
+    fn combine(&self, left: &Self::Summary, right: &Self::Summary) -> Self::Summary;
+
+    /// Computes the summary of a node from its entry and
+    /// the summaries of its children, where it has them.
+    /// The default combines them in order; override it
+    /// where that can be done more cheaply.
+    fn summarize(
+        &self,
+        key: &K,
+        value: &V,
+        left: Option<&Self::Summary>,
+        right: Option<&Self::Summary>,
+    ) -> Self::Summary {
+        let mut summary = self.single(key, value);
+        if let Some(l) = left {
+            summary = self.combine(l, &summary);
+        }
+        if let Some(r) = right {
+            summary = self.combine(&summary, r);
+        }
+        summary
+    }
+}
+
+// an entry of an augmented map along with the
+// summary of the subtree it is the root of
+#[derive(Clone)]
+pub struct AugEntry<K, V, S> {
+    key: K,
+    value: V,
+    summary: S,
+}
+
+// recomputes summaries as the tree changes shape
+struct Refresher<'a, A>(&'a A);
+
+impl<'a, K, V, A: Augment<K, V>> Refresh<AugEntry<K, V, A::Summary>> for Refresher<'a, A> {
+    fn refresh(
+        &self,
+        entry: &mut AugEntry<K, V, A::Summary>,
+        left: Option<&AugEntry<K, V, A::Summary>>,
+        right: Option<&AugEntry<K, V, A::Summary>>,
+    ) {
+        entry.summary = self.0.summarize(
+            &entry.key,
+            &entry.value,
+            left.map(|l| &l.summary),
+            right.map(|r| &r.summary),
+        );
+    }
+}
+
+fn above_lower<K: PartialOrd>(lower: Bound<&K>, key: &K) -> bool {
+    match lower {
+        Bound::Included(l) => key >= l,
+        Bound::Excluded(l) => key > l,
+        Bound::Unbounded => true,
+    }
+}
+
+fn below_upper<K: PartialOrd>(upper: Bound<&K>, key: &K) -> bool {
+    match upper {
+        Bound::Included(u) => key <= u,
+        Bound::Excluded(u) => key < u,
+        Bound::Unbounded => true,
+    }
+}
+
+impl<K: PartialOrd, V, A: Augment<K, V>> RBAugmentedMap<K, V, A> {
+    /// Creates and returns a new, empty RBAugmentedMap
+    /// that summarises its entries with aug.
+    /// # Example:
+    /// ```
+    /// use rb_tree::augment::Augment;
+    /// use rb_tree::RBAugmentedMap;
+    ///
+    /// struct Sum;
+    ///
+    /// impl Augment<u32, u64> for Sum {
+    ///     type Summary = u64;
+    ///
+    ///     fn empty(&self) -> u64 {
+    ///         0
+    ///     }
+    ///
+    ///     fn single(&self, _: &u32, value: &u64) -> u64 {
+    ///         *value
+    ///     }
+    ///
+    ///     fn combine(&self, l: &u64, r: &u64) -> u64 {
+    ///         l + r
+    ///     }
+    /// }
+    ///
+    /// let mut m = RBAugmentedMap::new(Sum);
+    /// m.extend((0..10).map(|k| (k, k as u64 * 10)));
+    /// assert_eq!(m.aggregate(2..5), 90);
+    /// assert_eq!(m.aggregate(..=1), 10);
+    /// ```
+    pub fn new(aug: A) -> RBAugmentedMap<K, V, A> {
+        RBAugmentedMap {
+            root: Node::default(),
+            contained: 0,
+            aug,
+            #[cfg(feature = "stats")]
+            ops: Default::default(),
+        }
+    }
+
+    /// Clears all entries from the map.
+    pub fn clear(&mut self) {
+        self.root = Node::default();
+        self.contained = 0;
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.contained
+    }
+
+    /// Returns true if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.contained == 0
+    }
+
+    /// Returns true if the map contains an entry
+    /// for key, false otherwise.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns a reference to the value associated
+    /// with key, or None if there is no such value.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_pair(key).map(|(_, v)| v)
+    }
+
+    /// Returns references to the key-value pair
+    /// associated with key, or None if there is no
+    /// such pair.
+    pub fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
+        counting!(self.ops);
+        self.root
+            .get(key, &|k: &K, e: &AugEntry<K, V, A::Summary>| {
+                k.partial_cmp(&e.key).unwrap()
+            })
+            .map(|e| (&e.key, &e.value))
+    }
+
+    /// Inserts a value to associate with the given key,
+    /// returning the previously stored key-value pair
+    /// if one existed, None otherwise.
+    pub fn insert(&mut self, key: K, value: V) -> Option<(K, V)> {
+        let entry = AugEntry {
+            summary: self.aug.single(&key, &value),
+            key,
+            value,
+        };
+        let cmp = |l: &AugEntry<K, V, A::Summary>, r: &AugEntry<K, V, A::Summary>| {
+            l.key.partial_cmp(&r.key).unwrap()
+        };
+        counting!(self.ops);
+        match self.root.insert_with(entry, &cmp, &Refresher(&self.aug)) {
+            Some(e) => Some((e.key, e.value)),
+            None => {
+                self.contained += 1;
+                None
+            }
+        }
+    }
+
+    /// Removes the entry associated with key and
+    /// returns its value, or None if there was none.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// Removes the entry associated with key and
+    /// returns it, or None if there was none.
+    pub fn remove_entry(&mut self, key: &K) -> Option<(K, V)> {
+        let cmp = |k: &K, e: &AugEntry<K, V, A::Summary>| k.partial_cmp(&e.key).unwrap();
+        counting!(self.ops);
+        let e = self.root.remove_with(key, &cmp, &Refresher(&self.aug))?;
+        self.contained -= 1;
+        Some((e.key, e.value))
+    }
+
+    /// Applies f to the value associated with key,
+    /// keeping the summaries up to date, and returns
+    /// true if there was such a value.
+    /// # Example:
+    /// ```
+    /// use rb_tree::augment::Augment;
+    /// use rb_tree::RBAugmentedMap;
+    ///
+    /// struct Count;
+    ///
+    /// impl Augment<u32, bool> for Count {
+    ///     type Summary = usize;
+    ///
+    ///     fn empty(&self) -> usize {
+    ///         0
+    ///     }
+    ///
+    ///     fn single(&self, _: &u32, flagged: &bool) -> usize {
+    ///         *flagged as usize
+    ///     }
+    ///
+    ///     fn combine(&self, l: &usize, r: &usize) -> usize {
+    ///         l + r
+    ///     }
+    /// }
+    ///
+    /// let mut m: RBAugmentedMap<u32, bool, Count> = RBAugmentedMap::new(Count);
+    /// m.extend((0..8).map(|k| (k, false)));
+    /// assert!(m.update(&3, |f| *f = true));
+    /// assert!(!m.update(&9, |f| *f = true));
+    /// assert_eq!(m.aggregate(2..6), 1);
+    /// assert_eq!(m.aggregate(4..), 0);
+    /// ```
+    pub fn update<F: FnOnce(&mut V)>(&mut self, key: &K, f: F) -> bool {
+        match self.remove_entry(key) {
+            Some((k, mut v)) => {
+                f(&mut v);
+                self.insert(k, v);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes and returns the entry with the smallest key.
+    pub fn pop_pair(&mut self) -> Option<(K, V)> {
+        counting!(self.ops);
+        let e = self.root.pop_with(false, &Refresher(&self.aug))?;
+        self.contained -= 1;
+        Some((e.key, e.value))
+    }
+
+    /// Removes and returns the entry with the largest key.
+    pub fn pop_pair_back(&mut self) -> Option<(K, V)> {
+        counting!(self.ops);
+        let e = self.root.pop_with(true, &Refresher(&self.aug))?;
+        self.contained -= 1;
+        Some((e.key, e.value))
+    }
+
+    /// Returns the entry with the smallest key.
+    pub fn peek_pair(&self) -> Option<(&K, &V)> {
+        self.root.peek(false).map(|e| (&e.key, &e.value))
+    }
+
+    /// Returns the entry with the largest key.
+    pub fn peek_pair_back(&self) -> Option<(&K, &V)> {
+        self.root.peek(true).map(|e| (&e.key, &e.value))
+    }
+
+    /// Returns the summary of every entry in the map.
+    pub fn summary(&self) -> A::Summary {
+        self.subtree(&self.root)
+    }
+
+    /// Returns the summary of the entries whose keys
+    /// lie in range, in logarithmic time.
+    /// # Example:
+    /// ```
+    /// use rb_tree::augment::Augment;
+    /// use rb_tree::RBAugmentedMap;
+    ///
+    /// // collects keys, to show which entries were summarised
+    /// struct Keys;
+    ///
+    /// impl Augment<i32, ()> for Keys {
+    ///     type Summary = Vec<i32>;
+    ///
+    ///     fn empty(&self) -> Vec<i32> {
+    ///         Vec::new()
+    ///     }
+    ///
+    ///     fn single(&self, key: &i32, _: &()) -> Vec<i32> {
+    ///         vec![*key]
+    ///     }
+    ///
+    ///     fn combine(&self, l: &Vec<i32>, r: &Vec<i32>) -> Vec<i32> {
+    ///         l.iter().chain(r).copied().collect()
+    ///     }
+    /// }
+    ///
+    /// let mut m = RBAugmentedMap::new(Keys);
+    /// m.extend((0..10).map(|k| (k, ())));
+    /// assert_eq!(m.aggregate(3..6), [3, 4, 5]);
+    /// assert_eq!(m.aggregate(8..), [8, 9]);
+    /// assert_eq!(m.aggregate(..=1), [0, 1]);
+    /// assert_eq!(m.aggregate(6..3), []);
+    /// ```
+    pub fn aggregate<R: RangeBounds<K>>(&self, range: R) -> A::Summary {
+        self.range(&self.root, range.start_bound(), range.end_bound())
+    }
+
+    fn subtree(&self, node: &Node<AugEntry<K, V, A::Summary>>) -> A::Summary {
+        match node.value() {
+            Some(e) => e.summary.clone(),
+            None => self.aug.empty(),
+        }
+    }
+
+    // summarises the entries below node that lie between lower and upper
+    fn range(
+        &self,
+        node: &Node<AugEntry<K, V, A::Summary>>,
+        lower: Bound<&K>,
+        upper: Bound<&K>,
+    ) -> A::Summary {
+        match node.value() {
+            None => self.aug.empty(),
+            Some(e) if !above_lower(lower, &e.key) => self.range(node.get_right(), lower, upper),
+            Some(e) if !below_upper(upper, &e.key) => self.range(node.get_left(), lower, upper),
+            Some(e) => {
+                // the range splits here, so each side only has one bound left
+                let left = self.above(node.get_left(), lower);
+                let mid = self.aug.single(&e.key, &e.value);
+                let right = self.below(node.get_right(), upper);
+                self.aug.combine(&self.aug.combine(&left, &mid), &right)
+            }
+        }
+    }
+
+    // summarises the entries below node that lie above lower
+    fn above(&self, node: &Node<AugEntry<K, V, A::Summary>>, lower: Bound<&K>) -> A::Summary {
+        match node.value() {
+            None => self.aug.empty(),
+            Some(e) if above_lower(lower, &e.key) => {
+                let left = self.above(node.get_left(), lower);
+                let mid = self.aug.single(&e.key, &e.value);
+                let right = self.subtree(node.get_right());
+                self.aug.combine(&self.aug.combine(&left, &mid), &right)
+            }
+            Some(_) => self.above(node.get_right(), lower),
+        }
+    }
+
+    // summarises the entries below node that lie below upper
+    fn below(&self, node: &Node<AugEntry<K, V, A::Summary>>, upper: Bound<&K>) -> A::Summary {
+        match node.value() {
+            None => self.aug.empty(),
+            Some(e) if below_upper(upper, &e.key) => {
+                let left = self.subtree(node.get_left());
+                let mid = self.aug.single(&e.key, &e.value);
+                let right = self.below(node.get_right(), upper);
+                self.aug.combine(&self.aug.combine(&left, &mid), &right)
+            }
+            Some(_) => self.below(node.get_left(), upper),
+        }
+    }
+
+    /// An iterator that visits all key-value
+    /// pairs in their key's order.
+    pub fn iter(&self) -> Iter<'_, K, V, A::Summary> {
+        Iter {
+            iter: rbbase::Iter::new(&self.root, self.contained),
+        }
+    }
+}
+
+impl<K: PartialOrd, V, A: Augment<K, V> + Default> Default for RBAugmentedMap<K, V, A> {
+    fn default() -> Self {
+        RBAugmentedMap::new(A::default())
+    }
+}
+
+impl<K: PartialOrd + Debug, V: Debug, A: Augment<K, V>> Debug for RBAugmentedMap<K, V, A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialOrd, V, A: Augment<K, V> + Default> FromIterator<(K, V)>
+    for RBAugmentedMap<K, V, A>
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = RBAugmentedMap::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: PartialOrd, V, A: Augment<K, V>> Extend<(K, V)> for RBAugmentedMap<K, V, A> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+pub struct Iter<'a, K, V, S> {
+    iter: rbbase::Iter<'a, AugEntry<K, V, S>>,
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.iter.next().map(|e| (&e.key, &e.value))
+    }
+}
+
+impl<'a, K, V, S> ExactSizeIterator for Iter<'a, K, V, S> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V, S> FusedIterator for Iter<'a, K, V, S> {}
+
+// checks every summary below node matches one computed from scratch
+#[cfg(test)]
+impl<K: PartialOrd, V, A: Augment<K, V>> RBAugmentedMap<K, V, A>
+where
+    A::Summary: PartialEq,
+{
+    pub(crate) fn summaries_valid(&self) -> bool {
+        self.check(&self.root).is_some() && self.root.black_height().is_some()
+    }
+
+    fn check(&self, node: &Node<AugEntry<K, V, A::Summary>>) -> Option<A::Summary> {
+        match node.value() {
+            None => Some(self.aug.empty()),
+            Some(e) => {
+                let left = self.check(node.get_left())?;
+                let right = self.check(node.get_right())?;
+                let mid = self.aug.single(&e.key, &e.value);
+                let expected = self.aug.combine(&self.aug.combine(&left, &mid), &right);
+                if expected == e.summary {
+                    Some(expected)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
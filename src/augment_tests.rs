@@ -0,0 +1,95 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::augment::Augment;
+use crate::RBAugmentedMap;
+
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+// sum of values, largest value and number of flagged entries
+struct Stats;
+
+impl Augment<u8, (i64, bool)> for Stats {
+    type Summary = (i64, Option<i64>, usize);
+
+    fn empty(&self) -> Self::Summary {
+        (0, None, 0)
+    }
+
+    fn single(&self, _: &u8, v: &(i64, bool)) -> Self::Summary {
+        (v.0, Some(v.0), v.1 as usize)
+    }
+
+    fn combine(&self, l: &Self::Summary, r: &Self::Summary) -> Self::Summary {
+        (l.0 + r.0, l.1.max(r.1), l.2 + r.2)
+    }
+}
+
+fn expected<'a, I: Iterator<Item = &'a (i64, bool)>>(values: I) -> (i64, Option<i64>, usize) {
+    values.fold((0, None, 0), |s, v| {
+        (s.0 + v.0, s.1.max(Some(v.0)), s.2 + v.1 as usize)
+    })
+}
+
+#[test]
+fn test_summaries_follow_changes() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(35);
+    let mut m = RBAugmentedMap::new(Stats);
+    let mut model = BTreeMap::new();
+    for _ in 0..3000 {
+        let key = rng.gen_range(0..64u8);
+        match rng.gen_range(0..6) {
+            0 | 1 => {
+                let value = (rng.gen_range(-100..100), rng.gen_bool(0.3));
+                assert_eq!(m.insert(key, value).map(|p| p.1), model.insert(key, value));
+            }
+            2 => assert_eq!(m.remove(&key), model.remove(&key)),
+            3 => {
+                let flip = |v: &mut (i64, bool)| v.1 = !v.1;
+                let found = m.update(&key, flip);
+                assert_eq!(found, model.get_mut(&key).map(flip).is_some());
+            }
+            4 => {
+                let first = model.keys().next().copied();
+                assert_eq!(m.pop_pair().map(|p| p.0), first);
+                first.map(|k| model.remove(&k));
+            }
+            _ => {
+                let last = model.keys().next_back().copied();
+                assert_eq!(m.pop_pair_back().map(|p| p.0), last);
+                last.map(|k| model.remove(&k));
+            }
+        }
+        assert!(m.summaries_valid());
+        assert_eq!(m.len(), model.len());
+
+        let lo = rng.gen_range(0..64u8);
+        let hi = rng.gen_range(lo..=64u8);
+        assert_eq!(
+            m.aggregate(lo..hi),
+            expected(model.range(lo..hi).map(|p| p.1))
+        );
+        assert_eq!(
+            m.aggregate(lo..=hi),
+            expected(model.range(lo..=hi).map(|p| p.1))
+        );
+        assert_eq!(m.aggregate(lo..), expected(model.range(lo..).map(|p| p.1)));
+        assert_eq!(m.aggregate(..hi), expected(model.range(..hi).map(|p| p.1)));
+        assert_eq!(m.summary(), expected(model.values()));
+    }
+}
+
+#[test]
+fn test_iter_and_clear() {
+    let mut m: RBAugmentedMap<u8, (i64, bool), Stats> = RBAugmentedMap::new(Stats);
+    m.extend((0..10).rev().map(|k| (k, (k as i64, k % 2 == 0))));
+    assert_eq!(m.iter().len(), 10);
+    assert!(m.iter().map(|p| *p.0).eq(0..10));
+    assert_eq!(m.peek_pair(), Some((&0, &(0, true))));
+    assert_eq!(m.peek_pair_back(), Some((&9, &(9, false))));
+    assert_eq!(m.aggregate(2..=4), (9, Some(4), 2));
+    m.clear();
+    assert!(m.is_empty());
+    assert_eq!(m.summary(), (0, None, 0));
+}
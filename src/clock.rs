@@ -0,0 +1,92 @@
+//! Sources of the current time for the types that
+//! track deadlines, so that tests can control time
+//! rather than waiting on the system clock.
+
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A source of the current time.
+pub trait Clock {
+    /// Returns the current time. This should never
+    /// go backwards.
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock, as given by Instant::now.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to. Share it with
+/// the structure using it by reference, Rc or Arc, and
+/// advance it from outside.
+/// # Example:
+/// ```
+/// use rb_tree::clock::{Clock, ManualClock};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(clock.now() - start, Duration::from_secs(5));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Cell<Instant>,
+}
+
+impl ManualClock {
+    /// Creates a clock stopped at the current time.
+    pub fn new() -> ManualClock {
+        ManualClock::starting_at(Instant::now())
+    }
+
+    /// Creates a clock stopped at start.
+    pub fn starting_at(start: Instant) -> ManualClock {
+        ManualClock {
+            now: Cell::new(start),
+        }
+    }
+
+    /// Moves the clock forwards by the given duration.
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Rc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
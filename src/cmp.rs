@@ -0,0 +1,360 @@
+//! Composable Comparators.
+//!
+//! Each type here implements `Comparator`, so it can
+//! order an RBTreeWithCmp, RBMapWithCmp or RBSet
+//! directly. An RBQueue can use one through the boxed
+//! function returned by `Comparator::cmp`.
+//! # Example:
+//! ```
+//! use rb_tree::cmp::{ByKey, Natural, Reverse, Then};
+//! use rb_tree::{Comparator, RBQueue, RBTreeWithCmp};
+//!
+//! // longest first, then alphabetically
+//! let order = Then(Reverse(ByKey(|s: &&str| s.len())), Natural);
+//! let mut t = RBTreeWithCmp::new(order);
+//! t.extend(vec!["bb", "a", "ccc", "ab"]);
+//! assert_eq!(t.ordered(), [&"ccc", &"ab", &"bb", &"a"]);
+//!
+//! let mut q = RBQueue::new(Reverse(Natural).cmp());
+//! q.extend(vec![1, 3, 2]);
+//! assert_eq!(q.ordered(), [&3, &2, &1]);
+//! ```
+
+use crate::Comparator;
+use std::cmp::Ordering;
+
+/// Orders values by their Ord implementation.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::Natural;
+/// use rb_tree::RBSet;
+///
+/// let s = RBSet::from_iter_with(Natural, vec![3, 1, 2]);
+/// assert_eq!(s.ordered(), [&1, &2, &3]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Natural;
+
+impl<T: Ord> Comparator<T> for Natural {
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        Box::new(|l: &T, r: &T| l.cmp(r))
+    }
+}
+
+/// Reverses the order given by the wrapped Comparator.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::{Natural, Reverse};
+/// use rb_tree::RBSet;
+///
+/// let s = RBSet::from_iter_with(Reverse(Natural), vec![3, 1, 2]);
+/// assert_eq!(s.ordered(), [&3, &2, &1]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Reverse<C>(pub C);
+
+impl<T: 'static, C: Comparator<T>> Comparator<T> for Reverse<C> {
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        let cmp = self.0.cmp();
+        Box::new(move |l: &T, r: &T| cmp(r, l))
+    }
+}
+
+/// Orders values by the natural order of the key
+/// the given function extracts from them.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::ByKey;
+/// use rb_tree::RBMapWithCmp;
+///
+/// let mut m = RBMapWithCmp::new(ByKey(|k: &i32| k.abs()));
+/// m.insert(-3, 'a');
+/// m.insert(2, 'b');
+/// m.insert(1, 'c');
+/// assert_eq!(m.keys().collect::<Vec<_>>(), [&1, &2, &-3]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ByKey<F>(pub F);
+
+impl<T, K, F> Comparator<T> for ByKey<F>
+where
+    K: Ord,
+    F: Fn(&T) -> K + Clone + 'static,
+{
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        let key = self.0.clone();
+        Box::new(move |l: &T, r: &T| key(l).cmp(&key(r)))
+    }
+}
+
+/// Orders values by the first Comparator, falling
+/// back to the second when the first finds them equal.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::{ByKey, Natural, Then};
+/// use rb_tree::RBSet;
+///
+/// let s = RBSet::from_iter_with(
+///     Then(ByKey(|v: &(i32, i32)| v.1), Natural),
+///     vec![(2, 1), (1, 2), (1, 1)],
+/// );
+/// assert_eq!(s.ordered(), [&(1, 1), &(2, 1), &(1, 2)]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Then<A, B>(pub A, pub B);
+
+impl<T: 'static, A: Comparator<T>, B: Comparator<T>> Comparator<T> for Then<A, B> {
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        let first = self.0.cmp();
+        let second = self.1.cmp();
+        Box::new(move |l: &T, r: &T| first(l, r).then_with(|| second(l, r)))
+    }
+}
+
+/// Orders Options with None before any Some,
+/// comparing Some values with the wrapped Comparator.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::{Natural, NullsFirst};
+/// use rb_tree::RBSet;
+///
+/// let s = RBSet::from_iter_with(NullsFirst(Natural), vec![Some(2), None, Some(1)]);
+/// assert_eq!(s.ordered(), [&None, &Some(1), &Some(2)]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NullsFirst<C>(pub C);
+
+impl<T: 'static, C: Comparator<T>> Comparator<Option<T>> for NullsFirst<C> {
+    fn cmp(&self) -> Box<dyn Fn(&Option<T>, &Option<T>) -> Ordering> {
+        let cmp = self.0.cmp();
+        Box::new(move |l: &Option<T>, r: &Option<T>| match (l, r) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(l), Some(r)) => cmp(l, r),
+        })
+    }
+}
+
+/// Orders Options with None after any Some,
+/// comparing Some values with the wrapped Comparator.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::{Natural, NullsLast};
+/// use rb_tree::RBSet;
+///
+/// let s = RBSet::from_iter_with(NullsLast(Natural), vec![Some(2), None, Some(1)]);
+/// assert_eq!(s.ordered(), [&Some(1), &Some(2), &None]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NullsLast<C>(pub C);
+
+impl<T: 'static, C: Comparator<T>> Comparator<Option<T>> for NullsLast<C> {
+    fn cmp(&self) -> Box<dyn Fn(&Option<T>, &Option<T>) -> Ordering> {
+        let cmp = self.0.cmp();
+        Box::new(move |l: &Option<T>, r: &Option<T>| match (l, r) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(l), Some(r)) => cmp(l, r),
+        })
+    }
+}
+
+/// Orders floats by their IEEE 754 total order, so
+/// that NaN can be stored instead of causing a panic.
+/// Negative values come before `-0.0`, which comes
+/// before `0.0`; positive NaN sorts after infinity.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::TotalFloat;
+/// use rb_tree::RBTreeWithCmp;
+///
+/// let mut t = RBTreeWithCmp::new(TotalFloat);
+/// t.extend(vec![f64::NAN, 1.5, f64::INFINITY, -2.0]);
+/// assert_eq!(t.len(), 4);
+/// assert_eq!(t.peek(), Some(&-2.0));
+/// assert!(t.peek_back().unwrap().is_nan());
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct TotalFloat;
+
+impl Comparator<f32> for TotalFloat {
+    fn cmp(&self) -> Box<dyn Fn(&f32, &f32) -> Ordering> {
+        Box::new(|l: &f32, r: &f32| l.total_cmp(r))
+    }
+}
+
+impl Comparator<f64> for TotalFloat {
+    fn cmp(&self) -> Box<dyn Fn(&f64, &f64) -> Ordering> {
+        Box::new(|l: &f64, r: &f64| l.total_cmp(r))
+    }
+}
+
+/// Orders strings naturally, comparing runs of
+/// ASCII digits by their numeric value so that
+/// "file2" comes before "file10". Runs of equal
+/// value are ordered by their number of leading
+/// zeros ("1" before "01") only when the strings
+/// are otherwise equal, and all other characters
+/// are compared by code point.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::Alphanumeric;
+/// use rb_tree::RBSet;
+///
+/// let s = RBSet::from_iter_with(Alphanumeric, vec!["file10", "file2", "file02", "file1"]);
+/// assert_eq!(s.ordered(), [&"file1", &"file2", &"file02", &"file10"]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Alphanumeric;
+
+impl<T: AsRef<str>> Comparator<T> for Alphanumeric {
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        Box::new(|l: &T, r: &T| alphanumeric(l.as_ref(), r.as_ref()))
+    }
+}
+
+// splits a string at the end of its leading run of ascii digits
+fn split_digits(s: &[u8]) -> (&[u8], &[u8]) {
+    let end = s
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .unwrap_or(s.len());
+    s.split_at(end)
+}
+
+fn alphanumeric(l: &str, r: &str) -> Ordering {
+    // utf-8 bytes order the same way as the code points they encode,
+    // and digits are always single bytes, so bytes are compared directly
+    let (mut l, mut r) = (l.as_bytes(), r.as_bytes());
+    let mut zeros = Ordering::Equal;
+    loop {
+        match (l.first(), r.first()) {
+            (None, None) => return zeros,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) if a.is_ascii_digit() && b.is_ascii_digit() => {
+                let (l_run, l_rest) = split_digits(l);
+                let (r_run, r_rest) = split_digits(r);
+                // compare without parsing so arbitrarily long runs cannot overflow
+                let l_num = &l_run[l_run.iter().take_while(|&&d| d == b'0').count()..];
+                let r_num = &r_run[r_run.iter().take_while(|&&d| d == b'0').count()..];
+                let ord = l_num.len().cmp(&r_num.len()).then_with(|| l_num.cmp(r_num));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                if zeros == Ordering::Equal {
+                    zeros = l_run.len().cmp(&r_run.len());
+                }
+                l = l_rest;
+                r = r_rest;
+            }
+            (Some(a), Some(b)) => {
+                if a != b {
+                    return a.cmp(b);
+                }
+                l = &l[1..];
+                r = &r[1..];
+            }
+        }
+    }
+}
+
+/// Orders strings ignoring the case of ASCII letters.
+/// Strings that differ only in ASCII case are equal,
+/// and inserting one into a set or map that holds
+/// another replaces it, so the last inserted spelling
+/// is the one kept.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::AsciiCaseInsensitive;
+/// use rb_tree::RBMapWithCmp;
+///
+/// let mut m = RBMapWithCmp::new(AsciiCaseInsensitive);
+/// m.insert("Hello".to_string(), 1);
+/// m.insert("HELLO".to_string(), 2);
+/// m.insert("apple".to_string(), 3);
+/// assert_eq!(m.len(), 2);
+/// assert_eq!(m.get(&"hello".to_string()), Some(&2));
+/// assert_eq!(m.peek_pair(), Some((&"apple".to_string(), &3)));
+/// assert_eq!(m.peek_pair_back(), Some((&"HELLO".to_string(), &2)));
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct AsciiCaseInsensitive;
+
+impl<T: AsRef<str>> Comparator<T> for AsciiCaseInsensitive {
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        Box::new(|l: &T, r: &T| {
+            let l = l.as_ref().bytes().map(|b| b.to_ascii_lowercase());
+            l.cmp(r.as_ref().bytes().map(|b| b.to_ascii_lowercase()))
+        })
+    }
+}
+
+/// Orders strings by their Unicode simple case folding,
+/// which maps each character to a single character, so
+/// that "ſ", "S" and "s" or "ς", "Σ" and "σ" are equal.
+/// Characters that only fold to several characters,
+/// such as "ß", are left as they are.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::CaseInsensitive;
+/// use rb_tree::RBSet;
+///
+/// let mut s = RBSet::new(CaseInsensitive);
+/// s.insert("ΣΟΦΟΣ");
+/// assert!(s.contains(&"σοφος"));
+/// assert!(s.contains(&"σοφοσ"));
+/// assert!(!s.contains(&"σοφ"));
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CaseInsensitive;
+
+impl<T: AsRef<str>> Comparator<T> for CaseInsensitive {
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        Box::new(|l: &T, r: &T| {
+            let l = l.as_ref().chars().map(fold);
+            l.cmp(r.as_ref().chars().map(fold))
+        })
+    }
+}
+
+// returns the only item of iter, if it has exactly one
+fn single<I: Iterator<Item = char>>(mut iter: I) -> Option<char> {
+    match (iter.next(), iter.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+// simple case folding: going through the upper case form
+// first joins up variants such as final sigma and long s,
+// and only single character mappings are applied
+fn fold(c: char) -> char {
+    let upper = single(c.to_uppercase()).unwrap_or(c);
+    single(upper.to_lowercase()).unwrap_or(upper)
+}
+
+/// Orders values lexicographically by their raw bytes,
+/// as used for binary keys. Strings are ordered by
+/// their UTF-8 encoding, which matches their code points.
+/// # Example:
+/// ```
+/// use rb_tree::cmp::Bytewise;
+/// use rb_tree::RBMapWithCmp;
+///
+/// let mut m = RBMapWithCmp::new(Bytewise);
+/// m.insert(vec![0xff], "high");
+/// m.insert(vec![0x00, 0xff], "low");
+/// m.insert(vec![0x00], "prefix");
+/// assert_eq!(m.values().collect::<Vec<_>>(), [&"prefix", &"low", &"high"]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Bytewise;
+
+impl<T: AsRef<[u8]>> Comparator<T> for Bytewise {
+    fn cmp(&self) -> Box<dyn Fn(&T, &T) -> Ordering> {
+        Box::new(|l: &T, r: &T| l.as_ref().cmp(r.as_ref()))
+    }
+}
@@ -0,0 +1,107 @@
+use crate::cmp::{Alphanumeric, AsciiCaseInsensitive, Bytewise, CaseInsensitive};
+use crate::{Comparator, RBMapWithCmp, RBSet};
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+
+fn check<C: Comparator<&'static str>>(c: C, cases: &[(&'static str, &'static str, Ordering)]) {
+    let cmp = c.cmp();
+    for (l, r, expected) in cases {
+        assert_eq!(cmp(l, r), *expected, "{:?} vs {:?}", l, r);
+        assert_eq!(cmp(r, l), expected.reverse(), "{:?} vs {:?}", r, l);
+    }
+}
+
+#[test]
+fn test_alphanumeric() {
+    check(
+        Alphanumeric,
+        &[
+            ("file2", "file10", Less),
+            ("file10", "file10", Equal),
+            ("", "", Equal),
+            ("", "0", Less),
+            ("a", "a1", Less),
+            ("1", "01", Less),
+            ("01", "001", Less),
+            ("001", "2", Less),
+            ("01b", "1a", Greater),
+            ("x01y1", "x1y01", Greater),
+            ("0", "00", Less),
+            ("9", "10", Less),
+            ("a9b", "a10a", Less),
+            (
+                "123456789012345678901234567890",
+                "99999999999999999999",
+                Greater,
+            ),
+            ("v1.10", "v1.9", Greater),
+            ("File2", "file1", Less),
+            ("é1", "e2", Greater),
+        ],
+    );
+
+    let mut names: Vec<&str> = vec!["img12", "img10", "IMG3", "img2", "img02", "img1"];
+    let cmp = Comparator::<&str>::cmp(&Alphanumeric);
+    names.sort_by(|l, r| cmp(l, r));
+    assert_eq!(names, ["IMG3", "img1", "img2", "img02", "img10", "img12"]);
+}
+
+#[test]
+fn test_ascii_case_insensitive() {
+    check(
+        AsciiCaseInsensitive,
+        &[
+            ("Hello", "hELLO", Equal),
+            ("apple", "Banana", Less),
+            ("Zebra", "apple", Greater),
+            ("abc", "ABCD", Less),
+            ("_", "a", Less),
+            ("É", "é", Less),
+            ("", "", Equal),
+        ],
+    );
+}
+
+#[test]
+fn test_ascii_case_insensitive_keeps_last_spelling() {
+    let mut s = RBSet::new(AsciiCaseInsensitive);
+    assert_eq!(s.insert("Hello"), None);
+    assert_eq!(s.insert("HELLO"), Some("Hello"));
+    assert_eq!(s.insert("hello"), Some("HELLO"));
+    assert_eq!(s.ordered(), [&"hello"]);
+
+    let mut m = RBMapWithCmp::new(AsciiCaseInsensitive);
+    m.insert("Hello", 1);
+    assert_eq!(m.insert("HELLO", 2), Some(("Hello", 1)));
+    assert_eq!(m.peek_pair(), Some((&"HELLO", &2)));
+}
+
+#[test]
+fn test_case_insensitive() {
+    check(
+        CaseInsensitive,
+        &[
+            ("Hello", "hELLO", Equal),
+            ("É", "é", Equal),
+            ("ΣΟΦΟΣ", "σοφος", Equal),
+            ("ſ", "S", Equal),
+            ("\u{212a}", "k", Equal),
+            ("ß", "ss", Greater),
+            ("ß", "SS", Greater),
+            ("İ", "i", Greater),
+            ("apple", "Banana", Less),
+        ],
+    );
+}
+
+#[test]
+fn test_bytewise() {
+    let cmp = Comparator::<Vec<u8>>::cmp(&Bytewise);
+    assert_eq!(cmp(&vec![], &vec![0]), Less);
+    assert_eq!(cmp(&vec![0x7f], &vec![0x80]), Less);
+    assert_eq!(cmp(&vec![0xff], &vec![0x00, 0xff]), Greater);
+    assert_eq!(cmp(&vec![1, 2], &vec![1, 2]), Equal);
+    check(
+        Bytewise,
+        &[("B", "a", Less), ("a", "é", Less), ("ab", "a", Greater)],
+    );
+}
@@ -0,0 +1,395 @@
+use crate::sharednode::{self, Link, SharedNode};
+use crate::{ConcurrentRBMap, RBMap, SharedRBMap};
+use std::fmt::{Debug, Formatter, Result};
+use std::iter::FusedIterator;
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// the map is left valid when a thread panics while holding
+// one of these locks, so poisoning can be ignored
+fn lock<T>(lock: &Mutex<T>) -> MutexGuard<'_, T> {
+    lock.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<K: PartialOrd, V> ConcurrentRBMap<K, V> {
+    /// Creates and returns a new, empty ConcurrentRBMap.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ConcurrentRBMap;
+    /// use std::sync::Arc;
+    /// use std::thread;
+    ///
+    /// let map = Arc::new(ConcurrentRBMap::new());
+    /// let writers: Vec<_> = (0..4)
+    ///     .map(|t| {
+    ///         let map = Arc::clone(&map);
+    ///         thread::spawn(move || map.insert(t, t * 10))
+    ///     })
+    ///     .collect();
+    /// for w in writers {
+    ///     w.join().unwrap();
+    /// }
+    /// assert_eq!(map.len(), 4);
+    /// assert_eq!(map.get(&2), Some(20));
+    /// ```
+    pub fn new() -> ConcurrentRBMap<K, V> {
+        ConcurrentRBMap::from(RBMap::new())
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        read(&self.current).len()
+    }
+
+    /// Returns true if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        read(&self.current).is_empty()
+    }
+
+    /// Returns true if the map contains an entry for key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        read(&self.current).contains_key(key)
+    }
+
+    /// Returns a snapshot of the map as it is now. The
+    /// snapshot is not affected by later writes, so it can
+    /// be iterated over at leisure without holding up
+    /// readers or writers. Taking one is O(1), as the
+    /// snapshot shares its nodes with the map.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ConcurrentRBMap;
+    ///
+    /// let map = ConcurrentRBMap::new();
+    /// map.insert(1, 'a');
+    /// let snapshot = map.snapshot();
+    /// map.insert(2, 'b');
+    /// assert_eq!(snapshot.len(), 1);
+    /// assert_eq!(map.snapshot().keys().collect::<Vec<_>>(), [&1, &2]);
+    /// ```
+    pub fn snapshot(&self) -> SharedRBMap<K, V> {
+        read(&self.current).clone()
+    }
+}
+
+impl<K: PartialOrd, V: Clone> ConcurrentRBMap<K, V> {
+    /// Returns a copy of the value associated with
+    /// key, or None if the map does not contain it.
+    pub fn get(&self, key: &K) -> Option<V> {
+        read(&self.current).get(key).cloned()
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone> ConcurrentRBMap<K, V> {
+    /// Applies f to the map as one atomic change: readers
+    /// and snapshots see either none or all of it. Writers
+    /// are applied one at a time.
+    ///
+    /// f is applied to a copy of the map, which then replaces
+    /// it, so readers are only held up while the two are
+    /// swapped, never while f runs. The copy shares its nodes
+    /// with the map, and copies only those on the way down to
+    /// each entry f changes, so each change costs O(log n).
+    /// If f panics the copy is thrown away and the map is left
+    /// as it was.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ConcurrentRBMap;
+    ///
+    /// let accounts = ConcurrentRBMap::new();
+    /// accounts.insert("alice", 10);
+    /// accounts.insert("bob", 0);
+    /// // readers never see the money in both or neither account
+    /// accounts.write(|map| {
+    ///     *map.get_mut(&"alice").unwrap() -= 5;
+    ///     *map.get_mut(&"bob").unwrap() += 5;
+    /// });
+    /// assert_eq!(accounts.get(&"bob"), Some(5));
+    /// ```
+    pub fn write<R, F: FnOnce(&mut SharedRBMap<K, V>) -> R>(&self, f: F) -> R {
+        // only writers replace the current map, and they take
+        // turns, so it cannot change before the swap below
+        let _writer = lock(&self.writer);
+        let mut next = read(&self.current).clone();
+        let res = f(&mut next);
+        *write(&self.current) = next;
+        res
+    }
+
+    /// Inserts a value to associate with the given key into
+    /// the map, returning the previously-stored value, if it
+    /// existed.
+    pub fn insert(&self, key: K, val: V) -> Option<V> {
+        self.write(|map| map.insert(key, val))
+    }
+
+    /// Removes the entry for key, returning its
+    /// value, or None if the map did not contain it.
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.write(|map| map.remove(key))
+    }
+
+    /// Removes every entry from the map.
+    pub fn clear(&self) {
+        let _writer = lock(&self.writer);
+        *write(&self.current) = SharedRBMap::new();
+    }
+}
+
+impl<K: PartialOrd, V> Default for ConcurrentRBMap<K, V> {
+    fn default() -> Self {
+        ConcurrentRBMap::new()
+    }
+}
+
+impl<K: PartialOrd, V> From<RBMap<K, V>> for ConcurrentRBMap<K, V> {
+    fn from(map: RBMap<K, V>) -> Self {
+        ConcurrentRBMap {
+            current: RwLock::new(map.into()),
+            writer: Mutex::new(()),
+        }
+    }
+}
+
+impl<K: PartialOrd + Debug, V: Debug> Debug for ConcurrentRBMap<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map().entries(self.snapshot().iter()).finish()
+    }
+}
+
+impl<K: PartialOrd, V> SharedRBMap<K, V> {
+    /// Creates and returns a new, empty SharedRBMap.
+    pub fn new() -> SharedRBMap<K, V> {
+        SharedRBMap {
+            root: None,
+            contained: 0,
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.contained
+    }
+
+    /// Returns true if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.contained == 0
+    }
+
+    /// Returns true if the map contains an entry for key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        sharednode::get(&self.root, key).is_some()
+    }
+
+    /// Returns a reference to the value associated
+    /// with key, or None if the map does not contain it.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        sharednode::get(&self.root, key).map(|n| &n.val)
+    }
+
+    /// An iterator that visits each entry in order of key.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ConcurrentRBMap;
+    ///
+    /// let map = ConcurrentRBMap::new();
+    /// map.insert(2, 'b');
+    /// map.insert(1, 'a');
+    /// let snapshot = map.snapshot();
+    /// assert_eq!(snapshot.iter().collect::<Vec<_>>(), [(&1, &'a'), (&2, &'b')]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            ordered: Vec::new(),
+            remaining: self.contained,
+        };
+        iter.push_left(&self.root);
+        iter
+    }
+
+    /// An iterator that visits each key in order.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { iter: self.iter() }
+    }
+
+    /// An iterator that visits each value in order of key.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { iter: self.iter() }
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone> SharedRBMap<K, V> {
+    /// Returns a mutable reference to the value associated
+    /// with key, or None if the map does not contain it.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        sharednode::get_mut(&mut self.root, key)
+    }
+
+    /// Inserts a value to associate with the given key into
+    /// the map, returning the previously-stored value, if it
+    /// existed.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ConcurrentRBMap;
+    ///
+    /// let map = ConcurrentRBMap::new();
+    /// map.insert(1, 'a');
+    /// let snapshot = map.snapshot();
+    /// let mut copy = snapshot.clone();
+    /// assert_eq!(copy.insert(1, 'b'), Some('a'));
+    /// assert_eq!(copy.get(&1), Some(&'b'));
+    /// assert_eq!(snapshot.get(&1), Some(&'a'));
+    /// ```
+    pub fn insert(&mut self, key: K, val: V) -> Option<V> {
+        let old = sharednode::insert(&mut self.root, key, val);
+        if old.is_none() {
+            self.contained += 1;
+        }
+        old
+    }
+
+    /// Removes the entry for key, returning its
+    /// value, or None if the map did not contain it.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (_, val) = sharednode::remove(&mut self.root, key)?;
+        self.contained -= 1;
+        Some(val)
+    }
+
+    /// Removes every entry from the map.
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.contained = 0;
+    }
+}
+
+impl<K: PartialOrd, V> Clone for SharedRBMap<K, V> {
+    fn clone(&self) -> Self {
+        SharedRBMap {
+            root: self.root.clone(),
+            contained: self.contained,
+        }
+    }
+}
+
+impl<K: PartialOrd, V> Default for SharedRBMap<K, V> {
+    fn default() -> Self {
+        SharedRBMap::new()
+    }
+}
+
+impl<K: PartialOrd, V> From<RBMap<K, V>> for SharedRBMap<K, V> {
+    fn from(map: RBMap<K, V>) -> Self {
+        let contained = map.len();
+        SharedRBMap {
+            root: sharednode::from_sorted(map.into_iter().collect()),
+            contained,
+        }
+    }
+}
+
+impl<K: PartialOrd + Debug, V: Debug> Debug for SharedRBMap<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K: PartialOrd, V> IntoIterator for &'a SharedRBMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a SharedRBMap,
+/// in order of key.
+pub struct Iter<'a, K, V> {
+    // the nodes still to visit along with their
+    // right subtrees, the next on top
+    ordered: Vec<&'a SharedNode<K, V>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.ordered.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.ordered.pop()?;
+        self.push_left(&node.right);
+        self.remaining -= 1;
+        Some((&node.key, &node.val))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+/// An iterator over the keys of a SharedRBMap, in order.
+pub struct Keys<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Keys<'a, K, V> {}
+
+/// An iterator over the values of a SharedRBMap,
+/// in order of key.
+pub struct Values<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<&'a V> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
@@ -0,0 +1,260 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::sharednode::{black_height, Link};
+use crate::{ConcurrentRBMap, RBMap, SharedRBMap};
+
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+const ACCOUNTS: u32 = 20;
+const BALANCE: i64 = 100;
+
+#[test]
+fn test_snapshots_see_whole_writes() {
+    let map: Arc<ConcurrentRBMap<u32, i64>> = Arc::new(
+        (0..ACCOUNTS)
+            .map(|a| (a, BALANCE))
+            .collect::<RBMap<_, _>>()
+            .into(),
+    );
+    let mut threads = Vec::new();
+    for t in 0..4 {
+        let map = Arc::clone(&map);
+        threads.push(thread::spawn(move || {
+            let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(t);
+            for _ in 0..2000 {
+                let from = rng.gen_range(0..ACCOUNTS);
+                let to = rng.gen_range(0..ACCOUNTS);
+                let amount = rng.gen_range(0..10);
+                map.write(|m| {
+                    *m.get_mut(&from).unwrap() -= amount;
+                    *m.get_mut(&to).unwrap() += amount;
+                });
+            }
+        }));
+    }
+    for _ in 0..4 {
+        let map = Arc::clone(&map);
+        threads.push(thread::spawn(move || {
+            let mut held = Vec::new();
+            for i in 0..2000 {
+                let snapshot = map.snapshot();
+                assert_eq!(snapshot.len(), ACCOUNTS as usize);
+                assert_eq!(snapshot.values().sum::<i64>(), BALANCE * ACCOUNTS as i64);
+                assert!(map.get(&(i % ACCOUNTS)).is_some());
+                // keep some snapshots alive across writes so
+                // writers have to copy the map
+                if i % 100 == 0 {
+                    held.push(snapshot);
+                }
+            }
+            for snapshot in held {
+                assert_eq!(snapshot.values().sum::<i64>(), BALANCE * ACCOUNTS as i64);
+            }
+        }));
+    }
+    for t in threads {
+        t.join().unwrap();
+    }
+    assert_eq!(
+        map.snapshot().values().sum::<i64>(),
+        BALANCE * ACCOUNTS as i64
+    );
+}
+
+#[test]
+fn test_concurrent_inserts_and_removes() {
+    let map = Arc::new(ConcurrentRBMap::new());
+    let mut threads = Vec::new();
+    for t in 0..8u32 {
+        let map = Arc::clone(&map);
+        threads.push(thread::spawn(move || {
+            for k in (t * 1000)..(t + 1) * 1000 {
+                assert_eq!(map.insert(k, k * 2), None);
+            }
+            for k in ((t * 1000)..(t + 1) * 1000).step_by(2) {
+                assert_eq!(map.remove(&k), Some(k * 2));
+            }
+        }));
+    }
+    for _ in 0..4 {
+        let map = Arc::clone(&map);
+        threads.push(thread::spawn(move || {
+            for _ in 0..200 {
+                let snapshot = map.snapshot();
+                assert!(black_height(&snapshot.root).is_some());
+                let keys: Vec<_> = snapshot.keys().collect();
+                assert!(keys.windows(2).all(|w| w[0] < w[1]));
+                assert!(snapshot.iter().all(|(k, v)| *v == k * 2));
+            }
+        }));
+    }
+    for t in threads {
+        t.join().unwrap();
+    }
+    assert_eq!(map.len(), 4000);
+    assert!(map.snapshot().keys().copied().eq((1..8000).step_by(2)));
+    map.clear();
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_readers_not_blocked_by_writer() {
+    let map = Arc::new(ConcurrentRBMap::new());
+    map.insert(1, 'a');
+    let (entered_tx, entered_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    let writer = {
+        let map = Arc::clone(&map);
+        thread::spawn(move || {
+            map.write(|m| {
+                m.insert(1, 'b');
+                entered_tx.send(()).unwrap();
+                // stay inside write until the reader is done
+                release_rx.recv().unwrap();
+                m.insert(2, 'c');
+            })
+        })
+    };
+    entered_rx.recv().unwrap();
+
+    let (read_tx, read_rx) = mpsc::channel();
+    let reader = {
+        let map = Arc::clone(&map);
+        thread::spawn(move || {
+            let seen = (map.get(&1), map.len(), map.snapshot().contains_key(&2));
+            read_tx.send(seen).unwrap();
+        })
+    };
+    let seen = read_rx.recv_timeout(Duration::from_secs(10));
+    release_tx.send(()).unwrap();
+    assert_eq!(seen, Ok((Some('a'), 1, false)));
+
+    reader.join().unwrap();
+    writer.join().unwrap();
+    assert_eq!(map.get(&1), Some('b'));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_panicking_write_publishes_nothing() {
+    let map = ConcurrentRBMap::new();
+    (0..10).for_each(|k| {
+        map.insert(k, k);
+    });
+    let snapshot = map.snapshot();
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        map.write(|m| {
+            m.insert(10, 10);
+            m.remove(&0);
+            *m.get_mut(&5).unwrap() = 50;
+            panic!("injected write panic");
+        })
+    }));
+    assert!(res.is_err());
+    assert!(map.snapshot().iter().eq(snapshot.iter()));
+    drop(snapshot);
+
+    // the map can still be written to afterwards
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        map.write(|m| {
+            m.clear();
+            panic!("injected write panic");
+        })
+    }));
+    assert!(res.is_err());
+    assert_eq!(map.len(), 10);
+    assert_eq!(map.insert(5, 50), Some(5));
+    assert_eq!(map.get(&5), Some(50));
+}
+
+// the addresses of the nodes of a map
+fn nodes<K: PartialOrd, V>(map: &SharedRBMap<K, V>) -> HashSet<usize> {
+    fn walk<K, V>(link: &Link<K, V>, found: &mut HashSet<usize>) {
+        if let Some(node) = link {
+            found.insert(Arc::as_ptr(node) as usize);
+            walk(&node.left, found);
+            walk(&node.right, found);
+        }
+    }
+    let mut found = HashSet::new();
+    walk(&map.root, &mut found);
+    found
+}
+
+#[test]
+fn test_writes_copy_only_a_path() {
+    let map: ConcurrentRBMap<u32, u32> = (0..1000)
+        .map(|k| (k * 2, k))
+        .collect::<RBMap<_, _>>()
+        .into();
+    let before = map.snapshot();
+    let height = 2 * (black_height(&before.root).unwrap() + 1);
+    // the nodes a write made, rather than shared with the map before it
+    let copied = |write: &dyn Fn()| {
+        let last = map.snapshot();
+        write();
+        nodes(&map.snapshot()).difference(&nodes(&last)).count()
+    };
+
+    assert!(copied(&|| assert_eq!(map.insert(501, 0), None)) <= height + 1);
+    assert!(copied(&|| assert_eq!(map.remove(&501), Some(0))) <= height);
+    assert!(copied(&|| map.write(|m| *m.get_mut(&1000).unwrap() += 1)) <= height);
+    // a miss copies nothing
+    assert_eq!(copied(&|| assert_eq!(map.remove(&1), None)), 0);
+    assert_eq!(
+        copied(&|| map.write(|m| assert!(m.get_mut(&1).is_none()))),
+        0
+    );
+    assert_eq!(map.get(&1000), Some(501));
+
+    assert_eq!(before.len(), 1000);
+    assert!(before.iter().all(|(k, v)| *k == v * 2));
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(before.len(), 1000);
+}
+
+#[test]
+fn test_shared_map_matches_btreemap() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(7);
+    let mut map = SharedRBMap::new();
+    let mut expected = BTreeMap::new();
+    let mut held = Vec::new();
+    for i in 0..5000 {
+        let key = rng.gen_range(0..500u32);
+        if rng.gen_bool(0.6) {
+            assert_eq!(map.insert(key, i), expected.insert(key, i));
+        } else {
+            assert_eq!(map.remove(&key), expected.remove(&key));
+        }
+        assert!(black_height(&map.root).is_some());
+        assert_eq!(map.len(), expected.len());
+        if i % 250 == 0 {
+            held.push((map.clone(), expected.clone()));
+        }
+    }
+    assert!(map.iter().eq(expected.iter()));
+    // earlier versions are left as they were
+    for (map, expected) in held {
+        assert!(black_height(&map.root).is_some());
+        assert!(map.iter().eq(expected.iter()));
+        assert_eq!(map.iter().len(), expected.len());
+    }
+}
+
+#[test]
+fn test_shared_map_from_rbmap() {
+    for n in 0..200u32 {
+        let map: SharedRBMap<u32, u32> = (0..n).map(|k| (k, k)).collect::<RBMap<_, _>>().into();
+        assert!(black_height(&map.root).is_some());
+        assert_eq!(map.len(), n as usize);
+        assert!(map.keys().copied().eq(0..n));
+        assert!(map.values().copied().eq(0..n));
+    }
+}
@@ -0,0 +1,273 @@
+use crate::clock::{Clock, SystemClock};
+use crate::rbmap;
+use crate::{ExpiringMap, RBMap, RBQueue};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter, Result};
+use std::iter::FusedIterator;
+use std::time::{Duration, Instant};
+
+// deadlines are queued alongside their keys, soonest first
+pub(crate) type ByDeadline<K> = fn(&(Instant, K), &(Instant, K)) -> Ordering;
+
+fn by_deadline<K: PartialOrd>(l: &(Instant, K), r: &(Instant, K)) -> Ordering {
+    l.partial_cmp(r).unwrap()
+}
+
+pub(crate) struct Expiring<V> {
+    value: V,
+    deadline: Instant,
+    ttl: Duration,
+}
+
+impl<K: PartialOrd + Clone, V> ExpiringMap<K, V, SystemClock> {
+    /// Creates and returns a new, empty ExpiringMap
+    /// that tells the time with the system clock.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ExpiringMap;
+    /// use std::time::Duration;
+    ///
+    /// let mut sessions = ExpiringMap::new();
+    /// sessions.insert("alice", 1, Duration::from_secs(60));
+    /// assert_eq!(sessions.get(&"alice"), Some(&1));
+    /// sessions.insert("bob", 2, Duration::ZERO);
+    /// assert_eq!(sessions.get(&"bob"), None);
+    /// ```
+    pub fn new() -> ExpiringMap<K, V, SystemClock> {
+        ExpiringMap::with_clock(SystemClock)
+    }
+}
+
+impl<K: PartialOrd + Clone, V, C: Clock> ExpiringMap<K, V, C> {
+    /// Creates and returns a new, empty ExpiringMap
+    /// that tells the time with the given clock.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ExpiringMap;
+    /// use rb_tree::clock::ManualClock;
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut m = ExpiringMap::with_clock(&clock);
+    /// m.insert(1, 'a', Duration::from_secs(10));
+    /// clock.advance(Duration::from_secs(9));
+    /// assert_eq!(m.get(&1), Some(&'a'));
+    /// clock.advance(Duration::from_secs(1));
+    /// assert_eq!(m.get(&1), None);
+    /// ```
+    pub fn with_clock(clock: C) -> ExpiringMap<K, V, C> {
+        ExpiringMap {
+            entries: RBMap::new(),
+            deadlines: RBQueue::new(by_deadline as ByDeadline<K>),
+            clock,
+        }
+    }
+
+    /// Returns the clock the map tells the time with.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Associates value with key until ttl has passed,
+    /// returning the value previously associated with
+    /// key if it had not yet expired. Expired entries
+    /// are purged first.
+    /// # Panics:
+    /// Panics if the deadline cannot be represented
+    /// as an Instant.
+    pub fn insert(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        let now = self.clock.now();
+        self.purge_before(now);
+        let deadline = now + ttl;
+        let previous = self.entries.insert(
+            key.clone(),
+            Expiring {
+                value,
+                deadline,
+                ttl,
+            },
+        );
+        // the old deadline goes first, in case it equals the new one
+        let previous = previous.map(|(key, old)| {
+            self.deadlines.remove(&(old.deadline, key));
+            old.value
+        });
+        self.deadlines.insert((deadline, key));
+        previous
+    }
+
+    /// Returns the value associated with key,
+    /// or None if there is none or it has expired.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let now = self.clock.now();
+        self.entries
+            .get(key)
+            .filter(|e| e.deadline > now)
+            .map(|e| &e.value)
+    }
+
+    /// Returns a mutable reference to the value associated
+    /// with key, or None if there is none or it has expired.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let now = self.clock.now();
+        self.entries
+            .get_mut(key)
+            .filter(|e| e.deadline > now)
+            .map(|e| &mut e.value)
+    }
+
+    /// Returns true if the map holds an unexpired value for key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns how long the value associated with key has
+    /// left before it expires, or None if there is none or
+    /// it has expired.
+    pub fn time_to_live(&self, key: &K) -> Option<Duration> {
+        let now = self.clock.now();
+        self.entries
+            .get(key)
+            .filter(|e| e.deadline > now)
+            .map(|e| e.deadline - now)
+    }
+
+    /// Restarts the time to live of the value associated
+    /// with key, as given when it was inserted. Returns
+    /// false, doing nothing, if there is no value for key
+    /// or it has already expired.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ExpiringMap;
+    /// use rb_tree::clock::ManualClock;
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut m = ExpiringMap::with_clock(&clock);
+    /// m.insert("session", 7, Duration::from_secs(30));
+    /// clock.advance(Duration::from_secs(20));
+    /// assert!(m.refresh(&"session"));
+    /// clock.advance(Duration::from_secs(20));
+    /// assert_eq!(m.get(&"session"), Some(&7));
+    /// clock.advance(Duration::from_secs(10));
+    /// assert!(!m.refresh(&"session"));
+    /// ```
+    pub fn refresh(&mut self, key: &K) -> bool {
+        let now = self.clock.now();
+        let entry = match self.entries.get_mut(key) {
+            Some(entry) if entry.deadline > now => entry,
+            _ => return false,
+        };
+        let old = std::mem::replace(&mut entry.deadline, now + entry.ttl);
+        self.deadlines.remove(&(old, key.clone()));
+        self.deadlines.insert((entry.deadline, key.clone()));
+        true
+    }
+
+    /// Removes the entry for key, returning its value if
+    /// it had not yet expired.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let now = self.clock.now();
+        let entry = self.entries.remove(key)?;
+        self.deadlines.remove(&(entry.deadline, key.clone()));
+        if entry.deadline > now {
+            Some(entry.value)
+        } else {
+            None
+        }
+    }
+
+    /// Removes every expired entry, returning how many
+    /// there were. Expired entries are never returned by
+    /// the map, so this is only needed to free their
+    /// memory sooner than the next insertion would.
+    /// # Example:
+    /// ```
+    /// use rb_tree::ExpiringMap;
+    /// use rb_tree::clock::ManualClock;
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut m = ExpiringMap::with_clock(&clock);
+    /// for i in 0..10 {
+    ///     m.insert(i, i, Duration::from_secs(i + 1));
+    /// }
+    /// clock.advance(Duration::from_secs(5));
+    /// assert_eq!(m.purge_expired(), 5);
+    /// assert_eq!(m.len(), 5);
+    /// ```
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        self.purge_before(now)
+    }
+
+    fn purge_before(&mut self, now: Instant) -> usize {
+        let mut purged = 0;
+        while self.deadlines.peek().is_some_and(|(d, _)| *d <= now) {
+            let (_, key) = self.deadlines.pop().unwrap();
+            self.entries.remove(&key);
+            purged += 1;
+        }
+        purged
+    }
+
+    /// Returns the number of unexpired entries in the map.
+    /// This takes time proportional to the number of
+    /// expired entries not yet purged.
+    pub fn len(&self) -> usize {
+        let now = self.clock.now();
+        let expired = self.deadlines.iter().take_while(|(d, _)| *d <= now).count();
+        self.entries.len() - expired
+    }
+
+    /// Returns true if the map holds no unexpired entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes every entry from the map.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.deadlines.clear();
+    }
+
+    /// Returns an iterator over the unexpired
+    /// entries, ordered by their keys.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            now: self.clock.now(),
+            iter: self.entries.iter(),
+        }
+    }
+}
+
+impl<K: PartialOrd + Clone, V> Default for ExpiringMap<K, V, SystemClock> {
+    fn default() -> Self {
+        ExpiringMap::new()
+    }
+}
+
+impl<K: PartialOrd + Clone + Debug, V: Debug, C: Clock> Debug for ExpiringMap<K, V, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the unexpired entries of an ExpiringMap.
+pub struct Iter<'a, K: PartialOrd, V> {
+    now: Instant,
+    iter: rbmap::Iter<'a, K, Expiring<V>>,
+}
+
+impl<'a, K: PartialOrd, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let now = self.now;
+        self.iter
+            .find(|(_, e)| e.deadline > now)
+            .map(|(k, e)| (k, &e.value))
+    }
+}
+
+impl<'a, K: PartialOrd, V> FusedIterator for Iter<'a, K, V> {}
@@ -0,0 +1,86 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::clock::ManualClock;
+use crate::ExpiringMap;
+
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+const KEYS: u32 = 50;
+
+#[test]
+fn test_matches_model() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(44);
+    let clock = ManualClock::new();
+    let mut m = ExpiringMap::with_clock(&clock);
+    // key -> (value, seconds left, ttl)
+    let mut model: BTreeMap<u32, (u32, u64, u64)> = BTreeMap::new();
+    for i in 0..5000 {
+        let key = rng.gen_range(0..KEYS);
+        match rng.gen_range(0..6) {
+            0 | 1 => {
+                let ttl = rng.gen_range(0..20);
+                let previous = model.insert(key, (i, ttl, ttl)).map(|(v, _, _)| v);
+                assert_eq!(m.insert(key, i, Duration::from_secs(ttl)), previous);
+                if ttl == 0 {
+                    model.remove(&key);
+                }
+            }
+            2 => {
+                let refreshed = match model.get_mut(&key) {
+                    Some((_, left, ttl)) => {
+                        *left = *ttl;
+                        true
+                    }
+                    None => false,
+                };
+                assert_eq!(m.refresh(&key), refreshed);
+            }
+            3 => assert_eq!(m.remove(&key), model.remove(&key).map(|(v, _, _)| v)),
+            4 => {
+                m.purge_expired();
+                assert_eq!(m.entries.len(), model.len());
+                assert_eq!(m.deadlines.len(), model.len());
+            }
+            _ => {
+                let secs = rng.gen_range(0..3);
+                clock.advance(Duration::from_secs(secs));
+                for (_, left, _) in model.values_mut() {
+                    *left = left.saturating_sub(secs);
+                }
+                model.retain(|_, (_, left, _)| *left > 0);
+            }
+        }
+        // entries expired by advancing the clock are left for insert to purge
+        for k in 0..KEYS {
+            let expected = model.get(&k);
+            assert_eq!(m.get(&k), expected.map(|(v, _, _)| v));
+            let left = expected.map(|(_, left, _)| Duration::from_secs(*left));
+            assert_eq!(m.time_to_live(&k), left);
+        }
+        assert_eq!(m.len(), model.len());
+        let live: Vec<_> = model.iter().map(|(k, (v, _, _))| (k, v)).collect();
+        assert_eq!(m.iter().collect::<Vec<_>>(), live);
+    }
+}
+
+#[test]
+fn test_lazy_purge() {
+    let clock = ManualClock::new();
+    let mut m = ExpiringMap::with_clock(&clock);
+    for i in 0..100 {
+        m.insert(i, i, Duration::from_secs(1));
+    }
+    clock.advance(Duration::from_secs(1));
+    assert!(m.is_empty());
+    assert_eq!(m.entries.len(), 100);
+    // inserting purges everything that has expired
+    m.insert(100, 100, Duration::from_secs(1));
+    assert_eq!(m.entries.len(), 1);
+    assert_eq!(m.deadlines.len(), 1);
+    assert_eq!(m.remove(&0), None);
+    m.clear();
+    assert!(m.is_empty());
+}
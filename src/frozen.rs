@@ -0,0 +1,610 @@
+//! Read-only trees laid out in a flat array in Eytzinger
+//! (breadth-first) order, so that searches walk forwards
+//! through memory without following pointers and without
+//! unpredictable branches.
+//!
+//! Trees of plain-old-data values can be saved as raw bytes
+//! with `as_bytes` and viewed again, without copying, with
+//! `from_bytes`. The bytes are in the platform's native
+//! endianness.
+
+#[cfg(feature = "map")]
+use crate::RBMap;
+#[cfg(feature = "set")]
+use crate::RBTree;
+use crate::{FrozenRBMap, FrozenRBTree};
+use std::cmp::Ordering::{self, Equal, Less};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter, Result};
+use std::iter::FusedIterator;
+use std::ops::{Bound, Deref, RangeBounds};
+
+// the values of a frozen tree, which may be borrowed from a buffer
+#[derive(Clone)]
+pub(crate) enum Flat<'a, T> {
+    #[cfg(feature = "set")]
+    Owned(Vec<T>),
+    Borrowed(&'a [T]),
+}
+
+impl<'a, T> Deref for Flat<'a, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            #[cfg(feature = "set")]
+            Flat::Owned(values) => values,
+            Flat::Borrowed(values) => values,
+        }
+    }
+}
+
+/// Marks types that can be viewed directly from bytes.
+/// # Safety
+/// Implementors must be `Copy`, contain no padding
+/// bytes and be valid for every possible bit pattern.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! pod {
+    ($($t:ty),*) => {$(
+        unsafe impl Pod for $t {}
+    )*};
+}
+
+pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+/// The reasons a buffer cannot be viewed as a frozen tree.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FrozenError {
+    /// The buffer's length is not a multiple of the value size,
+    /// or the keys and values buffers hold different numbers
+    /// of elements.
+    Length,
+    /// The buffer is not aligned for the value type.
+    Misaligned,
+    /// The values are not in Eytzinger order of
+    /// strictly increasing values.
+    Unordered,
+}
+
+impl Display for FrozenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            FrozenError::Length => write!(f, "buffer length does not fit the value type"),
+            FrozenError::Misaligned => write!(f, "buffer is not aligned for the value type"),
+            FrozenError::Unordered => write!(f, "buffer values are not in frozen tree order"),
+        }
+    }
+}
+
+impl Error for FrozenError {}
+
+// the positions of the nodes of an n node implicit tree in order.
+// node k (counting from 1) has children 2k and 2k + 1
+#[cfg(feature = "set")]
+fn in_order_positions(k: usize, n: usize, positions: &mut Vec<usize>) {
+    if k <= n {
+        in_order_positions(2 * k, n, positions);
+        positions.push(k - 1);
+        in_order_positions(2 * k + 1, n, positions);
+    }
+}
+
+// lays sorted out in Eytzinger order
+#[cfg(feature = "set")]
+fn eytzinger<T>(sorted: Vec<T>) -> Vec<T> {
+    let n = sorted.len();
+    let mut positions = Vec::with_capacity(n);
+    in_order_positions(1, n, &mut positions);
+    let mut slots: Vec<Option<T>> = (0..n).map(|_| None).collect();
+    for (value, pos) in sorted.into_iter().zip(positions) {
+        slots[pos] = Some(value);
+    }
+    slots.into_iter().map(Option::unwrap).collect()
+}
+
+// the nodes below are counted from 1, with 0 meaning none
+
+fn first(n: usize) -> usize {
+    let mut k = if n == 0 { 0 } else { 1 };
+    while k != 0 && 2 * k <= n {
+        k *= 2;
+    }
+    k
+}
+
+fn last(n: usize) -> usize {
+    let mut k = if n == 0 { 0 } else { 1 };
+    while k != 0 && 2 * k < n {
+        k = 2 * k + 1;
+    }
+    k
+}
+
+fn successor(mut k: usize, n: usize) -> usize {
+    if 2 * k < n {
+        k = 2 * k + 1;
+        while 2 * k <= n {
+            k *= 2;
+        }
+        k
+    } else {
+        // climb out of every subtree k is the right child of
+        k >>= k.trailing_ones();
+        k >> 1
+    }
+}
+
+fn predecessor(mut k: usize, n: usize) -> usize {
+    if 2 * k <= n {
+        k *= 2;
+        while 2 * k < n {
+            k = 2 * k + 1;
+        }
+        k
+    } else {
+        k >>= k.trailing_zeros();
+        k >> 1
+    }
+}
+
+// the first node for which before returns false, given that
+// before is true for some prefix of the values in order. the
+// loop has no branch that depends on the values
+fn partition<T, P: Fn(&T) -> bool>(values: &[T], before: P) -> usize {
+    let n = values.len();
+    let mut k = 1;
+    while k <= n {
+        k = 2 * k + before(&values[k - 1]) as usize;
+    }
+    k >> (k.trailing_ones() + 1)
+}
+
+fn cmp<T: PartialOrd>(l: &T, r: &T) -> Ordering {
+    l.partial_cmp(r).unwrap()
+}
+
+// the first node at or after bound
+fn lower<T: PartialOrd>(values: &[T], bound: Bound<&T>) -> usize {
+    match bound {
+        Bound::Included(b) => partition(values, |v| cmp(v, b) == Less),
+        Bound::Excluded(b) => partition(values, |v| cmp(v, b) != Ordering::Greater),
+        Bound::Unbounded => first(values.len()),
+    }
+}
+
+// the last node at or before bound
+fn upper<T: PartialOrd>(values: &[T], bound: Bound<&T>) -> usize {
+    let n = values.len();
+    let after = match bound {
+        Bound::Included(b) => partition(values, |v| cmp(v, b) != Ordering::Greater),
+        Bound::Excluded(b) => partition(values, |v| cmp(v, b) == Less),
+        Bound::Unbounded => return last(n),
+    };
+    if after == 0 {
+        last(n)
+    } else {
+        predecessor(after, n)
+    }
+}
+
+// the nodes from front to back, in order
+#[derive(Clone)]
+struct Cursor {
+    front: usize,
+    back: usize,
+    n: usize,
+}
+
+impl Cursor {
+    fn new<T: PartialOrd, R: RangeBounds<T>>(values: &[T], range: &R) -> Cursor {
+        let front = lower(values, range.start_bound());
+        let back = upper(values, range.end_bound());
+        let empty = front == 0
+            || back == 0
+            || cmp(&values[front - 1], &values[back - 1]) == Ordering::Greater;
+        if empty {
+            Cursor {
+                front: 0,
+                back: 0,
+                n: values.len(),
+            }
+        } else {
+            Cursor {
+                front,
+                back,
+                n: values.len(),
+            }
+        }
+    }
+
+    fn all(n: usize) -> Cursor {
+        Cursor {
+            front: first(n),
+            back: last(n),
+            n,
+        }
+    }
+
+    fn next(&mut self) -> Option<usize> {
+        if self.front == 0 {
+            return None;
+        }
+        let cur = self.front;
+        if cur == self.back {
+            self.front = 0;
+            self.back = 0;
+        } else {
+            self.front = successor(cur, self.n);
+        }
+        Some(cur - 1)
+    }
+
+    fn next_back(&mut self) -> Option<usize> {
+        if self.back == 0 {
+            return None;
+        }
+        let cur = self.back;
+        if cur == self.front {
+            self.front = 0;
+            self.back = 0;
+        } else {
+            self.back = predecessor(cur, self.n);
+        }
+        Some(cur - 1)
+    }
+}
+
+// views bytes as values of a Pod type, checking they are in order.
+// is_multiple_of is too new for the compilers the crate supports
+#[allow(clippy::manual_is_multiple_of)]
+fn view<T: Pod + PartialOrd>(bytes: &[u8]) -> std::result::Result<&[T], FrozenError> {
+    let size = std::mem::size_of::<T>();
+    if size == 0 || bytes.len() % size != 0 {
+        return Err(FrozenError::Length);
+    }
+    if bytes.as_ptr() as usize % std::mem::align_of::<T>() != 0 {
+        return Err(FrozenError::Misaligned);
+    }
+    // SAFETY: the buffer is aligned for T and holds a whole number
+    // of them, and Pod promises any bytes make a valid T
+    let values =
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const T, bytes.len() / size) };
+    let mut cursor = Cursor::all(values.len());
+    let mut prev: Option<&T> = None;
+    while let Some(i) = cursor.next() {
+        // a value that is not equal to itself, like NaN, has no place
+        // in the order, and would panic the lookups that meet it
+        let v = &values[i];
+        if v.partial_cmp(v) != Some(Equal) || prev.is_some_and(|p| p.partial_cmp(v) != Some(Less))
+        {
+            return Err(FrozenError::Unordered);
+        }
+        prev = Some(&values[i]);
+    }
+    Ok(values)
+}
+
+fn bytes_of<T: Pod>(values: &[T]) -> &[u8] {
+    // SAFETY: Pod types have no padding, so every byte is initialised
+    unsafe {
+        std::slice::from_raw_parts(values.as_ptr() as *const u8, std::mem::size_of_val(values))
+    }
+}
+
+impl<'a, T: PartialOrd> FrozenRBTree<'a, T> {
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns true if the tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns true if the tree contains value.
+    pub fn contains(&self, value: &T) -> bool {
+        self.get(value).is_some()
+    }
+
+    /// Returns the value in the tree equal to value, if any.
+    pub fn get(&self, value: &T) -> Option<&T> {
+        self.ceiling(value).filter(|v| cmp(*v, value) == Equal)
+    }
+
+    /// Returns the greatest value not greater than value.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let t: RBTree<u32> = (0..100).map(|i| i * 10).collect();
+    /// let frozen = t.freeze();
+    /// assert_eq!(frozen.floor(&55), Some(&50));
+    /// assert_eq!(frozen.ceiling(&55), Some(&60));
+    /// assert_eq!(frozen.floor(&990), Some(&990));
+    /// assert_eq!(frozen.ceiling(&991), None);
+    /// ```
+    pub fn floor(&self, value: &T) -> Option<&T> {
+        let k = upper(&self.values, Bound::Included(value));
+        self.at(k)
+    }
+
+    /// Returns the least value not less than value.
+    pub fn ceiling(&self, value: &T) -> Option<&T> {
+        let k = lower(&self.values, Bound::Included(value));
+        self.at(k)
+    }
+
+    /// Returns the smallest value in the tree.
+    pub fn first(&self) -> Option<&T> {
+        self.at(first(self.len()))
+    }
+
+    /// Returns the largest value in the tree.
+    pub fn last(&self) -> Option<&T> {
+        self.at(last(self.len()))
+    }
+
+    /// Returns an iterator over the values in range, in order.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let frozen = (0..20).collect::<RBTree<_>>().freeze();
+    /// assert_eq!(frozen.range(5..8).collect::<Vec<_>>(), [&5, &6, &7]);
+    /// assert_eq!(frozen.range(18..).rev().collect::<Vec<_>>(), [&19, &18]);
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T> {
+        Iter {
+            values: &self.values,
+            cursor: Cursor::new(&self.values, &range),
+        }
+    }
+
+    /// Returns an iterator over the values in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            values: &self.values,
+            cursor: Cursor::all(self.len()),
+        }
+    }
+
+    fn at(&self, k: usize) -> Option<&T> {
+        k.checked_sub(1).map(|i| &self.values[i])
+    }
+}
+
+impl<'a, T: Pod + PartialOrd> FrozenRBTree<'a, T> {
+    /// Views bytes written by as_bytes as a frozen
+    /// tree, without copying them.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{FrozenRBTree, RBTree};
+    ///
+    /// let frozen = (0..1000u64).collect::<RBTree<_>>().freeze();
+    /// // bytes would usually come from a file or a memory map
+    /// let bytes: &[u8] = frozen.as_bytes();
+    /// let view = FrozenRBTree::<u64>::from_bytes(bytes).unwrap();
+    /// assert!(view.iter().eq(frozen.iter()));
+    /// assert!(view.contains(&999));
+    /// assert!(FrozenRBTree::<u64>::from_bytes(&bytes[..12]).is_err());
+    /// ```
+    pub fn from_bytes(bytes: &'a [u8]) -> std::result::Result<FrozenRBTree<'a, T>, FrozenError> {
+        Ok(FrozenRBTree {
+            values: Flat::Borrowed(view(bytes)?),
+        })
+    }
+
+    /// Returns the tree's values as bytes, in their
+    /// flat layout, for from_bytes to read back.
+    pub fn as_bytes(&self) -> &[u8] {
+        bytes_of(&self.values)
+    }
+}
+
+impl<'a, T: PartialOrd + Debug> Debug for FrozenRBTree<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K: PartialOrd, V> FrozenRBMap<'a, K, V> {
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns true if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns true if the map contains an entry for key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the value associated with key, if any.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let map: RBMap<u32, char> = (0..26).map(|i| (i, (b'a' + i as u8) as char)).collect();
+    /// let frozen = map.freeze();
+    /// assert_eq!(frozen.get(&7), Some(&'h'));
+    /// assert_eq!(frozen.get(&26), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_pair(key).map(|(_, v)| v)
+    }
+
+    /// Returns the entry for key, if any.
+    pub fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
+        self.ceiling(key).filter(|(k, _)| cmp(*k, key) == Equal)
+    }
+
+    /// Returns the entry with the greatest key not greater than key.
+    pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        self.at(upper(&self.keys, Bound::Included(key)))
+    }
+
+    /// Returns the entry with the least key not less than key.
+    pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        self.at(lower(&self.keys, Bound::Included(key)))
+    }
+
+    /// Returns the entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        self.at(first(self.len()))
+    }
+
+    /// Returns the entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        self.at(last(self.len()))
+    }
+
+    /// Returns an iterator over the entries whose
+    /// keys are in range, ordered by their keys.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let map: RBMap<u32, u32> = (0..10).map(|i| (i * 2, i)).collect();
+    /// let frozen = map.freeze();
+    /// assert_eq!(frozen.range(3..=8).collect::<Vec<_>>(), [(&4, &2), (&6, &3), (&8, &4)]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> MapIter<'_, K, V> {
+        MapIter {
+            keys: &self.keys,
+            values: &self.values,
+            cursor: Cursor::new(&self.keys, &range),
+        }
+    }
+
+    /// Returns an iterator over the entries, ordered by their keys.
+    pub fn iter(&self) -> MapIter<'_, K, V> {
+        MapIter {
+            keys: &self.keys,
+            values: &self.values,
+            cursor: Cursor::all(self.len()),
+        }
+    }
+
+    fn at(&self, k: usize) -> Option<(&K, &V)> {
+        k.checked_sub(1).map(|i| (&self.keys[i], &self.values[i]))
+    }
+}
+
+impl<'a, K: Pod + PartialOrd, V: Pod> FrozenRBMap<'a, K, V> {
+    /// Views the key and value bytes written by as_bytes
+    /// as a frozen map, without copying them.
+    #[allow(clippy::manual_is_multiple_of)]
+    pub fn from_bytes(
+        keys: &'a [u8],
+        values: &'a [u8],
+    ) -> std::result::Result<FrozenRBMap<'a, K, V>, FrozenError> {
+        let keys: &[K] = view(keys)?;
+        let size = std::mem::size_of::<V>();
+        if size == 0 || values.len() != keys.len() * size {
+            return Err(FrozenError::Length);
+        }
+        if values.as_ptr() as usize % std::mem::align_of::<V>() != 0 {
+            return Err(FrozenError::Misaligned);
+        }
+        // SAFETY: as in view, without the ordering check values don't need
+        let values =
+            unsafe { std::slice::from_raw_parts(values.as_ptr() as *const V, keys.len()) };
+        Ok(FrozenRBMap {
+            keys: Flat::Borrowed(keys),
+            values: Flat::Borrowed(values),
+        })
+    }
+
+    /// Returns the map's keys and values as bytes, in
+    /// their flat layout, for from_bytes to read back.
+    pub fn as_bytes(&self) -> (&[u8], &[u8]) {
+        (bytes_of(&self.keys), bytes_of(&self.values))
+    }
+}
+
+impl<'a, K: PartialOrd + Debug, V: Debug> Debug for FrozenRBMap<'a, K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "set")]
+impl<T: PartialOrd> RBTree<T> {
+    /// Consumes the tree, returning a read-only copy of
+    /// it laid out for fast searches.
+    pub fn freeze(mut self) -> FrozenRBTree<'static, T> {
+        FrozenRBTree {
+            values: Flat::Owned(eytzinger(self.take_sorted())),
+        }
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd, V> RBMap<K, V> {
+    /// Consumes the map, returning a read-only copy of it
+    /// laid out for fast searches. Keys are stored apart from
+    /// values so that searches only touch the keys.
+    pub fn freeze(mut self) -> FrozenRBMap<'static, K, V> {
+        let (keys, values): (Vec<K>, Vec<V>) = eytzinger(self.map.take_sorted())
+            .into_iter()
+            .map(|m| m.consume())
+            .unzip();
+        FrozenRBMap {
+            keys: Flat::Owned(keys),
+            values: Flat::Owned(values),
+        }
+    }
+}
+
+/// An iterator over the values of a FrozenRBTree.
+pub struct Iter<'a, T> {
+    values: &'a [T],
+    cursor: Cursor,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.cursor.next().map(|i| &self.values[i])
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.cursor.next_back().map(|i| &self.values[i])
+    }
+}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+/// An iterator over the entries of a FrozenRBMap.
+pub struct MapIter<'a, K, V> {
+    keys: &'a [K],
+    values: &'a [V],
+    cursor: Cursor,
+}
+
+impl<'a, K, V> Iterator for MapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.cursor.next().map(|i| (&self.keys[i], &self.values[i]))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for MapIter<'a, K, V> {
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        self.cursor
+            .next_back()
+            .map(|i| (&self.keys[i], &self.values[i]))
+    }
+}
+
+impl<'a, K, V> FusedIterator for MapIter<'a, K, V> {}
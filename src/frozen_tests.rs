@@ -0,0 +1,130 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::frozen::FrozenError;
+use crate::{FrozenRBMap, FrozenRBTree, RBMap, RBTree};
+
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+
+fn bound(rng: &mut rand_chacha::ChaCha8Rng) -> Bound<u32> {
+    match rng.gen_range(0..3) {
+        0 => Included(rng.gen_range(0..120)),
+        1 => Excluded(rng.gen_range(0..120)),
+        _ => Unbounded,
+    }
+}
+
+#[test]
+fn test_matches_btreemap() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(42);
+    // every size up to a few full levels, so each tree shape is covered
+    for n in 0..70 {
+        let model: BTreeMap<u32, u32> = (0..n).map(|_| (rng.gen_range(0..100), n)).collect();
+        let map: RBMap<u32, u32> = model.iter().map(|(k, v)| (*k, *v)).collect();
+        let frozen = map.freeze();
+        assert_eq!(frozen.len(), model.len());
+        assert!(frozen.iter().eq(model.iter()));
+        assert!(frozen.iter().rev().eq(model.iter().rev()));
+        assert_eq!(frozen.first(), model.iter().next());
+        assert_eq!(frozen.last(), model.iter().next_back());
+        for k in 0..110 {
+            assert_eq!(frozen.get(&k), model.get(&k));
+            assert_eq!(frozen.floor(&k), model.range(..=k).next_back());
+            assert_eq!(frozen.ceiling(&k), model.range(k..).next());
+        }
+        for _ in 0..20 {
+            let (start, end) = (bound(&mut rng), bound(&mut rng));
+            let empty = match (start, end) {
+                (Included(s), Included(e)) => s > e,
+                (Included(s), Excluded(e)) | (Excluded(s), Included(e)) => s >= e,
+                (Excluded(s), Excluded(e)) => s >= e,
+                _ => false,
+            };
+            if empty {
+                // BTreeMap panics on these, rather than returning nothing
+                assert_eq!(frozen.range((start, end)).next(), None);
+                continue;
+            }
+            let expected: Vec<_> = model.range((start, end)).collect();
+            assert_eq!(frozen.range((start, end)).collect::<Vec<_>>(), expected);
+            assert!(frozen
+                .range((start, end))
+                .rev()
+                .eq(expected.into_iter().rev()));
+        }
+    }
+}
+
+#[test]
+fn test_iter_from_both_ends() {
+    let frozen = (0..10).collect::<RBTree<_>>().freeze();
+    let mut iter = frozen.range(2..7);
+    assert_eq!(iter.next(), Some(&2));
+    assert_eq!(iter.next_back(), Some(&6));
+    assert_eq!(iter.next_back(), Some(&5));
+    assert_eq!(iter.next(), Some(&3));
+    assert_eq!(iter.next(), Some(&4));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
+
+#[test]
+fn test_bytes_round_trip() {
+    let map: RBMap<i64, f32> = (0..500).map(|i| (i * 3 - 700, i as f32 / 2.0)).collect();
+    let frozen = map.freeze();
+    let (keys, values) = frozen.as_bytes();
+    let view = FrozenRBMap::<i64, f32>::from_bytes(keys, values).unwrap();
+    assert!(view.iter().eq(frozen.iter()));
+    assert_eq!(view.get(&-100), Some(&100.0));
+    assert_eq!(view.floor(&-99), Some((&-100, &100.0)));
+
+    assert_eq!(
+        FrozenRBMap::<i64, f32>::from_bytes(keys, &values[4..]).err(),
+        Some(FrozenError::Length)
+    );
+    assert_eq!(
+        FrozenRBTree::<i64>::from_bytes(&keys[1..9]).err(),
+        Some(FrozenError::Misaligned)
+    );
+    assert_eq!(
+        FrozenRBTree::<i64>::from_bytes(&keys[..7]).err(),
+        Some(FrozenError::Length)
+    );
+}
+
+#[test]
+fn test_bytes_unordered() {
+    // sorted order is not Eytzinger order
+    let sorted: [u32; 7] = [0, 1, 2, 3, 4, 5, 6];
+    let view = FrozenRBTree::<u32>::from_bytes(bytes_of(&sorted));
+    assert_eq!(view.err(), Some(FrozenError::Unordered));
+    let eytzinger: [u32; 7] = [3, 1, 5, 0, 2, 4, 6];
+    let view = FrozenRBTree::<u32>::from_bytes(bytes_of(&eytzinger)).unwrap();
+    assert!(view.iter().copied().eq(0..7));
+    // nor are repeated values strictly increasing
+    let repeated = [5u32; 3];
+    let view = FrozenRBTree::<u32>::from_bytes(bytes_of(&repeated));
+    assert_eq!(view.err(), Some(FrozenError::Unordered));
+    // nor can NaN be placed in any order, alone or among others
+    for nans in [
+        &[f64::NAN][..],
+        &[1.0, f64::NAN, 2.0],
+        &[f64::NAN, 0.0, 1.0],
+    ] {
+        let bytes = f64_bytes_of(nans);
+        let view = FrozenRBTree::<f64>::from_bytes(bytes);
+        assert_eq!(view.err(), Some(FrozenError::Unordered));
+        let view = FrozenRBMap::<f64, u64>::from_bytes(bytes, bytes);
+        assert_eq!(view.err(), Some(FrozenError::Unordered));
+    }
+}
+
+fn f64_bytes_of(values: &[f64]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, values.len() * 8) }
+}
+
+fn bytes_of(values: &[u32]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, values.len() * 4) }
+}
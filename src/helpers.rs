@@ -1,42 +1,49 @@
+#[cfg(feature = "set")]
+use crate::node::count;
 use crate::node::Node;
 use crate::node::Node::{Internal, Leaf};
-use std::fmt::Debug;
+use std::mem::take;
 
-pub fn write_to_level<T: Debug>(
-    cur: &Node<T>,
-    from_str: String,
-    level: usize,
-    levels: &mut Vec<String>,
-) {
-    if levels.len() <= level {
-        match cur {
-            Internal(n) => levels.push(format!("{}{}:{:?}", from_str, n.colour(), n.value())),
-            Leaf(_) => levels.push(format!("{}___", from_str)),
-        }
-    } else {
-        match cur {
-            Internal(n) => {
-                levels[level] += &format!(" {}{}:{:?}", from_str, n.colour(), n.value())
+// renders the tree rooted at cur one node per line, each
+// child indented below its parent with the left child first,
+// and leaves shown only where their sibling is not a leaf
+pub fn tree_view<T, F: Fn(&T) -> String>(cur: &Node<T>, label: &F) -> String {
+    let mut out = String::new();
+    write_node(cur, "", &mut out, label);
+    out
+}
+
+fn write_node<T, F: Fn(&T) -> String>(cur: &Node<T>, prefix: &str, out: &mut String, label: &F) {
+    match cur {
+        Internal(n) => {
+            out.push_str(&format!("{}:{}", n.colour(), label(n.value())));
+            if !cur.get_left().is_leaf() || !cur.get_right().is_leaf() {
+                write_child(cur.get_left(), prefix, false, out, label);
+                write_child(cur.get_right(), prefix, true, out, label);
             }
-            Leaf(_) => levels[level] += &format!(" {}___", from_str),
         }
-    }
-    if !cur.is_leaf() {
-        write_to_level(
-            cur.get_left(),
-            format!("{:?}->", cur.value().unwrap()),
-            level + 1,
-            levels,
-        );
-        write_to_level(
-            cur.get_right(),
-            format!("{:?}->", cur.value().unwrap()),
-            level + 1,
-            levels,
-        );
+        Leaf(_) => out.push_str("___"),
     }
 }
 
+fn write_child<T, F: Fn(&T) -> String>(
+    cur: &Node<T>,
+    prefix: &str,
+    last: bool,
+    out: &mut String,
+    label: &F,
+) {
+    let (branch, indent) = if last {
+        ("`-- ", "    ")
+    } else {
+        ("|-- ", "|   ")
+    };
+    out.push('\n');
+    out.push_str(prefix);
+    out.push_str(branch);
+    write_node(cur, &format!("{}{}", prefix, indent), out, label);
+}
+
 pub fn ordered_insertion<'a, T>(cur: &'a Node<T>, order: &mut Vec<&'a T>) {
     if cur.is_leaf() {
         return;
@@ -50,7 +57,6 @@ pub fn ordered_insertion<'a, T>(cur: &'a Node<T>, order: &mut Vec<&'a T>) {
 
 // inserts into stack start and all left children
 // of start down to the leaf
-#[cfg(feature = "set")]
 pub fn insert_left_down<'a, T>(start: &'a Node<T>, stack: &mut Vec<&'a Node<T>>) {
     let mut cur = start;
     while !cur.is_leaf() {
@@ -58,3 +64,65 @@ pub fn insert_left_down<'a, T>(start: &'a Node<T>, stack: &mut Vec<&'a Node<T>>)
         cur = cur.get_left();
     }
 }
+
+// like insert_left_down, but skips the values that come
+// before val, so that popping the stack visits the values
+// from val onwards in order
+#[cfg(feature = "set")]
+pub fn insert_from<'a, T, K, P>(start: &'a Node<T>, val: &K, cmp: &P, stack: &mut Vec<&'a Node<T>>)
+where
+    P: Fn(&K, &T) -> std::cmp::Ordering,
+{
+    let mut cur = start;
+    while let Some(cur_val) = cur.value() {
+        count!(comparisons);
+        if cmp(val, cur_val) == std::cmp::Ordering::Greater {
+            cur = cur.get_right();
+        } else {
+            stack.push(cur);
+            cur = cur.get_left();
+        }
+    }
+}
+
+// keeps only the values below root for which keep returns true.
+// the tree is rebuilt by the guard when it drops, which also
+// happens while unwinding, so a panicking predicate leaves a
+// valid tree that still holds every value it was given
+pub fn retain<T, F: FnMut(&mut T) -> bool>(
+    root: &mut Node<T>,
+    contained: &mut usize,
+    mut keep: F,
+) {
+    let mut values = Vec::with_capacity(*contained);
+    take(root).into_sorted(&mut values);
+    let mut guard = Retainer {
+        kept: Vec::with_capacity(values.len()),
+        rest: values.into_iter(),
+        root,
+        contained,
+    };
+    while let Some(v) = guard.rest.as_mut_slice().first_mut() {
+        let keeping = keep(v);
+        let v = guard.rest.next().unwrap();
+        if keeping {
+            guard.kept.push(v);
+        }
+    }
+}
+
+struct Retainer<'a, T> {
+    root: &'a mut Node<T>,
+    contained: &'a mut usize,
+    kept: Vec<T>,
+    rest: std::vec::IntoIter<T>,
+}
+
+impl<'a, T> Drop for Retainer<'a, T> {
+    fn drop(&mut self) {
+        let mut values = take(&mut self.kept);
+        values.extend(&mut self.rest);
+        *self.contained = values.len();
+        *self.root = Node::from_sorted(values);
+    }
+}
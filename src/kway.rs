@@ -0,0 +1,445 @@
+//! Lazy merges of many trees at once, streaming the
+//! combined order with a heap over each tree's iterator
+//! rather than combining the trees two at a time.
+//!
+//! Trees are ordered by the order of the first tree
+//! given, so every tree merged should use the same one.
+//! Values that compare equal across trees are yielded
+//! in the order their trees were given.
+
+use crate::node::count;
+use crate::order::TreeOrder;
+use crate::rbbase::Iter;
+use crate::RBBase;
+#[cfg(feature = "map")]
+use crate::{mapper::Mapper, order::PartialOrdering, RBMap};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::iter::FusedIterator;
+
+// the next value of one of the merged trees. BinaryHeap is a
+// max heap, so heads compare in reverse to pop the smallest
+// value first, taking the earliest tree on ties
+struct Head<'a, T, O> {
+    val: &'a T,
+    src: usize,
+    order: &'a O,
+}
+
+impl<'a, T, O: TreeOrder<T>> Ord for Head<'a, T, O> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        count!(comparisons);
+        self.order
+            .compare(other.val, self.val)
+            .then(other.src.cmp(&self.src))
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> PartialOrd for Head<'a, T, O> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> PartialEq for Head<'a, T, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> Eq for Head<'a, T, O> {}
+
+/// Yields every value of many trees in order,
+/// keeping values that appear in several trees.
+pub struct MergeSorted<'a, T, O> {
+    heap: BinaryHeap<Head<'a, T, O>>,
+    iters: Vec<Iter<'a, T>>,
+    // set once any tree has run out of values
+    exhausted: bool,
+    // the counts of the first tree, which the merge's work
+    // is counted against
+    #[cfg(feature = "stats")]
+    ops: Option<&'a crate::stats::TreeOps>,
+}
+
+impl<'a, T, O: TreeOrder<T>> MergeSorted<'a, T, O> {
+    fn new<I: IntoIterator<Item = &'a RBBase<T, O>>>(trees: I) -> MergeSorted<'a, T, O> {
+        let mut merge = MergeSorted {
+            heap: BinaryHeap::new(),
+            iters: Vec::new(),
+            exhausted: false,
+            #[cfg(feature = "stats")]
+            ops: None,
+        };
+        for tree in trees {
+            #[cfg(feature = "stats")]
+            let _counting = crate::stats::Counting::new(merge.ops.get_or_insert(&tree.ops));
+            merge.iters.push(tree.iter());
+            merge.advance(merge.iters.len() - 1, &tree.order);
+        }
+        merge
+    }
+
+    // pushes the next value of the tree src onto the heap
+    fn advance(&mut self, src: usize, order: &'a O) {
+        match self.iters[src].next() {
+            Some(val) => self.heap.push(Head { val, src, order }),
+            None => self.exhausted = true,
+        }
+    }
+
+    #[cfg(feature = "stats")]
+    fn counting(&self) -> Option<crate::stats::Counting<'a>> {
+        self.ops.map(crate::stats::Counting::new)
+    }
+
+    fn pop(&mut self) -> Option<&'a T> {
+        #[cfg(feature = "stats")]
+        let _counting = self.counting();
+        let head = self.heap.pop()?;
+        self.advance(head.src, head.order);
+        Some(head.val)
+    }
+
+    // fills group with the smallest value of each tree that
+    // holds it, in the order the trees were given
+    fn next_group(&mut self, group: &mut Vec<&'a T>) {
+        #[cfg(feature = "stats")]
+        let _counting = self.counting();
+        group.clear();
+        let first = match self.pop() {
+            Some(val) => val,
+            None => return,
+        };
+        group.push(first);
+        while let Some(head) = self.heap.peek() {
+            count!(comparisons);
+            if head.order.compare(head.val, first) != Ordering::Equal {
+                break;
+            }
+            group.push(self.pop().unwrap());
+        }
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> Iterator for MergeSorted<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> ExactSizeIterator for MergeSorted<'a, T, O> {
+    fn len(&self) -> usize {
+        self.heap.len() + self.iters.iter().map(|i| i.len()).sum::<usize>()
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> FusedIterator for MergeSorted<'a, T, O> {}
+
+/// Yields the values found in at least one of many
+/// trees, in order, taking each from the first tree
+/// that holds it.
+pub struct UnionAll<'a, T, O> {
+    merge: MergeSorted<'a, T, O>,
+    group: Vec<&'a T>,
+}
+
+impl<'a, T, O: TreeOrder<T>> Iterator for UnionAll<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.merge.next_group(&mut self.group);
+        self.group.first().copied()
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> FusedIterator for UnionAll<'a, T, O> {}
+
+/// Yields the values found in every one of many
+/// trees, in order, taking each from the first tree.
+pub struct IntersectionAll<'a, T, O> {
+    merge: MergeSorted<'a, T, O>,
+    group: Vec<&'a T>,
+}
+
+impl<'a, T, O: TreeOrder<T>> IntersectionAll<'a, T, O> {
+    // fills group with the next value held by every tree
+    fn next_group(&mut self) -> bool {
+        let trees = self.merge.iters.len();
+        // once a tree runs out no later value can be in all of them
+        while !self.merge.exhausted {
+            self.merge.next_group(&mut self.group);
+            if self.group.is_empty() {
+                break;
+            }
+            if self.group.len() == trees {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> Iterator for IntersectionAll<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.next_group() {
+            Some(self.group[0])
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> FusedIterator for IntersectionAll<'a, T, O> {}
+
+impl<T, O: TreeOrder<T>> RBBase<T, O> {
+    /// Returns an iterator over every value of the given
+    /// trees in order, including values held by more
+    /// than one of them. See the kway module.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let shards: Vec<RBTree<_>> = vec![
+    ///     [1, 4, 7].iter().copied().collect(),
+    ///     [2, 4, 8].iter().copied().collect(),
+    ///     [0, 9].iter().copied().collect(),
+    /// ];
+    /// let merged: Vec<_> = RBTree::merge_sorted(&shards).copied().collect();
+    /// assert_eq!(merged, [0, 1, 2, 4, 4, 7, 8, 9]);
+    /// ```
+    pub fn merge_sorted<'a, I>(trees: I) -> MergeSorted<'a, T, O>
+    where
+        I: IntoIterator<Item = &'a RBBase<T, O>>,
+    {
+        MergeSorted::new(trees)
+    }
+
+    /// Returns an iterator over the values found in at
+    /// least one of the given trees, in order. See the
+    /// kway module.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let shards: Vec<RBTree<_>> = (0..4).map(|s| (s..s + 3).collect()).collect();
+    /// let all: Vec<_> = RBTree::union_all(&shards).copied().collect();
+    /// assert_eq!(all, [0, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn union_all<'a, I>(trees: I) -> UnionAll<'a, T, O>
+    where
+        I: IntoIterator<Item = &'a RBBase<T, O>>,
+    {
+        UnionAll {
+            merge: MergeSorted::new(trees),
+            group: Vec::new(),
+        }
+    }
+
+    /// Returns an iterator over the values found in every
+    /// one of the given trees, in order, or nothing if no
+    /// trees are given. See the kway module.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let shards: Vec<RBTree<_>> = (1..4).map(|s| (0..20).step_by(s).collect()).collect();
+    /// let common: Vec<_> = RBTree::intersection_all(&shards).copied().collect();
+    /// assert_eq!(common, [0, 6, 12, 18]);
+    /// ```
+    pub fn intersection_all<'a, I>(trees: I) -> IntersectionAll<'a, T, O>
+    where
+        I: IntoIterator<Item = &'a RBBase<T, O>>,
+    {
+        IntersectionAll {
+            merge: MergeSorted::new(trees),
+            group: Vec::new(),
+        }
+    }
+}
+
+/// Yields every entry of many maps in key order,
+/// keeping keys that appear in several maps.
+#[cfg(feature = "map")]
+pub struct MapMergeSorted<'a, K: PartialOrd, V> {
+    merge: MergeSorted<'a, Mapper<K, V>, PartialOrdering>,
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V> Iterator for MapMergeSorted<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.merge.next().map(|m| m.pair())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.merge.size_hint()
+    }
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V> ExactSizeIterator for MapMergeSorted<'a, K, V> {}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V> FusedIterator for MapMergeSorted<'a, K, V> {}
+
+/// Yields the keys found in at least one of many maps,
+/// in order, each with the result of resolving the
+/// values held for it.
+#[cfg(feature = "map")]
+pub struct MapUnionAll<'a, K: PartialOrd, V, F> {
+    merge: MergeSorted<'a, Mapper<K, V>, PartialOrdering>,
+    group: Vec<&'a Mapper<K, V>>,
+    values: Vec<&'a V>,
+    resolve: F,
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V, R, F> Iterator for MapUnionAll<'a, K, V, F>
+where
+    F: FnMut(&'a K, &[&'a V]) -> R,
+{
+    type Item = (&'a K, R);
+
+    fn next(&mut self) -> Option<(&'a K, R)> {
+        self.merge.next_group(&mut self.group);
+        let key = self.group.first()?.key();
+        self.values.clear();
+        self.values.extend(self.group.iter().map(|m| m.as_ref()));
+        Some((key, (self.resolve)(key, &self.values)))
+    }
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V, R, F> FusedIterator for MapUnionAll<'a, K, V, F> where
+    F: FnMut(&'a K, &[&'a V]) -> R
+{
+}
+
+/// Yields the keys found in every one of many maps,
+/// in order, each with the result of resolving the
+/// values held for it.
+#[cfg(feature = "map")]
+pub struct MapIntersectionAll<'a, K: PartialOrd, V, F> {
+    inner: IntersectionAll<'a, Mapper<K, V>, PartialOrdering>,
+    values: Vec<&'a V>,
+    resolve: F,
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V, R, F> Iterator for MapIntersectionAll<'a, K, V, F>
+where
+    F: FnMut(&'a K, &[&'a V]) -> R,
+{
+    type Item = (&'a K, R);
+
+    fn next(&mut self) -> Option<(&'a K, R)> {
+        if !self.inner.next_group() {
+            return None;
+        }
+        let key = self.inner.group[0].key();
+        self.values.clear();
+        self.values
+            .extend(self.inner.group.iter().map(|m| m.as_ref()));
+        Some((key, (self.resolve)(key, &self.values)))
+    }
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V, R, F> FusedIterator for MapIntersectionAll<'a, K, V, F> where
+    F: FnMut(&'a K, &[&'a V]) -> R
+{
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd, V> RBMap<K, V> {
+    /// Returns an iterator over every entry of the given
+    /// maps in key order, including keys held by more
+    /// than one of them. See the kway module.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let a: RBMap<_, _> = [(1, 'a'), (3, 'a')].iter().copied().collect();
+    /// let b: RBMap<_, _> = [(1, 'b'), (2, 'b')].iter().copied().collect();
+    /// let merged: Vec<_> = RBMap::merge_sorted(vec![&a, &b]).collect();
+    /// assert_eq!(merged, [(&1, &'a'), (&1, &'b'), (&2, &'b'), (&3, &'a')]);
+    /// ```
+    pub fn merge_sorted<'a, I>(maps: I) -> MapMergeSorted<'a, K, V>
+    where
+        I: IntoIterator<Item = &'a RBMap<K, V>>,
+    {
+        MapMergeSorted {
+            merge: MergeSorted::new(maps.into_iter().map(|m| &m.map)),
+        }
+    }
+
+    /// Returns an iterator over the keys found in at least
+    /// one of the given maps, in order. Each key is paired
+    /// with the result of calling resolve with the key and
+    /// the values the maps hold for it, in the order the
+    /// maps were given. See the kway module.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let shards: Vec<RBMap<_, _>> = (0..3)
+    ///     .map(|s| (s..s + 2).map(|k| (k, s)).collect())
+    ///     .collect();
+    /// // keep the value from the latest shard
+    /// let latest: Vec<_> = RBMap::union_all(&shards, |_, vs| *vs[vs.len() - 1]).collect();
+    /// assert_eq!(latest, [(&0, 0), (&1, 1), (&2, 2), (&3, 2)]);
+    /// ```
+    pub fn union_all<'a, I, R, F>(maps: I, resolve: F) -> MapUnionAll<'a, K, V, F>
+    where
+        I: IntoIterator<Item = &'a RBMap<K, V>>,
+        F: FnMut(&'a K, &[&'a V]) -> R,
+    {
+        MapUnionAll {
+            merge: MergeSorted::new(maps.into_iter().map(|m| &m.map)),
+            group: Vec::new(),
+            values: Vec::new(),
+            resolve,
+        }
+    }
+
+    /// Returns an iterator over the keys found in every one
+    /// of the given maps, in order, paired as union_all
+    /// pairs them. See the kway module.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let counts: Vec<RBMap<_, _>> = vec![
+    ///     [("a", 1), ("b", 2)].iter().copied().collect(),
+    ///     [("b", 3), ("c", 4)].iter().copied().collect(),
+    /// ];
+    /// let totals: Vec<_> =
+    ///     RBMap::intersection_all(&counts, |_, vs| vs.iter().copied().sum::<i32>()).collect();
+    /// assert_eq!(totals, [(&"b", 5)]);
+    /// ```
+    pub fn intersection_all<'a, I, R, F>(maps: I, resolve: F) -> MapIntersectionAll<'a, K, V, F>
+    where
+        I: IntoIterator<Item = &'a RBMap<K, V>>,
+        F: FnMut(&'a K, &[&'a V]) -> R,
+    {
+        MapIntersectionAll {
+            inner: IntersectionAll {
+                merge: MergeSorted::new(maps.into_iter().map(|m| &m.map)),
+                group: Vec::new(),
+            },
+            values: Vec::new(),
+            resolve,
+        }
+    }
+}
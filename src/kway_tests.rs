@@ -0,0 +1,109 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::{RBMap, RBQueue, RBTree};
+
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, BTreeSet};
+
+fn random_shards(rng: &mut rand_chacha::ChaCha8Rng, shards: usize) -> Vec<RBTree<u16>> {
+    (0..shards)
+        .map(|_| {
+            let len = rng.gen_range(0..60);
+            (0..len).map(|_| rng.gen_range(0..100)).collect()
+        })
+        .collect()
+}
+
+#[test]
+fn test_against_model() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(50);
+    for round in 0..200 {
+        let shards = random_shards(&mut rng, round % 9);
+        let sets: Vec<BTreeSet<u16>> =
+            shards.iter().map(|s| s.iter().copied().collect()).collect();
+
+        let mut all: Vec<u16> = sets.iter().flatten().copied().collect();
+        all.sort_unstable();
+        let merged = RBTree::merge_sorted(&shards);
+        assert_eq!(merged.len(), all.len());
+        assert_eq!(merged.copied().collect::<Vec<_>>(), all);
+
+        let union: BTreeSet<u16> = all.iter().copied().collect();
+        let found: Vec<_> = RBTree::union_all(&shards).copied().collect();
+        assert_eq!(found, union.into_iter().collect::<Vec<_>>());
+
+        let common: Vec<_> = match sets.split_first() {
+            Some((first, rest)) => first
+                .iter()
+                .filter(|v| rest.iter().all(|s| s.contains(v)))
+                .copied()
+                .collect(),
+            None => Vec::new(),
+        };
+        let found: Vec<_> = RBTree::intersection_all(&shards).copied().collect();
+        assert_eq!(found, common);
+    }
+}
+
+#[test]
+fn test_ties_follow_tree_order() {
+    // equal under the queue's order, but distinguishable
+    let cmp = |l: &(u8, char), r: &(u8, char)| l.0.cmp(&r.0);
+    let mut queues = Vec::new();
+    for c in ['a', 'b', 'c'].iter() {
+        let mut q = RBQueue::new(cmp);
+        (0..4).for_each(|i| {
+            q.insert((i, *c));
+        });
+        queues.push(q);
+    }
+    // the queues are given in reverse, so ties come out c, b, a
+    let merged: Vec<_> = RBQueue::merge_sorted(queues.iter().rev())
+        .copied()
+        .collect();
+    assert_eq!(&merged[..3], [(0, 'c'), (0, 'b'), (0, 'a')]);
+    let union: Vec<_> = RBQueue::union_all(queues.iter().rev()).copied().collect();
+    assert_eq!(union, [(0, 'c'), (1, 'c'), (2, 'c'), (3, 'c')]);
+    let common: Vec<_> = RBQueue::intersection_all(&queues).map(|v| v.1).collect();
+    assert_eq!(common, ['a'; 4]);
+}
+
+#[test]
+fn test_maps_against_model() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(51);
+    for round in 0..100 {
+        let maps: Vec<RBMap<u16, usize>> = (0..round % 7)
+            .map(|shard| {
+                let len = rng.gen_range(0..40);
+                (0..len).map(|_| (rng.gen_range(0..60), shard)).collect()
+            })
+            .collect();
+
+        let mut model: BTreeMap<u16, Vec<usize>> = BTreeMap::new();
+        for (k, v) in maps.iter().flat_map(|m| m.iter()) {
+            model.entry(*k).or_default().push(*v);
+        }
+
+        let merged: Vec<_> = RBMap::merge_sorted(&maps).map(|(k, v)| (*k, *v)).collect();
+        let expected: Vec<_> = model
+            .iter()
+            .flat_map(|(k, vs)| vs.iter().map(move |v| (*k, *v)))
+            .collect();
+        assert_eq!(merged, expected);
+
+        let union: Vec<_> =
+            RBMap::union_all(&maps, |_, vs| vs.iter().map(|v| **v).collect::<Vec<_>>())
+                .map(|(k, vs)| (*k, vs))
+                .collect();
+        assert_eq!(union, model.clone().into_iter().collect::<Vec<_>>());
+
+        let common: Vec<_> = RBMap::intersection_all(&maps, |k, vs| (*k, vs.len())).collect();
+        let expected: Vec<_> = model
+            .iter()
+            .filter(|(_, vs)| vs.len() == maps.len())
+            .map(|(k, vs)| (k, (*k, vs.len())))
+            .collect();
+        assert_eq!(common, expected);
+    }
+}
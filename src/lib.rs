@@ -1,13 +1,70 @@
+pub mod augment;
+#[cfg(test)]
+mod augment_tests;
+pub mod clock;
+pub mod cmp;
+#[cfg(test)]
+mod cmp_tests;
+#[cfg(feature = "map")]
+pub mod concurrentrbmap;
+#[cfg(all(test, feature = "map"))]
+mod concurrentrbmap_tests;
+#[cfg(all(feature = "map", feature = "queue"))]
+pub mod expiringmap;
+#[cfg(all(test, feature = "map", feature = "queue"))]
+mod expiringmap_tests;
+pub mod frozen;
+#[cfg(all(test, feature = "map"))]
+mod frozen_tests;
+pub mod kway;
+#[cfg(all(test, feature = "map"))]
+mod kway_tests;
 mod node;
+pub mod order;
+#[cfg(feature = "rayon")]
+pub mod par;
+#[cfg(all(test, feature = "rayon", feature = "map", feature = "queue"))]
+mod par_tests;
+#[cfg(feature = "set")]
+pub mod prefix;
+#[cfg(all(test, feature = "map"))]
+mod prefix_tests;
+pub mod rbbase;
+pub mod rbintervalmap;
+#[cfg(test)]
+mod rbintervalmap_tests;
 #[cfg(feature = "map")]
 pub mod rbmap;
+#[cfg(feature = "map")]
+pub mod rbrangemap;
+#[cfg(all(test, feature = "map"))]
+mod rbrangemap_tests;
+pub mod rbseq;
+#[cfg(test)]
+mod rbseq_tests;
 #[cfg(feature = "set")]
 pub mod rbtree;
+mod setops;
+#[cfg(feature = "map")]
+mod sharednode;
+pub mod snapshot;
+#[cfg(all(test, feature = "map", feature = "queue"))]
+mod snapshot_tests;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(all(test, feature = "stats", feature = "set"))]
+mod stats_tests;
+#[cfg(feature = "map")]
+pub mod timerqueue;
+#[cfg(all(test, feature = "map"))]
+mod timerqueue_tests;
+pub mod trace;
+#[cfg(all(test, feature = "map"))]
+mod trace_tests;
 #[macro_use]
 #[cfg(feature = "queue")]
 pub mod rbqueue;
 mod helpers;
-#[cfg(feature = "map")]
 mod mapper;
 #[cfg(test)]
 mod rbtree_tests;
@@ -25,12 +82,34 @@ mod rbset;
 #[cfg(test)]
 mod rbset_test;
 
+#[cfg(test)]
+mod panic_tests;
+
+use crate::augment::{AugEntry, Augment};
+#[cfg(feature = "map")]
+use crate::clock::SystemClock;
+#[cfg(all(feature = "map", feature = "queue"))]
+use crate::expiringmap::{ByDeadline, Expiring};
+use crate::frozen::Flat;
 use crate::mapper::SimpleMapper;
+use crate::rbintervalmap::Interval;
+use crate::rbseq::Positioned;
+#[cfg(feature = "map")]
+use crate::timerqueue::{ByDeadline as TimerOrder, Timer, TimerId};
 #[cfg(feature = "map")]
 use mapper::Mapper;
 use node::Node;
-use std::marker::PhantomData;
-use std::rc::Rc;
+use order::ByComparator;
+#[cfg(feature = "queue")]
+use order::ByFn;
+#[cfg(feature = "set")]
+use order::PartialOrdering;
+#[cfg(feature = "map")]
+use sharednode::Link;
+#[cfg(feature = "map")]
+use std::sync::{Mutex, RwLock};
+#[cfg(feature = "map")]
+use std::time::Instant;
 
 /// A map implemented using a red black tree to
 /// store key-value pairs.
@@ -41,31 +120,136 @@ pub struct RBMap<K: PartialOrd, V> {
     map: RBTree<Mapper<K, V>>,
 }
 
+/// An ordered map that can be shared between threads. Readers
+/// never wait for a write to be applied, and snapshots
+/// give a consistent view of it that later writes leave
+/// untouched.
+#[cfg(feature = "map")]
+pub struct ConcurrentRBMap<K: PartialOrd, V> {
+    current: RwLock<SharedRBMap<K, V>>,
+    writer: Mutex<()>,
+}
+
+/// An ordered map whose clones share their nodes, as
+/// the snapshots of a ConcurrentRBMap and the copies
+/// its writers change are. Cloning one is O(1), and
+/// changing an entry copies only the O(log n) nodes
+/// on the way down to it that another clone still
+/// holds, so its key and value types must be Clone
+/// for it to be changed.
+#[cfg(feature = "map")]
+pub struct SharedRBMap<K: PartialOrd, V> {
+    root: Link<K, V>,
+    contained: usize,
+}
+
+/// A map from disjoint half-open ranges of keys to
+/// values, stored in an RBMap by the start of each
+/// range. Overlapping ranges are split or overwritten
+/// on insertion, and adjacent ranges holding equal
+/// values are merged.
+#[cfg(feature = "map")]
 #[derive(Clone)]
-struct ComparatorWrapper<K, F: Comparator<K>> {
-    cmp: Rc<F>,
-    _k: PhantomData<K>,
+pub struct RBRangeMap<K: PartialOrd, V> {
+    map: RBMap<K, (K, V)>,
 }
 
-impl<K, F: Comparator<K>> ComparatorWrapper<K, F> {
-    pub fn new(cmp: F) -> Self {
-        Self {
-            cmp: Rc::new(cmp),
-            _k: PhantomData,
-        }
-    }
+/// A set of keys stored as disjoint, non-adjacent
+/// half-open ranges.
+#[cfg(feature = "map")]
+#[derive(Clone)]
+pub struct RBRangeSet<K: PartialOrd> {
+    map: RBRangeMap<K, ()>,
 }
 
-impl<K, V, F: 'static + Comparator<K>> Comparator<SimpleMapper<K, V>> for ComparatorWrapper<K, F> {
-    fn cmp(&self) -> Box<dyn Fn(&SimpleMapper<K, V>, &SimpleMapper<K, V>) -> std::cmp::Ordering> {
-        let f = self.cmp.clone();
-        Box::new(move |a: &SimpleMapper<K, V>, b: &SimpleMapper<K, V>| f.cmp()(a.key(), b.key()))
-    }
+/// A map implemented using a red black tree that
+/// keeps a summary of every subtree, as described by
+/// the given Augment, so that the entries in any
+/// range of keys can be aggregated in logarithmic time.
+#[derive(Clone)]
+pub struct RBAugmentedMap<K: PartialOrd, V, A: Augment<K, V>> {
+    root: Node<AugEntry<K, V, A::Summary>>,
+    contained: usize,
+    aug: A,
+    #[cfg(feature = "stats")]
+    ops: stats::TreeOps,
 }
 
+/// A map whose entries expire once their time to live
+/// has passed, tracked by an RBMap of entries and an
+/// RBQueue of their deadlines. Expired entries are never
+/// returned, and are purged lazily on insertion or on
+/// request. The time is read from the clock C, which
+/// tests can replace with a clock::ManualClock.
+#[cfg(all(feature = "map", feature = "queue"))]
+pub struct ExpiringMap<K: PartialOrd, V, C = SystemClock> {
+    entries: RBMap<K, Expiring<V>>,
+    deadlines: RBQueue<(Instant, K), ByDeadline<K>>,
+    clock: C,
+}
+
+/// A queue of timers, each carrying a payload, that are
+/// popped once their deadline has passed. Timers are kept
+/// in a red black tree by deadline, with ties broken in
+/// the order they were scheduled, and can be cancelled or
+/// rescheduled by the TimerId returned when scheduling.
+#[cfg(feature = "map")]
+pub struct TimerQueue<T, C = SystemClock> {
+    timers: RBBase<Timer<T>, TimerOrder>,
+    deadlines: RBMap<TimerId, (Instant, u64)>,
+    next: u64,
+    clock: C,
+}
+
+/// A read-only map, made by RBMap::freeze, that stores
+/// its keys in a flat array in Eytzinger order so that
+/// lookups are cache friendly and branch predictable.
+/// Maps of plain-old-data keys and values can also be
+/// viewed directly from a byte buffer.
 #[derive(Clone)]
-pub struct RBMapWithCmp<K, V, F: 'static + Comparator<K>> {
-    map: RBTreeWithCmp<SimpleMapper<K, V>, ComparatorWrapper<K, F>>,
+pub struct FrozenRBMap<'a, K, V> {
+    keys: Flat<'a, K>,
+    values: Flat<'a, V>,
+}
+
+/// A read-only set, made by RBTree::freeze, that stores
+/// its values in a flat array in Eytzinger order.
+#[derive(Clone)]
+pub struct FrozenRBTree<'a, T> {
+    values: Flat<'a, T>,
+}
+
+/// A map from half-open intervals to values, implemented
+/// using a red black tree that tracks the largest end
+/// of the intervals below each node, so that the
+/// intervals containing a point or overlapping another
+/// interval can be found quickly.
+#[derive(Clone)]
+pub struct RBIntervalMap<K, V> {
+    root: Node<Interval<K, V>>,
+    contained: usize,
+    #[cfg(feature = "stats")]
+    ops: stats::TreeOps,
+}
+
+/// A sequence implemented using a red black tree that
+/// orders its values by position rather than by key,
+/// tracking the size of every subtree so that values can
+/// be inserted and removed at any index, and sequences
+/// split and joined, in logarithmic time.
+#[derive(Clone)]
+pub struct RBSeq<T> {
+    root: Node<Positioned<T>>,
+    #[cfg(feature = "stats")]
+    ops: stats::TreeOps,
+}
+
+/// A map implemented using a red black tree to
+/// store key-value pairs, ordered by the given
+/// Comparator applied to their keys.
+#[derive(Clone)]
+pub struct RBMapWithCmp<K, V, F: Comparator<K>> {
+    map: RBBase<SimpleMapper<K, V>, ByComparator<K, F>>,
 }
 
 #[allow(clippy::upper_case_acronyms)]
@@ -74,21 +258,33 @@ pub struct RBSet<K, F: Comparator<K>> {
     map: RBTreeWithCmp<K, F>,
 }
 
-/// A red black tree that can be used to store
-/// elements sorted by their PartialOrd provided
-/// ordering.
-#[cfg(feature = "set")]
+/// A red black tree that stores its elements in
+/// the order given by `O`, an implementation of
+/// `order::TreeOrder`. RBTree, RBTreeWithCmp and
+/// RBQueue are all instances of this type, and
+/// share every method defined on it.
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Clone)]
-pub struct RBTree<T: PartialOrd> {
+pub struct RBBase<T, O> {
     root: Node<T>,
     contained: usize,
+    order: O,
+    #[cfg(feature = "stats")]
+    ops: stats::TreeOps,
 }
 
+/// A red black tree that can be used to store
+/// elements sorted by their PartialOrd provided
+/// ordering.
+#[cfg(feature = "set")]
+pub type RBTree<T> = RBBase<T, PartialOrdering>;
+
 pub trait Comparator<T> {
+    #[allow(clippy::type_complexity)]
     fn cmp(&self) -> Box<dyn Fn(&T, &T) -> std::cmp::Ordering>;
 }
 
+#[derive(Clone, Copy, Default, Debug)]
 pub struct TestComparator;
 
 impl<T> Comparator<T> for TestComparator
@@ -100,29 +296,16 @@ where
     }
 }
 
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Clone)]
-pub struct RBTreeWithCmp<T, F: Comparator<T>> {
-    root: Node<T>,
-    cmp: F,
-    contained: usize,
-}
+/// A red black tree that orders its elements
+/// using the given Comparator.
+pub type RBTreeWithCmp<T, F> = RBBase<T, ByComparator<T, F>>;
 
 /// A priority queue implemented using a red black
 /// tree. The ordering supplied must satisfy the assymetry
 /// and transitivity rules as outlined by  the dorumentation
 /// of std::cmp::PartialOrd.
 #[cfg(feature = "queue")]
-#[allow(clippy::upper_case_acronyms)]
-#[derive(Clone)]
-pub struct RBQueue<T, P>
-where
-    P: Fn(&T, &T) -> std::cmp::Ordering,
-{
-    root: Node<T>,
-    contained: usize,
-    cmp: P,
-}
+pub type RBQueue<T, P> = RBBase<T, ByFn<P>>;
 
 /// Returns an RBTree containing the items
 /// given separated by commas.
@@ -187,8 +370,12 @@ macro_rules! new_queue {
 /// respectively.
 ///
 /// `cmp` should be a function that takes two values
-/// from the queue and returns an integer (i8)
-/// providing the information as above.
+/// from the queue and returns any integer or float,
+/// or an Ordering, providing the information as above.
+/// The result is interpreted by `order::Sign`, so wide
+/// differences are never truncated and a NaN result
+/// panics. Note that `l - r` can itself overflow;
+/// `order::ascending` and `order::descending` avoid that.
 ///
 /// # Example:
 /// ```
@@ -196,11 +383,11 @@ macro_rules! new_queue {
 /// # extern crate rb_tree;
 /// # use rb_tree::RBQueue;
 /// # fn main() {
-/// let mut q = new_c_queue!(|l: &i64, r| (r - l));
+/// let mut q = new_c_queue!(|l: &i64, r| (l - r));
+/// q.insert(3);
 /// q.insert(1);
 /// q.insert(2);
-/// q.insert(3);
-/// assert_eq!(q.ordered(), [&3, &2, &1]);
+/// assert_eq!(q.ordered(), [&1, &2, &3]);
 /// # }
 /// ```
 ///
@@ -209,33 +396,37 @@ macro_rules! new_queue {
 /// # #[macro_use(new_c_queue)]
 /// # extern crate rb_tree;
 /// # use rb_tree::RBQueue;
+/// # use rb_tree::order::descending;
 /// # fn main() {
-/// let q = new_c_queue!(|l: &i64, r| (r - l); 1, 2, 3);
+/// let q = new_c_queue!(descending; 1, 2, 3);
 /// assert_eq!(q.ordered(), [&3, &2, &1]);
 /// # }
 /// ```
+///
+/// # Example:
+/// ```
+/// # #[macro_use(new_c_queue)]
+/// # extern crate rb_tree;
+/// # use rb_tree::RBQueue;
+/// # fn main() {
+/// let q = new_c_queue!(|l: &f64, r| (l - r); 0.4, 0.0, 0.8);
+/// assert_eq!(q.ordered(), [&0.0, &0.4, &0.8]);
+/// # }
+/// ```
 #[cfg(feature = "queue")]
 #[macro_export]
 macro_rules! new_c_queue {
     ($cmp:expr) => {
         RBQueue::new(move |l, r| {
-            let comp = Box::new($cmp);
-            match comp(l, r) as i8 {
-                -128i8 ..= -1 => std::cmp::Ordering::Less,
-                0 => std::cmp::Ordering::Equal,
-                1 ..= 127i8 => std::cmp::Ordering::Greater
-            }
+            let comp = $cmp;
+            $crate::order::Sign::sign(comp(l, r))
         })
     };
 
     ($cmp:expr; $($v:expr),*) => {{
         let mut q = RBQueue::new(move |l, r| {
-            let comp = Box::new($cmp);
-            match comp(l, r) as i8 {
-                -128i8 ..= -1 => std::cmp::Ordering::Less,
-                0 => std::cmp::Ordering::Equal,
-                1 ..= 127i8 => std::cmp::Ordering::Greater
-            }
+            let comp = $cmp;
+            $crate::order::Sign::sign(comp(l, r))
         });
         $(
             q.insert($v);
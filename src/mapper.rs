@@ -1,11 +1,16 @@
+use crate::order::{ByComparator, TreeOrder};
+use crate::Comparator;
+use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter, Result};
 
+#[cfg(feature = "map")]
 #[derive(Clone)]
 pub struct Mapper<K: PartialOrd, V> {
     key: K,
     val: Option<V>,
 }
 
+#[cfg(feature = "map")]
 impl<K: PartialOrd, V> Mapper<K, V> {
     pub fn new(key: K, val: Option<V>) -> Mapper<K, V> {
         Mapper { key, val }
@@ -40,30 +45,35 @@ impl<K: PartialOrd, V> Mapper<K, V> {
     }
 }
 
+#[cfg(feature = "map")]
 impl<K: PartialOrd + Debug, V: Debug> Debug for Mapper<K, V> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "[{:?}: {:?}]", self.key, self.val)
     }
 }
 
+#[cfg(feature = "map")]
 impl<K: PartialOrd, V> PartialEq for Mapper<K, V> {
     fn eq(&self, other: &Mapper<K, V>) -> bool {
         self.key == other.key
     }
 }
 
+#[cfg(feature = "map")]
 impl<K: PartialOrd, V> PartialOrd for Mapper<K, V> {
     fn partial_cmp(&self, other: &Mapper<K, V>) -> Option<std::cmp::Ordering> {
         self.key.partial_cmp(&other.key)
     }
 }
 
+#[cfg(feature = "map")]
 impl<K: PartialOrd, V> PartialEq<Mapper<K, V>> for Mapper<&K, V> {
     fn eq(&self, other: &Mapper<K, V>) -> bool {
         *self.key == other.key
     }
 }
 
+#[cfg(feature = "map")]
 impl<K: PartialOrd, V> PartialOrd<Mapper<K, V>> for Mapper<&K, V> {
     fn partial_cmp(&self, other: &Mapper<K, V>) -> Option<std::cmp::Ordering> {
         self.key.partial_cmp(&other.key)
@@ -111,3 +121,17 @@ impl<K: Debug, V: Debug> Debug for SimpleMapper<K, V> {
         write!(f, "[{:?}: {:?}]", self.key, self.val)
     }
 }
+
+// maps with a Comparator order their entries by key alone,
+// which also allows them to be searched for by a bare key
+impl<K, V, F: Comparator<K>> TreeOrder<SimpleMapper<K, V>> for ByComparator<K, F> {
+    fn compare(&self, l: &SimpleMapper<K, V>, r: &SimpleMapper<K, V>) -> Ordering {
+        TreeOrder::<K>::compare(self, l.key(), r.key())
+    }
+}
+
+impl<K, V, F: Comparator<K>> TreeOrder<K, SimpleMapper<K, V>> for ByComparator<K, F> {
+    fn compare(&self, l: &K, r: &SimpleMapper<K, V>) -> Ordering {
+        TreeOrder::<K>::compare(self, l, r.key())
+    }
+}
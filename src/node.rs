@@ -1,8 +1,40 @@
+use crate::trace::DoubleBlackCase;
 use std::boxed::Box;
 use std::cmp::Ordering::{Equal, Greater, Less};
 use std::mem::swap as m_swap;
 use std::ops::{Deref, DerefMut};
 
+// records an operation in the stats module's counts,
+// when the stats feature is enabled
+macro_rules! count {
+    // straight to a tree's counts, from any thread
+    ($counter:ident => $ops:expr) => {
+        #[cfg(feature = "stats")]
+        $ops.record(|c| c.$counter += 1);
+    };
+    ($counter:ident) => {
+        #[cfg(feature = "stats")]
+        crate::stats::record(|c| c.$counter += 1);
+    };
+    ($counter:ident if $cond:expr) => {
+        #[cfg(feature = "stats")]
+        if $cond {
+            crate::stats::record(|c| c.$counter += 1);
+        }
+    };
+}
+pub(crate) use count;
+
+// hands the operations counted on this thread to the tree
+// owning ops, until the end of the enclosing block
+macro_rules! counting {
+    ($ops:expr) => {
+        #[cfg(feature = "stats")]
+        let _counting = crate::stats::Counting::new(&$ops);
+    };
+}
+pub(crate) use counting;
+
 #[derive(PartialEq, Debug, Clone, Copy)]
 pub enum Colour {
     Red,
@@ -42,6 +74,41 @@ pub enum Node<T> {
     Leaf(Colour),
 }
 
+// keeps data that a value derives from its children, such
+// as an augmented tree's summaries, up to date. it is called
+// bottom-up on every node whose subtree changes
+pub trait Refresh<T> {
+    fn refresh(&self, value: &mut T, left: Option<&T>, right: Option<&T>);
+
+    // told about each rebalancing step just before it is
+    // taken, so that operations can be traced
+    fn observe(&self, _step: Step<'_, T>) {}
+}
+
+// the rebalancing steps passed to Refresh::observe, with the
+// values of the nodes involved
+pub enum Step<'a, T> {
+    // a black node with two red children swaps colours with them
+    Recolour(&'a T),
+    // the node and the grandchild lifted above it
+    InnerRotation(&'a T, &'a T),
+    // the node and the child lifted above it
+    OuterRotation(&'a T, &'a T),
+    // the case met, and the parent and sibling of a double black node
+    DoubleBlack(DoubleBlackCase, &'a T, &'a T),
+}
+
+// the unit type is used by every tree that isn't augmented
+impl<T> Refresh<T> for () {
+    fn refresh(&self, _: &mut T, _: Option<&T>, _: Option<&T>) {}
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Leaf(Black)
+    }
+}
+
 use Colour::*;
 use Insertion::*;
 use Node::*;
@@ -78,6 +145,7 @@ impl<T> Innards<T> {
     }
 
     pub fn swap_colour(&mut self) {
+        count!(recolours);
         self.colour = match self.colour {
             Red => Black,
             Black => Red,
@@ -107,6 +175,90 @@ impl<T> Node<T> {
         })
     }
 
+    // builds a valid tree out of values that are already in
+    // their sorted order without performing any comparisons.
+    // every level but the last is full, so all nodes are black
+    // except for those on the last level which are red
+    pub fn from_sorted(values: Vec<T>) -> Node<T> {
+        Node::from_sorted_with(values, &())
+    }
+
+    pub fn from_sorted_with<R: Refresh<T>>(values: Vec<T>, aug: &R) -> Node<T> {
+        let len = values.len();
+        let mut full = 0;
+        while (1 << (full + 1)) - 1 <= len {
+            full += 1;
+        }
+        Node::build_sorted(&mut values.into_iter(), len, 0, full, aug)
+    }
+
+    fn build_sorted<I, R>(
+        values: &mut I,
+        len: usize,
+        depth: usize,
+        full: usize,
+        aug: &R,
+    ) -> Node<T>
+    where
+        I: Iterator<Item = T>,
+        R: Refresh<T>,
+    {
+        if len == 0 {
+            return Leaf(Black);
+        }
+        let l_child = Node::build_sorted(values, len / 2, depth + 1, full, aug);
+        let value = values.next().unwrap();
+        let r_child = Node::build_sorted(values, len - len / 2 - 1, depth + 1, full, aug);
+        let mut node = Internal(Innards {
+            value,
+            colour: if depth < full { Black } else { Red },
+            r_child: Box::new(r_child),
+            l_child: Box::new(l_child),
+        });
+        node.refresh(aug);
+        node
+    }
+
+    // moves every value below this node into sorted in their
+    // in-order traversal order
+    pub fn into_sorted(self, sorted: &mut Vec<T>) {
+        if let Internal(n) = self {
+            n.l_child.into_sorted(sorted);
+            sorted.push(n.value);
+            n.r_child.into_sorted(sorted);
+        }
+    }
+
+    // checks the red-black properties hold below this node and
+    // returns its black height if they do
+    #[cfg(test)]
+    pub fn black_height(&self) -> Option<usize> {
+        match self {
+            Internal(n) => {
+                if n.is_double_black() || n.is_red() && (n.l_child.is_red() || n.r_child.is_red())
+                {
+                    return None;
+                }
+                let left = n.l_child.black_height()?;
+                let right = n.r_child.black_height()?;
+                if left != right {
+                    None
+                } else if n.is_black() {
+                    Some(left + 1)
+                } else {
+                    Some(left)
+                }
+            }
+            Leaf(c) => {
+                if *c == Black {
+                    Some(1)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
     // convenience functions so matches don't appear everywhere
     pub fn is_black(&self) -> bool {
         match self {
@@ -144,7 +296,6 @@ impl<T> Node<T> {
         }
     }
 
-    #[cfg(feature = "map")]
     pub fn value_mut(&mut self) -> Option<&mut T> {
         match self {
             Internal(n) => Some(&mut n.value),
@@ -152,18 +303,66 @@ impl<T> Node<T> {
         }
     }
 
+    // splits the borrow of this node into its left subtree,
+    // value and right subtree, or returns None for a leaf
+    #[cfg(all(feature = "rayon", feature = "map"))]
+    pub fn parts_mut(&mut self) -> Option<(&mut Node<T>, &mut T, &mut Node<T>)> {
+        match self {
+            Internal(n) => Some((&mut n.l_child, &mut n.value, &mut n.r_child)),
+            Leaf(_) => None,
+        }
+    }
+
+    // as parts_mut, taking ownership of the parts
+    #[cfg(feature = "rayon")]
+    pub fn into_parts(self) -> Option<(Node<T>, T, Node<T>)> {
+        match self {
+            Internal(n) => Some((*n.l_child, n.value, *n.r_child)),
+            Leaf(_) => None,
+        }
+    }
+
+    // collects mutable references to every value below
+    // this node in their in-order traversal order
+    pub fn ordered_mut<'a>(&'a mut self, order: &mut Vec<&'a mut T>) {
+        if let Internal(n) = self {
+            n.l_child.ordered_mut(order);
+            order.push(&mut n.value);
+            n.r_child.ordered_mut(order);
+        }
+    }
+
     pub fn swap_colour(&mut self) {
         if let Internal(n) = self {
             n.swap_colour();
         } // leaves always black
     }
+
+    // recomputes the derived data of this node's value
+    // from its children, which must already be up to date
+    fn refresh<R: Refresh<T>>(&mut self, aug: &R) {
+        if let Internal(n) = self {
+            aug.refresh(&mut n.value, n.l_child.value(), n.r_child.value());
+        }
+    }
+
+    // refreshes every node on the path from this node
+    // down to its left-most descendant, bottom-up
+    fn refresh_left_spine<R: Refresh<T>>(&mut self, aug: &R) {
+        if let Internal(n) = self {
+            n.l_child.refresh_left_spine(aug);
+        }
+        self.refresh(aug);
+    }
     fn black(&mut self) {
+        count!(recolours if self.colour() != Black);
         match self {
             Internal(n) => n.colour = Black,
             Leaf(c) => *c = Black,
         }
     }
     fn red(&mut self) {
+        count!(recolours if self.colour() != Red);
         match self {
             Internal(n) => n.colour = Red,
             Leaf(c) => *c = Red,
@@ -268,7 +467,12 @@ impl<T> Node<T> {
      / \  / \           / \  /   \
           c                       b
     */
-    fn inner_switcheroo(&mut self, right: bool) {
+    fn inner_switcheroo<R: Refresh<T>>(&mut self, right: bool, aug: &R) {
+        count!(inner_rotations);
+        aug.observe(Step::InnerRotation(
+            self.value().unwrap(),
+            self.peek_child(right).peek_child(!right).value().unwrap(),
+        ));
         let mut tmp = Leaf(Black);
         let mut l_child_tmp = Leaf(Black);
         let mut r_child_tmp = Leaf(Black);
@@ -280,6 +484,9 @@ impl<T> Node<T> {
         m_swap(&mut tmp, self);
         m_swap(self.child(false).child(true), &mut l_child_tmp);
         m_swap(self.child(true).child(false), &mut r_child_tmp);
+        self.child(false).refresh(aug);
+        self.child(true).refresh(aug);
+        self.refresh(aug);
     }
 
     /*
@@ -291,7 +498,12 @@ impl<T> Node<T> {
      / \  / \          / \   / \
              c        a
     */
-    fn outer_switcheroo(&mut self, right: bool) {
+    fn outer_switcheroo<R: Refresh<T>>(&mut self, right: bool, aug: &R) {
+        count!(outer_rotations);
+        aug.observe(Step::OuterRotation(
+            self.value().unwrap(),
+            self.peek_child(right).value().unwrap(),
+        ));
         let mut tmp = Leaf(Black);
         let mut child_tmp = Leaf(Black);
         m_swap(&mut tmp, self.child(right));
@@ -299,26 +511,35 @@ impl<T> Node<T> {
         m_swap(self, &mut child_tmp);
         m_swap(&mut tmp, self);
         m_swap(self.child(!right).child(right), &mut child_tmp);
+        self.child(!right).refresh(aug);
+        self.refresh(aug);
     }
 
     // reorders nodes when required upon insertion
-    fn insert_switcheroo(&mut self, right: bool, inner: bool, recolour: bool) -> Insertion<T> {
+    fn insert_switcheroo<R: Refresh<T>>(
+        &mut self,
+        right: bool,
+        inner: bool,
+        recolour: bool,
+        aug: &R,
+    ) -> Insertion<T> {
         if recolour {
             // doesn't move anything, simply recolours
+            aug.observe(Step::Recolour(self.value().unwrap()));
             self.swap_colour();
             self.child(false).swap_colour();
             self.child(true).swap_colour();
             Recoloured
         } else if inner {
             // realligns the newly inserted value as the new local root
-            self.inner_switcheroo(right);
+            self.inner_switcheroo(right, aug);
             self.swap_colour();
             self.child(!right).swap_colour();
             Success
         } else {
             // realigns the parent of the newly inserted value as the new
             // local root
-            self.outer_switcheroo(right);
+            self.outer_switcheroo(right, aug);
             self.swap_colour();
             self.child(!right).swap_colour();
             Success
@@ -326,24 +547,39 @@ impl<T> Node<T> {
     }
 
     // returns the value if the value was not inserted
-    fn insert_op<P>(&mut self, mut new_v: T, cmp: &P) -> Insertion<T>
+    //
+    // every call to cmp happens on the way down, before anything
+    // in the tree has been moved, so a panicking comparison leaves
+    // the tree exactly as it was; keep it that way
+    fn insert_op<P, R>(&mut self, mut new_v: T, cmp: &P, aug: &R) -> Insertion<T>
     where
         P: Fn(&T, &T) -> std::cmp::Ordering,
+        R: Refresh<T>,
     {
         match self {
             Internal(n) => {
+                count!(comparisons);
                 let order = cmp(&n.value, &new_v);
                 let (res, right, recolour) = match order {
                     Equal => {
                         m_swap(&mut n.value, &mut new_v); // useful if used like a map
                         (Replaced(new_v), true, true)
                     }
-                    Greater => (n.l_child.insert_op(new_v, cmp), false, n.r_child.is_red()),
-                    Less => (n.r_child.insert_op(new_v, cmp), true, n.l_child.is_red()),
+                    Greater => (
+                        n.l_child.insert_op(new_v, cmp, aug),
+                        false,
+                        n.r_child.is_red(),
+                    ),
+                    Less => (
+                        n.r_child.insert_op(new_v, cmp, aug),
+                        true,
+                        n.l_child.is_red(),
+                    ),
                 };
+                self.refresh(aug);
                 match res {
-                    InvalidLeft => self.insert_switcheroo(right, right, recolour),
-                    InvalidRight => self.insert_switcheroo(right, !right, recolour),
+                    InvalidLeft => self.insert_switcheroo(right, right, recolour, aug),
+                    InvalidRight => self.insert_switcheroo(right, !right, recolour, aug),
                     Recoloured => {
                         if self.is_red() && self.child(right).is_red() {
                             if right {
@@ -370,6 +606,7 @@ impl<T> Node<T> {
             }
             Leaf(_) => {
                 *self = Node::new(new_v);
+                self.refresh(aug);
                 Inserted
             }
         }
@@ -380,7 +617,16 @@ impl<T> Node<T> {
     where
         P: Fn(&T, &T) -> std::cmp::Ordering,
     {
-        let res = self.insert_op(new_v, cmp);
+        self.insert_with(new_v, cmp, &())
+    }
+
+    // as insert, keeping derived data up to date with aug
+    pub fn insert_with<P, R>(&mut self, new_v: T, cmp: &P, aug: &R) -> Option<T>
+    where
+        P: Fn(&T, &T) -> std::cmp::Ordering,
+        R: Refresh<T>,
+    {
+        let res = self.insert_op(new_v, cmp, aug);
         if self.is_red() {
             self.swap_colour();
         }
@@ -396,15 +642,28 @@ impl<T> Node<T> {
     // should only be called on the PARENT of a
     // double black node (right is true if the double
     // black is the right child, false otherwise)
-    fn deletion_switcheroo(&mut self, right: bool) -> bool {
+    //
+    // rotations keep the values below self the same, so the
+    // derived data of self's ancestors stays valid
+    fn deletion_switcheroo<R: Refresh<T>>(&mut self, right: bool, aug: &R) -> bool {
+        count!(deletion_fixups);
         let mut was_red = false;
+        // a double black always has a sibling, as the
+        // black heights either side of self are equal
+        let observe = |node: &Node<T>, case| {
+            if let (Some(parent), Some(sibling)) = (node.value(), node.peek_child(!right).value())
+            {
+                aug.observe(Step::DoubleBlack(case, parent, sibling));
+            }
+        };
 
         // unique case
         if self.child(!right).is_red() {
-            self.outer_switcheroo(!right);
+            observe(self, DoubleBlackCase::RedSibling);
+            self.outer_switcheroo(!right, aug);
             self.black();
             self.child(right).red();
-            self.child(right).deletion_switcheroo(right);
+            self.child(right).deletion_switcheroo(right, aug);
             if !self.child(right).is_double_black() {
                 return false;
             }
@@ -412,10 +671,12 @@ impl<T> Node<T> {
 
         // do switcheroos if required
         if self.child(!right).child_safe(right).is_red() {
-            self.inner_switcheroo(!right);
+            observe(self, DoubleBlackCase::RedNephew);
+            self.inner_switcheroo(!right, aug);
             was_red = true;
         } else if self.child(!right).child_safe(!right).is_red() {
-            self.outer_switcheroo(!right);
+            observe(self, DoubleBlackCase::RedNephew);
+            self.outer_switcheroo(!right, aug);
             was_red = true;
         }
 
@@ -429,6 +690,12 @@ impl<T> Node<T> {
             self.child(right).child_safe(right).black();
             false
         } else {
+            let case = if self.is_red() {
+                DoubleBlackCase::Absorbed
+            } else {
+                DoubleBlackCase::Propagated
+            };
+            observe(self, case);
             self.child(right).black();
             self.child(!right).red();
             if self.is_red() {
@@ -444,7 +711,7 @@ impl<T> Node<T> {
     // either swap the left-most right descendant, or just
     // swap the immediate left child if the right child
     // is a leaf
-    fn swap_innermost_descendant(&mut self) -> Removal<T> {
+    fn swap_innermost_descendant<R: Refresh<T>>(&mut self, aug: &R) -> Removal<T> {
         let mut tmp = Leaf(Black);
         let mut doubled = false;
         if !self.get_right().is_leaf() {
@@ -463,6 +730,8 @@ impl<T> Node<T> {
             }
             m_swap(&mut tmp, innermost);
             m_swap(&mut self.innards().value, &mut tmp.innards().value);
+            self.get_right_mut().refresh_left_spine(aug);
+            self.refresh(aug);
         } else {
             m_swap(self.get_left_mut(), &mut tmp);
             if self.is_black() {
@@ -482,33 +751,42 @@ impl<T> Node<T> {
         }
     }
 
-    fn bring_double_up_root(&mut self) -> bool {
-        if self.get_right().is_double_black() {
-            self.deletion_switcheroo(true)
-        } else {
-            self.get_right_mut().bring_double_up()
-        }
-    }
-    fn bring_double_up(&mut self) -> bool {
-        if self.get_left().is_double_black() {
-            self.deletion_switcheroo(false)
-        } else {
-            self.get_left_mut().bring_double_up()
-        }
+    // resolves the double black left behind on the left spine
+    // below self by swap_innermost_descendant, one level at a
+    // time on the way back up. returns true if self is left
+    // double black in turn
+    fn bring_double_up<R: Refresh<T>>(&mut self, aug: &R) -> bool {
+        let doubled =
+            self.get_left().is_double_black() || self.get_left_mut().bring_double_up(aug);
+        doubled && self.deletion_switcheroo(false, aug)
     }
 
-    fn remove_result_step(&mut self, res: Removal<T>, right: bool) -> Removal<T> {
+    // a Doubled result always means the node it came
+    // from is itself double black
+    fn remove_result_step<R: Refresh<T>>(
+        &mut self,
+        res: Removal<T>,
+        right: bool,
+        aug: &R,
+    ) -> Removal<T> {
         match res {
-            Match => self.swap_innermost_descendant(),
+            Match => match self.swap_innermost_descendant(aug) {
+                // the value came from the right subtree, so the
+                // double black is somewhere below it
+                Doubled(n) if !self.is_double_black() => {
+                    let doubled = (self.get_right().is_double_black()
+                        || self.get_right_mut().bring_double_up(aug))
+                        && self.deletion_switcheroo(true, aug);
+                    if doubled {
+                        Doubled(n)
+                    } else {
+                        Removed(n)
+                    }
+                }
+                res => res,
+            },
             Doubled(n) => {
-                let doubled = if self.child(right).is_double_black()
-                    || self.child(right).bring_double_up_root()
-                {
-                    self.deletion_switcheroo(right)
-                } else {
-                    false
-                };
-                if doubled {
+                if self.deletion_switcheroo(right, aug) {
                     Doubled(n)
                 } else {
                     Removed(n)
@@ -519,42 +797,67 @@ impl<T> Node<T> {
         }
     }
 
-    fn remove_op<K, P>(&mut self, val: &K, cmp: &P) -> Removal<T>
+    // as with insertion, cmp is only called while descending so
+    // nothing has been restructured if it panics
+    fn remove_op<K, P, R>(&mut self, val: &K, cmp: &P, aug: &R) -> Removal<T>
     where
         P: Fn(&K, &T) -> std::cmp::Ordering,
+        R: Refresh<T>,
     {
         match self {
             Internal(n) => {
+                count!(comparisons);
                 let order = cmp(val, &n.value);
                 let (res, right) = match order {
                     Equal => (Match, true),
-                    Less => (n.l_child.remove_op(val, cmp), false),
-                    Greater => (n.r_child.remove_op(val, cmp), true),
+                    Less => (n.l_child.remove_op(val, cmp, aug), false),
+                    Greater => (n.r_child.remove_op(val, cmp, aug), true),
                 };
-                self.remove_result_step(res, right)
+                self.removal_fixup(res, right, aug)
             }
             Leaf(_) => NotFound,
         }
     }
 
-    fn pop_op(&mut self, back: bool) -> Removal<T> {
-        let mut cur = self;
-        while !cur.is_leaf() {
-            if cur.child(back).is_leaf() {
-                return cur.remove_result_step(Match, true);
-            } else {
-                cur = cur.child(back);
-            }
+    // finishes a removal below self, refreshing self if a value was removed
+    fn removal_fixup<R: Refresh<T>>(
+        &mut self,
+        res: Removal<T>,
+        right: bool,
+        aug: &R,
+    ) -> Removal<T> {
+        let res = self.remove_result_step(res, right, aug);
+        if !matches!(res, NotFound) {
+            self.refresh(aug);
+        }
+        res
+    }
+
+    // descends recursively, like remove_op, so every node on
+    // the way back up gets to fix a double black below it
+    fn pop_op<R: Refresh<T>>(&mut self, back: bool, aug: &R) -> Removal<T> {
+        if self.is_leaf() {
+            NotFound
+        } else if self.child(back).is_leaf() {
+            self.removal_fixup(Match, true, aug)
+        } else {
+            let res = self.child(back).pop_op(back, aug);
+            self.removal_fixup(res, back, aug)
         }
-        NotFound
     }
 
     pub fn pop(&mut self, back: bool) -> Option<T> {
-        match self.pop_op(back) {
+        self.pop_with(back, &())
+    }
+
+    // as pop, keeping derived data up to date with aug
+    pub fn pop_with<R: Refresh<T>>(&mut self, back: bool, aug: &R) -> Option<T> {
+        match self.pop_op(back, aug) {
             NotFound => None,
             Removed(v) => Some(v),
             Doubled(v) => {
-                self.swap_colour();
+                // the root absorbs the extra black, even when it is a leaf
+                self.black();
                 Some(v)
             }
             // uhh, shouldn't ever happen if I've coded it right
@@ -567,11 +870,21 @@ impl<T> Node<T> {
     where
         P: Fn(&K, &T) -> std::cmp::Ordering,
     {
-        match self.remove_op(val, cmp) {
+        self.remove_with(val, cmp, &())
+    }
+
+    // as remove, keeping derived data up to date with aug
+    pub fn remove_with<K, P, R>(&mut self, val: &K, cmp: &P, aug: &R) -> Option<T>
+    where
+        P: Fn(&K, &T) -> std::cmp::Ordering,
+        R: Refresh<T>,
+    {
+        match self.remove_op(val, cmp, aug) {
             NotFound => None,
             Removed(v) => Some(v),
             Doubled(v) => {
-                self.swap_colour();
+                // the root absorbs the extra black, even when it is a leaf
+                self.black();
                 Some(v)
             }
             // uhh, shouldn't ever happen if I've coded it right
@@ -579,6 +892,123 @@ impl<T> Node<T> {
         }
     }
 
+    // the number of black nodes, leaf included, on the path from
+    // this node down to its left-most leaf. in a valid tree
+    // every path gives the same count
+    pub fn spine_black_height(&self) -> usize {
+        let mut cur = self;
+        let mut height = 1;
+        while let Internal(n) = cur {
+            if n.is_black() {
+                height += 1;
+            }
+            cur = &n.l_child;
+        }
+        height
+    }
+
+    // joins two valid trees and a value that comes after every
+    // value in left and before every value in right into one
+    // valid tree, in time proportional to the difference
+    // between the black heights of left and right
+    pub fn join_with<R: Refresh<T>>(
+        mut left: Node<T>,
+        mid: T,
+        mut right: Node<T>,
+        aug: &R,
+    ) -> Node<T> {
+        // a red root can always be made black
+        left.black();
+        right.black();
+        let l_height = left.spine_black_height();
+        let r_height = right.spine_black_height();
+        let mut joined = if l_height >= r_height {
+            left.join_down(mid, right, l_height, r_height, true, aug)
+        } else {
+            right.join_down(mid, left, r_height, l_height, false, aug)
+        };
+        joined.black();
+        joined
+    }
+
+    // descends the right (or left) spine of self to a black node
+    // with the same black height as other and replaces it with a
+    // red node holding mid, the black node and other. a red node
+    // left below another red one is fixed on the way back up
+    fn join_down<R: Refresh<T>>(
+        mut self,
+        mid: T,
+        other: Node<T>,
+        height: usize,
+        other_height: usize,
+        right: bool,
+        aug: &R,
+    ) -> Node<T> {
+        if height == other_height && self.is_black() {
+            let (l_child, r_child) = if right { (self, other) } else { (other, self) };
+            let mut joined = Internal(Innards {
+                value: mid,
+                colour: Red,
+                l_child: Box::new(l_child),
+                r_child: Box::new(r_child),
+            });
+            joined.refresh(aug);
+            return joined;
+        }
+        let below = if self.is_black() { height - 1 } else { height };
+        let child = std::mem::take(self.child(right));
+        *self.child(right) = child.join_down(mid, other, below, other_height, right, aug);
+        if self.is_black()
+            && self.child(right).is_red()
+            && self.child(right).child_safe(right).is_red()
+        {
+            self.child(right).child(right).black();
+            self.outer_switcheroo(right, aug);
+        } else {
+            self.refresh(aug);
+        }
+        self
+    }
+
+    // splits the tree into the values before and after the one for
+    // which locate returns Equal, which is returned between them
+    // if there is one. locate tells whether that value is to the
+    // left (Less) or right (Greater) of the value it is given and
+    // is called once on each node on the way down
+    pub fn split_with<P, R>(self, locate: &P, aug: &R) -> (Node<T>, Option<T>, Node<T>)
+    where
+        P: Fn(&T) -> std::cmp::Ordering,
+        R: Refresh<T>,
+    {
+        let n = match self {
+            Internal(n) => n,
+            Leaf(_) => return (Leaf(Black), None, Leaf(Black)),
+        };
+        let Innards {
+            value,
+            l_child,
+            r_child,
+            ..
+        } = n;
+        count!(comparisons);
+        match locate(&value) {
+            Equal => {
+                let (mut l_child, mut r_child) = (*l_child, *r_child);
+                l_child.black();
+                r_child.black();
+                (l_child, Some(value), r_child)
+            }
+            Less => {
+                let (before, found, after) = l_child.split_with(locate, aug);
+                (before, found, Node::join_with(after, value, *r_child, aug))
+            }
+            Greater => {
+                let (before, found, after) = r_child.split_with(locate, aug);
+                (Node::join_with(*l_child, value, before, aug), found, after)
+            }
+        }
+    }
+
     pub fn get<K, P>(&self, val: &K, cmp: &P) -> Option<&T>
     where
         P: Fn(&K, &T) -> std::cmp::Ordering,
@@ -586,6 +1016,7 @@ impl<T> Node<T> {
         let mut cur = self;
         while !cur.is_leaf() {
             let cur_val = cur.value();
+            count!(comparisons);
             let order = cmp(val, cur_val.unwrap());
             match order {
                 Equal => return cur_val,
@@ -599,7 +1030,27 @@ impl<T> Node<T> {
         }
     }
 
-    #[cfg(feature = "map")]
+    // the greatest value that does not come after val
+    #[cfg(feature = "set")]
+    pub fn floor<K, P>(&self, val: &K, cmp: &P) -> Option<&T>
+    where
+        P: Fn(&K, &T) -> std::cmp::Ordering,
+    {
+        let mut cur = self;
+        let mut found = None;
+        while let Some(cur_val) = cur.value() {
+            count!(comparisons);
+            match cmp(val, cur_val) {
+                Less => cur = cur.get_left(),
+                _ => {
+                    found = Some(cur_val);
+                    cur = cur.get_right();
+                }
+            }
+        }
+        found
+    }
+
     pub fn get_mut<K, P>(&mut self, val: &K, cmp: &P) -> Option<&mut T>
     where
         P: Fn(&K, &T) -> std::cmp::Ordering,
@@ -607,6 +1058,7 @@ impl<T> Node<T> {
         let mut cur = self;
         while !cur.is_leaf() {
             let cur_val = cur.value().unwrap();
+            count!(comparisons);
             let order = cmp(val, cur_val);
             match order {
                 Equal => return cur.value_mut(),
@@ -620,6 +1072,17 @@ impl<T> Node<T> {
         }
     }
 
+    #[cfg(feature = "map")]
+    pub fn peek_mut(&mut self, back: bool) -> Option<&mut T> {
+        if !self.is_leaf() && !self.peek_child(back).is_leaf() {
+            return self.child(back).peek_mut(back);
+        }
+        match self {
+            Internal(n) => Some(&mut n.value),
+            _ => None,
+        }
+    }
+
     pub fn peek(&self, back: bool) -> Option<&T> {
         let mut cur = self;
         while !cur.is_leaf() {
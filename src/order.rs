@@ -0,0 +1,207 @@
+use crate::Comparator;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter, Result};
+
+/// The strategy by which an RBBase orders its
+/// elements. `L` is the type being searched for
+/// and `R` the type stored in the tree, which
+/// allows lookups by a type other than the stored
+/// one where the strategy supports it.
+pub trait TreeOrder<L, R = L> {
+    /// Returns the position of `l` relative to `r`.
+    fn compare(&self, l: &L, r: &R) -> Ordering;
+}
+
+/// Orders elements by their PartialOrd implementation,
+/// as used by RBTree. Panics when two elements cannot
+/// be compared.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PartialOrdering;
+
+impl<K: PartialOrd<T>, T> TreeOrder<K, T> for PartialOrdering {
+    fn compare(&self, l: &K, r: &T) -> Ordering {
+        l.partial_cmp(r).unwrap()
+    }
+}
+
+/// Orders elements using a closure, as used by RBQueue.
+#[derive(Clone, Copy)]
+pub struct ByFn<P> {
+    cmp: P,
+}
+
+impl<P> ByFn<P> {
+    pub fn new(cmp: P) -> ByFn<P> {
+        ByFn { cmp }
+    }
+}
+
+impl<T, P: Fn(&T, &T) -> Ordering> TreeOrder<T> for ByFn<P> {
+    fn compare(&self, l: &T, r: &T) -> Ordering {
+        (self.cmp)(l, r)
+    }
+}
+
+/// Orders elements using a Comparator, as used by
+/// RBTreeWithCmp. The comparison function is fetched
+/// from the Comparator once and reused thereafter.
+#[allow(clippy::type_complexity)]
+pub struct ByComparator<T, F> {
+    cmp: F,
+    f: Box<dyn Fn(&T, &T) -> Ordering>,
+}
+
+impl<T, F: Comparator<T>> ByComparator<T, F> {
+    pub fn new(cmp: F) -> ByComparator<T, F> {
+        let f = cmp.cmp();
+        ByComparator { cmp, f }
+    }
+
+    /// Returns the Comparator this ordering was built from.
+    pub fn comparator(&self) -> &F {
+        &self.cmp
+    }
+}
+
+impl<T, F: Comparator<T>> TreeOrder<T> for ByComparator<T, F> {
+    fn compare(&self, l: &T, r: &T) -> Ordering {
+        (self.f)(l, r)
+    }
+}
+
+impl<T, F: Comparator<T> + Clone> Clone for ByComparator<T, F> {
+    fn clone(&self) -> Self {
+        ByComparator::new(self.cmp.clone())
+    }
+}
+
+impl<T, F: Comparator<T> + Default> Default for ByComparator<T, F> {
+    fn default() -> Self {
+        ByComparator::new(F::default())
+    }
+}
+
+impl<T, F: Comparator<T> + Debug> Debug for ByComparator<T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_tuple("ByComparator").field(&self.cmp).finish()
+    }
+}
+
+/// Orders references by the values they point to,
+/// using a borrowed ordering. Used for sets of
+/// references into another container, such as the
+/// keyset of an RBMapWithCmp.
+pub struct ByRef<'a, O> {
+    order: &'a O,
+}
+
+impl<'a, O> ByRef<'a, O> {
+    pub fn new(order: &'a O) -> ByRef<'a, O> {
+        ByRef { order }
+    }
+}
+
+impl<'a, O> Clone for ByRef<'a, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, O> Copy for ByRef<'a, O> {}
+
+impl<'a, 'b, T, O: TreeOrder<T>> TreeOrder<&'b T> for ByRef<'a, O> {
+    fn compare(&self, l: &&'b T, r: &&'b T) -> Ordering {
+        self.order.compare(*l, *r)
+    }
+}
+
+/// Turns a C-like comparison result into an Ordering,
+/// as used by `new_c_queue!`. Values below zero are
+/// `Less`, zero is `Equal` and values above zero are
+/// `Greater`.
+///
+/// Floats treat `-0.0` as zero and panic on NaN, in
+/// the same way PartialOrdering panics on values that
+/// cannot be compared.
+/// # Example:
+/// ```
+/// use rb_tree::order::Sign;
+/// use std::cmp::Ordering::{Equal, Greater, Less};
+///
+/// assert_eq!(256i64.sign(), Greater);
+/// assert_eq!((-200i64).sign(), Less);
+/// assert_eq!(0.4f64.sign(), Greater);
+/// assert_eq!((-0.0f32).sign(), Equal);
+/// ```
+pub trait Sign {
+    fn sign(self) -> Ordering;
+}
+
+impl Sign for Ordering {
+    fn sign(self) -> Ordering {
+        self
+    }
+}
+
+macro_rules! int_sign {
+    ($($t:ty),*) => {
+        $(
+            impl Sign for $t {
+                fn sign(self) -> Ordering {
+                    self.cmp(&0)
+                }
+            }
+        )*
+    };
+}
+
+int_sign!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! float_sign {
+    ($($t:ty),*) => {
+        $(
+            impl Sign for $t {
+                fn sign(self) -> Ordering {
+                    self.partial_cmp(&0.0)
+                        .expect("comparison returned NaN")
+                }
+            }
+        )*
+    };
+}
+
+float_sign!(f32, f64);
+
+/// Compares two values in ascending order without
+/// the overflow risk of subtracting them, for use
+/// with RBQueue or `new_c_queue!`.
+/// # Example:
+/// ```
+/// use rb_tree::RBQueue;
+/// use rb_tree::order::ascending;
+///
+/// let mut q = RBQueue::new(ascending);
+/// q.insert(i64::MAX);
+/// q.insert(i64::MIN);
+/// assert_eq!(q.ordered(), [&i64::MIN, &i64::MAX]);
+/// ```
+pub fn ascending<T: PartialOrd>(l: &T, r: &T) -> Ordering {
+    PartialOrdering.compare(l, r)
+}
+
+/// Compares two values in descending order without
+/// the overflow risk of subtracting them, for use
+/// with RBQueue or `new_c_queue!`.
+/// # Example:
+/// ```
+/// use rb_tree::RBQueue;
+/// use rb_tree::order::descending;
+///
+/// let mut q = RBQueue::new(descending);
+/// q.insert(i64::MIN);
+/// q.insert(i64::MAX);
+/// assert_eq!(q.ordered(), [&i64::MAX, &i64::MIN]);
+/// ```
+pub fn descending<T: PartialOrd>(l: &T, r: &T) -> Ordering {
+    PartialOrdering.compare(r, l)
+}
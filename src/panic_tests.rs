@@ -0,0 +1,362 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::node::Node;
+use crate::{Comparator, RBMap, RBMapWithCmp, RBQueue, RBTree, RBTreeWithCmp};
+
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+
+const SIZE: i32 = 64;
+
+// counts down the comparisons made and panics
+// on the one that brings it to zero
+#[derive(Clone)]
+struct Fuse {
+    left: Rc<Cell<usize>>,
+}
+
+impl Fuse {
+    fn new() -> Fuse {
+        Fuse {
+            left: Rc::new(Cell::new(0)),
+        }
+    }
+
+    fn arm(&self, after: usize) {
+        self.left.set(after);
+    }
+
+    fn disarm(&self) {
+        self.left.set(0);
+    }
+
+    fn compare(&self, l: &i32, r: &i32) -> Ordering {
+        match self.left.get() {
+            0 => {}
+            1 => {
+                self.left.set(0);
+                panic!("injected comparison panic");
+            }
+            n => self.left.set(n - 1),
+        }
+        l.cmp(r)
+    }
+}
+
+impl Comparator<i32> for Fuse {
+    fn cmp(&self) -> Box<dyn Fn(&i32, &i32) -> Ordering> {
+        let fuse = self.clone();
+        Box::new(move |l, r| fuse.compare(l, r))
+    }
+}
+
+fn values() -> Vec<i32> {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(26);
+    let mut values: Vec<i32> = (0..SIZE).map(|v| v * 2).collect();
+    values.shuffle(&mut rng);
+    values
+}
+
+fn assert_valid<T>(root: &Node<T>, len: usize, ordered: Vec<&i32>) {
+    assert!(root.black_height().is_some());
+    assert!(!root.is_red());
+    assert_eq!(ordered.len(), len);
+    assert!(ordered.windows(2).all(|w| w[0] < w[1]));
+}
+
+// runs op against a freshly built tree with a panic injected at
+// each comparison in turn until op manages to finish, running
+// check after every interrupted attempt
+macro_rules! inject_panics {
+    ($build:expr, $tree:ident, $op:expr, $check:expr) => {{
+        let mut at = 1;
+        loop {
+            let fuse = Fuse::new();
+            let mut $tree = $build(&fuse);
+            fuse.arm(at);
+            let res = catch_unwind(AssertUnwindSafe(|| $op));
+            fuse.disarm();
+            if res.is_ok() {
+                break;
+            }
+            $check;
+            at += 1;
+        }
+        assert!(at > 1);
+    }};
+}
+
+fn queue(fuse: &Fuse) -> RBQueue<i32, impl Fn(&i32, &i32) -> Ordering> {
+    let f = fuse.clone();
+    let mut q = RBQueue::new(move |l: &i32, r: &i32| f.compare(l, r));
+    for v in values() {
+        q.insert(v);
+    }
+    q
+}
+
+fn tree(fuse: &Fuse) -> RBTreeWithCmp<i32, Fuse> {
+    let mut t = RBTreeWithCmp::new(fuse.clone());
+    for v in values() {
+        t.insert(v);
+    }
+    t
+}
+
+fn map(fuse: &Fuse) -> RBMapWithCmp<i32, i32, Fuse> {
+    let mut m = RBMapWithCmp::new(fuse.clone());
+    for v in values() {
+        m.insert(v, -v);
+    }
+    m
+}
+
+fn full() -> Vec<i32> {
+    (0..SIZE).map(|v| v * 2).collect()
+}
+
+#[test]
+fn test_queue_insert_panics() {
+    for new in [-1, 7, 63, SIZE * 2 + 1, 10] {
+        inject_panics!(queue, q, q.insert(new), {
+            assert_valid(&q.root, q.len(), q.ordered());
+            assert_eq!(q.iter().copied().collect::<Vec<i32>>(), full());
+        });
+    }
+}
+
+#[test]
+fn test_queue_remove_panics() {
+    for old in [0, 10, 64, 126, 11] {
+        inject_panics!(queue, q, q.remove(&old), {
+            assert_valid(&q.root, q.len(), q.ordered());
+            assert_eq!(q.iter().copied().collect::<Vec<i32>>(), full());
+        });
+    }
+}
+
+#[test]
+fn test_queue_retain_panics() {
+    for at in 1..=SIZE {
+        let fuse = Fuse::new();
+        let mut q = queue(&fuse);
+        let mut seen = 0;
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            q.retain(|v| {
+                seen += 1;
+                if seen == at {
+                    panic!("injected predicate panic");
+                }
+                v % 4 == 0
+            })
+        }));
+        assert!(res.is_err());
+        assert_valid(&q.root, q.len(), q.ordered());
+        let expected = full()
+            .into_iter()
+            .enumerate()
+            .filter(|(i, v)| *i as i32 >= at - 1 || v % 4 == 0)
+            .map(|(_, v)| v)
+            .collect::<Vec<i32>>();
+        assert_eq!(q.iter().copied().collect::<Vec<i32>>(), expected);
+
+        // still usable afterwards
+        q.insert(1);
+        assert!(q.contains(&1));
+        assert_valid(&q.root, q.len(), q.ordered());
+    }
+}
+
+#[test]
+fn test_cmp_tree_insert_panics() {
+    for new in [-1, 7, 63, SIZE * 2 + 1, 10] {
+        inject_panics!(tree, t, t.insert(new), {
+            assert_valid(&t.root, t.len(), t.ordered());
+            assert_eq!(t.iter().copied().collect::<Vec<i32>>(), full());
+        });
+    }
+}
+
+#[test]
+fn test_cmp_tree_remove_panics() {
+    for old in [0, 10, 64, 126, 11] {
+        inject_panics!(tree, t, t.take(&old), {
+            assert_valid(&t.root, t.len(), t.ordered());
+            assert_eq!(t.iter().copied().collect::<Vec<i32>>(), full());
+        });
+    }
+}
+
+#[test]
+fn test_cmp_map_insert_panics() {
+    for new in [-1, 7, 63, SIZE * 2 + 1, 10] {
+        inject_panics!(map, m, m.insert(new, new), {
+            assert_valid(
+                &m.map.root,
+                m.len(),
+                m.ordered().iter().map(|p| p.0).collect(),
+            );
+            assert!(m.iter().all(|(k, v)| *k == -*v));
+        });
+    }
+}
+
+#[test]
+fn test_cmp_map_remove_panics() {
+    for old in [0, 10, 64, 126, 11] {
+        inject_panics!(map, m, m.remove(&old), {
+            assert_valid(
+                &m.map.root,
+                m.len(),
+                m.ordered().iter().map(|p| p.0).collect(),
+            );
+            assert_eq!(m.len(), SIZE as usize);
+        });
+    }
+}
+
+fn others() -> Vec<i32> {
+    let threes = (0..SIZE).filter(|v| v % 3 == 0).map(|v| v * 2);
+    threes.chain((0..SIZE / 2).map(|v| v * 4 + 1)).collect()
+}
+
+// a set operation interrupted by a panic leaves the tree holding the
+// result for the values below some point and its own values from there on
+fn assert_partial(found: Vec<i32>, expected: &[i32]) {
+    let mut cuts = full();
+    cuts.extend(others());
+    cuts.push(i32::MAX);
+    assert!(cuts.into_iter().any(|cut| {
+        let done = expected.iter().filter(|v| **v < cut);
+        let rest = full().into_iter().filter(|v| *v >= cut);
+        done.copied().chain(rest).eq(found.iter().copied())
+    }));
+}
+
+// applies each set operation, by value and by reference, with a
+// panic injected at each comparison in turn until it finishes
+macro_rules! inject_set_op_panics {
+    ($build:expr) => {{
+        let a: BTreeSet<i32> = full().into_iter().collect();
+        let b: BTreeSet<i32> = others().into_iter().collect();
+        let expected: [Vec<i32>; 4] = [
+            a.union(&b).copied().collect(),
+            a.intersection(&b).copied().collect(),
+            a.difference(&b).copied().collect(),
+            a.symmetric_difference(&b).copied().collect(),
+        ];
+        for (op, expected) in expected.iter().enumerate() {
+            for owned in [true, false] {
+                let mut at = 1;
+                loop {
+                    let fuse = Fuse::new();
+                    let mut t = $build(&fuse);
+                    let mut other = $build(&fuse);
+                    other.clear();
+                    others().into_iter().for_each(|v| {
+                        other.insert(v);
+                    });
+                    fuse.arm(at);
+                    let res = catch_unwind(AssertUnwindSafe(|| match (op, owned) {
+                        (0, true) => t |= other,
+                        (0, false) => t |= &other,
+                        (1, true) => t &= other,
+                        (1, false) => t &= &other,
+                        (2, true) => t -= other,
+                        (2, false) => t -= &other,
+                        (_, true) => t ^= other,
+                        (_, false) => t ^= &other,
+                    }));
+                    fuse.disarm();
+                    assert_valid(&t.root, t.len(), t.ordered());
+                    let found = t.iter().copied().collect::<Vec<i32>>();
+                    if res.is_ok() {
+                        assert_eq!(&found, expected);
+                        break;
+                    }
+                    assert_partial(found, expected);
+                    at += 1;
+                }
+                assert!(at > 1);
+            }
+        }
+    }};
+}
+
+#[test]
+fn test_queue_set_op_panics() {
+    inject_set_op_panics!(queue);
+}
+
+#[test]
+fn test_cmp_tree_set_op_panics() {
+    inject_set_op_panics!(tree);
+}
+
+#[derive(Debug)]
+struct Touchy(i32);
+
+impl PartialEq for Touchy {
+    fn eq(&self, other: &Touchy) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl PartialOrd for Touchy {
+    fn partial_cmp(&self, other: &Touchy) -> Option<Ordering> {
+        if self.0 == 13 || other.0 == 13 {
+            panic!("unlucky comparison");
+        }
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+#[test]
+fn test_partial_ord_panics() {
+    let mut t = RBTree::new();
+    for v in values() {
+        t.insert(Touchy(v));
+    }
+    assert!(catch_unwind(AssertUnwindSafe(|| t.insert(Touchy(13)))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| t.remove(&Touchy(13)))).is_err());
+    assert_eq!(t.len(), SIZE as usize);
+    assert!(t.root.black_height().is_some());
+    assert!(t.iter().map(|v| v.0).eq(full()));
+
+    let mut m = RBMap::new();
+    for v in values() {
+        m.insert(v, v);
+    }
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        m.retain(|k, v| {
+            if *k == 40 {
+                panic!("injected predicate panic");
+            }
+            *v += 1;
+            true
+        })
+    }));
+    assert!(res.is_err());
+    assert_eq!(m.len(), SIZE as usize);
+    assert!(m.map.root.black_height().is_some());
+    assert!(m
+        .iter()
+        .all(|(k, v)| if *k < 40 { *v == k + 1 } else { v == k }));
+}
+
+#[test]
+fn test_from_sorted() {
+    for len in 0..130 {
+        let root = Node::from_sorted((0..len).collect());
+        assert!(root.black_height().is_some());
+        let mut out = Vec::new();
+        root.into_sorted(&mut out);
+        assert_eq!(out, (0..len).collect::<Vec<i32>>());
+    }
+}
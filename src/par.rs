@@ -0,0 +1,516 @@
+//! Parallel iteration and bulk operations using rayon,
+//! enabled by the `rayon` feature.
+//!
+//! Parallel iterators split a tree recursively along its
+//! subtrees, which the tree's balance keeps roughly even.
+//! Collecting or extending in parallel sorts the new values,
+//! removes duplicates (later values replace earlier ones, as
+//! they do with `insert`) and builds the tree in one pass.
+
+use crate::helpers::insert_left_down;
+use crate::node::{count, counting, Node};
+use crate::order::{PartialOrdering, TreeOrder};
+use crate::RBBase;
+#[cfg(feature = "map")]
+use crate::{mapper::Mapper, RBMap};
+use rayon::iter::plumbing::{bridge_unindexed, Folder, UnindexedConsumer, UnindexedProducer};
+use rayon::iter::{FromParallelIterator, IntoParallelIterator, ParallelExtend, ParallelIterator};
+use rayon::slice::ParallelSliceMut;
+use std::cmp::Ordering::{self, Equal, Greater};
+
+// the values in a subtree, preceded by first. splitting hands
+// the left subtree to one half and the subtree's root and
+// right subtree to the other
+struct Subtree<'a, T> {
+    first: Option<&'a T>,
+    node: &'a Node<T>,
+}
+
+impl<'a, T: Sync> UnindexedProducer for Subtree<'a, T> {
+    type Item = &'a T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        match self.node.value() {
+            Some(value) => (
+                Subtree {
+                    first: self.first,
+                    node: self.node.get_left(),
+                },
+                Some(Subtree {
+                    first: Some(value),
+                    node: self.node.get_right(),
+                }),
+            ),
+            None => (self, None),
+        }
+    }
+
+    fn fold_with<F: Folder<&'a T>>(self, mut folder: F) -> F {
+        if let Some(first) = self.first {
+            folder = folder.consume(first);
+        }
+        let mut ordered = Vec::new();
+        insert_left_down(self.node, &mut ordered);
+        folder.consume_iter(std::iter::from_fn(|| {
+            let next = ordered.pop()?;
+            insert_left_down(next.get_right(), &mut ordered);
+            next.value()
+        }))
+    }
+}
+
+// as Subtree, for mutable references
+#[cfg(feature = "map")]
+struct SubtreeMut<'a, T> {
+    first: Option<&'a mut T>,
+    node: &'a mut Node<T>,
+}
+
+#[cfg(feature = "map")]
+impl<'a, T: Send> UnindexedProducer for SubtreeMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        if self.node.is_leaf() {
+            return (self, None);
+        }
+        let (left, value, right) = self.node.parts_mut().unwrap();
+        (
+            SubtreeMut {
+                first: self.first,
+                node: left,
+            },
+            Some(SubtreeMut {
+                first: Some(value),
+                node: right,
+            }),
+        )
+    }
+
+    fn fold_with<F: Folder<&'a mut T>>(self, mut folder: F) -> F {
+        if let Some(first) = self.first {
+            folder = folder.consume(first);
+        }
+        // each value waits on the stack with its right subtree
+        let mut ordered = Vec::new();
+        let push_left_down = |mut node: &'a mut Node<T>, ordered: &mut Vec<_>| {
+            while let Some((left, value, right)) = node.parts_mut() {
+                ordered.push((value, right));
+                node = left;
+            }
+        };
+        push_left_down(self.node, &mut ordered);
+        folder.consume_iter(std::iter::from_fn(|| {
+            let (value, right) = ordered.pop()?;
+            push_left_down(right, &mut ordered);
+            Some(value)
+        }))
+    }
+}
+
+// as Subtree, taking ownership of the values
+struct IntoSubtree<T> {
+    first: Option<T>,
+    node: Node<T>,
+}
+
+impl<T: Send> UnindexedProducer for IntoSubtree<T> {
+    type Item = T;
+
+    fn split(self) -> (Self, Option<Self>) {
+        if self.node.is_leaf() {
+            return (self, None);
+        }
+        let (left, value, right) = self.node.into_parts().unwrap();
+        (
+            IntoSubtree {
+                first: self.first,
+                node: left,
+            },
+            Some(IntoSubtree {
+                first: Some(value),
+                node: right,
+            }),
+        )
+    }
+
+    fn fold_with<F: Folder<T>>(self, folder: F) -> F {
+        let mut values = Vec::new();
+        values.extend(self.first);
+        self.node.into_sorted(&mut values);
+        folder.consume_iter(values)
+    }
+}
+
+// plans the merge of two sorted runs into one without duplicates,
+// keeping the last of any equal values so that newer ones replace
+// older ones, as they do when inserted one at a time. each pick
+// names a run, true for new, and an index into it. planning moves
+// nothing, so a panicking comparison cannot lose any values
+fn merge_plan<T, P: Fn(&T, &T) -> Ordering>(old: &[T], new: &[T], cmp: &P) -> Vec<(bool, usize)> {
+    let mut picks: Vec<(bool, usize)> = Vec::with_capacity(old.len() + new.len());
+    let (mut o, mut n) = (0, 0);
+    let value = |(is_new, i): (bool, usize)| if is_new { &new[i] } else { &old[i] };
+    loop {
+        let next = match (old.get(o), new.get(n)) {
+            (Some(ov), Some(nv)) if cmp(ov, nv) == Greater => (true, n),
+            (Some(_), _) => (false, o),
+            (None, Some(_)) => (true, n),
+            (None, None) => break,
+        };
+        if next.0 {
+            n += 1;
+        } else {
+            o += 1;
+        }
+        match picks.last_mut() {
+            Some(last) if cmp(value(*last), value(next)) == Equal => *last = next,
+            _ => picks.push(next),
+        }
+    }
+    picks
+}
+
+// carries out a plan made by merge_plan, dropping the values not picked
+fn merge_picked<T>(old: Vec<T>, new: Vec<T>, picks: Vec<(bool, usize)>) -> Vec<T> {
+    let mut old = old.into_iter().enumerate();
+    let mut new = new.into_iter().enumerate();
+    picks
+        .into_iter()
+        .map(|(is_new, i)| {
+            let run = if is_new { &mut new } else { &mut old };
+            run.find(|(j, _)| *j == i).unwrap().1
+        })
+        .collect()
+}
+
+// rebuilds a tree from values when dropped, so that the values
+// taken out of it are put back should a comparison panic
+struct Rebuild<'a, T> {
+    root: &'a mut Node<T>,
+    contained: &'a mut usize,
+    values: Vec<T>,
+}
+
+impl<'a, T> Drop for Rebuild<'a, T> {
+    fn drop(&mut self) {
+        let values = std::mem::take(&mut self.values);
+        *self.contained = values.len();
+        *self.root = Node::from_sorted(values);
+    }
+}
+
+/// A parallel iterator over the values of a tree.
+pub struct Iter<'a, T> {
+    root: &'a Node<T>,
+}
+
+impl<'a, T: Sync> ParallelIterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<C: UnindexedConsumer<&'a T>>(self, consumer: C) -> C::Result {
+        let producer = Subtree {
+            first: None,
+            node: self.root,
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+/// A parallel iterator that takes the values of a tree.
+pub struct IntoIter<T> {
+    root: Node<T>,
+}
+
+impl<T: Send> ParallelIterator for IntoIter<T> {
+    type Item = T;
+
+    fn drive_unindexed<C: UnindexedConsumer<T>>(self, consumer: C) -> C::Result {
+        let producer = IntoSubtree {
+            first: None,
+            node: self.root,
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+// only used by RBMap, whose values can be changed
+// without affecting the order of the tree
+#[cfg(feature = "map")]
+struct IterMutInner<'a, T> {
+    root: &'a mut Node<T>,
+}
+
+#[cfg(feature = "map")]
+impl<'a, T: Send> ParallelIterator for IterMutInner<'a, T> {
+    type Item = &'a mut T;
+
+    fn drive_unindexed<C: UnindexedConsumer<&'a mut T>>(self, consumer: C) -> C::Result {
+        let producer = SubtreeMut {
+            first: None,
+            node: self.root,
+        };
+        bridge_unindexed(producer, consumer)
+    }
+}
+
+impl<T: Sync, O> RBBase<T, O> {
+    /// Returns a parallel iterator over the values in the
+    /// tree. Collecting it into a Vec keeps their order.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    /// use rayon::prelude::*;
+    ///
+    /// let t: RBTree<u64> = (0..1000).collect();
+    /// assert_eq!(t.par_iter().sum::<u64>(), 499500);
+    /// assert_eq!(t.par_iter().collect::<Vec<_>>(), t.iter().collect::<Vec<_>>());
+    /// ```
+    pub fn par_iter(&self) -> Iter<'_, T> {
+        Iter { root: &self.root }
+    }
+}
+
+impl<'a, T: Sync, O> IntoParallelIterator for &'a RBBase<T, O> {
+    type Item = &'a T;
+    type Iter = Iter<'a, T>;
+
+    fn into_par_iter(self) -> Iter<'a, T> {
+        self.par_iter()
+    }
+}
+
+impl<T: Send, O> IntoParallelIterator for RBBase<T, O> {
+    type Item = T;
+    type Iter = IntoIter<T>;
+
+    fn into_par_iter(self) -> IntoIter<T> {
+        IntoIter { root: self.root }
+    }
+}
+
+impl<T: Send, O: TreeOrder<T> + Sync> ParallelExtend<T> for RBBase<T, O> {
+    /// Sorts the new values in parallel and merges them
+    /// with those in the tree, which is then rebuilt.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBQueue;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut q = RBQueue::new(|l: &i32, r: &i32| r.cmp(l));
+    /// q.insert(5);
+    /// q.par_extend((0..10).into_par_iter());
+    /// assert_eq!(q.len(), 10);
+    /// assert_eq!(q.pop(), Some(9));
+    /// ```
+    fn par_extend<I: IntoParallelIterator<Item = T>>(&mut self, par_iter: I) {
+        let mut new: Vec<T> = par_iter.into_par_iter().collect();
+        // a stable sort keeps equal values in the order given. the
+        // tree is left untouched until the sort has finished
+        let order = &self.order;
+        // the sort runs on rayon's workers, so it counts
+        // straight into the tree rather than through this thread
+        #[cfg(feature = "stats")]
+        let ops = &self.ops;
+        new.par_sort_by(|l: &T, r: &T| {
+            count!(comparisons => ops);
+            order.compare(l, r)
+        });
+        let old = self.take_sorted();
+        let order = &self.order;
+        counting!(self.ops);
+        let mut guard = Rebuild {
+            root: &mut self.root,
+            contained: &mut self.contained,
+            values: old,
+        };
+        let picks = merge_plan(&guard.values, &new, &|l: &T, r: &T| {
+            count!(comparisons);
+            order.compare(l, r)
+        });
+        let old = std::mem::take(&mut guard.values);
+        guard.values = merge_picked(old, new, picks);
+    }
+}
+
+impl<T: PartialOrd + Send> FromParallelIterator<T> for RBBase<T, PartialOrdering> {
+    fn from_par_iter<I: IntoParallelIterator<Item = T>>(par_iter: I) -> Self {
+        let mut tree = RBBase::with_order(PartialOrdering);
+        tree.par_extend(par_iter);
+        tree
+    }
+}
+
+/// A parallel iterator over the key-value pairs of an RBMap.
+#[cfg(feature = "map")]
+pub struct MapIter<'a, K: PartialOrd, V> {
+    iter: Iter<'a, Mapper<K, V>>,
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd + Sync, V: Sync> ParallelIterator for MapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn drive_unindexed<C: UnindexedConsumer<(&'a K, &'a V)>>(self, consumer: C) -> C::Result {
+        self.iter.map(Mapper::pair).drive_unindexed(consumer)
+    }
+}
+
+/// A parallel iterator over the keys of an RBMap
+/// along with mutable references to their values.
+#[cfg(feature = "map")]
+pub struct MapIterMut<'a, K: PartialOrd, V> {
+    iter: IterMutInner<'a, Mapper<K, V>>,
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd + Sync + Send, V: Send> ParallelIterator for MapIterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn drive_unindexed<C: UnindexedConsumer<(&'a K, &'a mut V)>>(self, consumer: C) -> C::Result {
+        self.iter.map(Mapper::mut_pair).drive_unindexed(consumer)
+    }
+}
+
+/// A parallel iterator over the values of an RBMap.
+#[cfg(feature = "map")]
+pub struct MapValues<'a, K: PartialOrd, V> {
+    iter: Iter<'a, Mapper<K, V>>,
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd + Sync, V: Sync> ParallelIterator for MapValues<'a, K, V> {
+    type Item = &'a V;
+
+    fn drive_unindexed<C: UnindexedConsumer<&'a V>>(self, consumer: C) -> C::Result {
+        self.iter.map(Mapper::as_ref).drive_unindexed(consumer)
+    }
+}
+
+/// A parallel iterator that takes the
+/// key-value pairs of an RBMap.
+#[cfg(feature = "map")]
+pub struct MapIntoIter<K: PartialOrd, V> {
+    iter: IntoIter<Mapper<K, V>>,
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd + Send, V: Send> ParallelIterator for MapIntoIter<K, V> {
+    type Item = (K, V);
+
+    fn drive_unindexed<C: UnindexedConsumer<(K, V)>>(self, consumer: C) -> C::Result {
+        self.iter.map(Mapper::consume).drive_unindexed(consumer)
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd + Sync, V: Sync> RBMap<K, V> {
+    /// Returns a parallel iterator over the
+    /// key-value pairs in the map.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let map: RBMap<u32, u64> = (0..1000).map(|k| (k, k as u64 * 2)).collect();
+    /// assert_eq!(map.par_iter().filter(|(k, _)| *k % 2 == 0).count(), 500);
+    /// assert_eq!(map.par_values().sum::<u64>(), 999000);
+    /// ```
+    pub fn par_iter(&self) -> MapIter<'_, K, V> {
+        MapIter {
+            iter: self.map.par_iter(),
+        }
+    }
+
+    /// Returns a parallel iterator over the values in the map.
+    pub fn par_values(&self) -> MapValues<'_, K, V> {
+        MapValues {
+            iter: self.map.par_iter(),
+        }
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd + Sync + Send, V: Send> RBMap<K, V> {
+    /// Returns a parallel iterator over the keys in
+    /// the map along with mutable references to their values.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let mut map: RBMap<u32, u32> = (0..100).map(|k| (k, k)).collect();
+    /// map.par_iter_mut().for_each(|(k, v)| *v += k);
+    /// assert_eq!(map.get(&7), Some(&14));
+    /// ```
+    pub fn par_iter_mut(&mut self) -> MapIterMut<'_, K, V> {
+        MapIterMut {
+            iter: IterMutInner {
+                root: &mut self.map.root,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd + Sync, V: Sync> IntoParallelIterator for &'a RBMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type Iter = MapIter<'a, K, V>;
+
+    fn into_par_iter(self) -> MapIter<'a, K, V> {
+        self.par_iter()
+    }
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd + Sync + Send, V: Send> IntoParallelIterator for &'a mut RBMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type Iter = MapIterMut<'a, K, V>;
+
+    fn into_par_iter(self) -> MapIterMut<'a, K, V> {
+        self.par_iter_mut()
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd + Send, V: Send> IntoParallelIterator for RBMap<K, V> {
+    type Item = (K, V);
+    type Iter = MapIntoIter<K, V>;
+
+    fn into_par_iter(self) -> MapIntoIter<K, V> {
+        MapIntoIter {
+            iter: self.map.into_par_iter(),
+        }
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd + Send, V: Send> ParallelExtend<(K, V)> for RBMap<K, V> {
+    /// Sorts the new entries in parallel and merges them
+    /// with those in the map, which is then rebuilt. Later
+    /// values replace earlier ones for the same key.
+    fn par_extend<I: IntoParallelIterator<Item = (K, V)>>(&mut self, par_iter: I) {
+        let entries = par_iter
+            .into_par_iter()
+            .map(|(k, v)| Mapper::new(k, Some(v)));
+        self.map.par_extend(entries);
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd + Send, V: Send> FromParallelIterator<(K, V)> for RBMap<K, V> {
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    /// use rayon::prelude::*;
+    ///
+    /// let map: RBMap<_, _> = (0..100).into_par_iter().map(|i| (i % 10, i)).collect();
+    /// assert_eq!(map.len(), 10);
+    /// assert_eq!(map.get(&3), Some(&93));
+    /// ```
+    fn from_par_iter<I: IntoParallelIterator<Item = (K, V)>>(par_iter: I) -> Self {
+        let mut map = RBMap::new();
+        map.par_extend(par_iter);
+        map
+    }
+}
@@ -0,0 +1,101 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::{RBMap, RBQueue, RBTree};
+
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_par_iter_matches_iter() {
+    let t: RBTree<u32> = (0..10_000).collect();
+    assert!(t
+        .par_iter()
+        .collect::<Vec<_>>()
+        .iter()
+        .copied()
+        .eq(t.iter()));
+    assert_eq!(
+        t.clone().into_par_iter().collect::<Vec<_>>(),
+        t.ordered().into_iter().copied().collect::<Vec<_>>()
+    );
+    assert_eq!(RBTree::<u32>::new().par_iter().count(), 0);
+
+    let mut m: RBMap<u32, u32> = (0..10_000).map(|k| (k, k)).collect();
+    m.par_iter_mut().for_each(|(k, v)| *v += k);
+    assert!(m.par_iter().all(|(k, v)| *v == k * 2));
+    assert_eq!(m.par_values().map(|v| *v as u64).sum::<u64>(), 99_990_000);
+    let pairs: Vec<_> = m.into_par_iter().collect();
+    assert!(pairs.iter().map(|p| p.0).eq(0..10_000));
+}
+
+#[test]
+fn test_par_collect_matches_sequential() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(40);
+    let entries: Vec<(u16, u32)> = (0..20_000).map(|i| (rng.gen_range(0..5000), i)).collect();
+    let mut expected: BTreeMap<u16, u32> = BTreeMap::new();
+    expected.extend(entries.iter().copied());
+
+    let m: RBMap<u16, u32> = entries.par_iter().copied().collect();
+    assert!(m.map.root.black_height().is_some());
+    assert!(m
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .eq(expected.iter().map(|(k, v)| (*k, *v))));
+
+    // extending merges with what is already there, newer values winning
+    let mut m: RBMap<u16, u32> = entries[..10_000].iter().copied().collect();
+    m.par_extend(entries[10_000..].par_iter().copied());
+    assert!(m.map.root.black_height().is_some());
+    assert!(m
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .eq(expected.iter().map(|(k, v)| (*k, *v))));
+
+    let t: RBTree<u16> = entries.par_iter().map(|e| e.0).collect();
+    assert_eq!(t.len(), expected.len());
+    assert!(t.iter().eq(expected.keys()));
+
+    let mut q = RBQueue::new(|l: &u16, r: &u16| r.cmp(l));
+    q.par_extend(entries.par_iter().map(|e| e.0));
+    assert_eq!(q.len(), expected.len());
+    assert!(q.iter().eq(expected.keys().rev()));
+}
+
+#[test]
+fn test_par_extend_panic_keeps_tree() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    // 500 only panics when compared against the values already in the
+    // queue, so the panic comes while merging, and any two values from
+    // 3000 up panic while sorting
+    let mut q = RBQueue::new(|l: &u32, r: &u32| {
+        if (*l == 500 && *r < 1000) || (*r == 500 && *l < 1000) {
+            panic!("merge");
+        }
+        if *l >= 3000 && *r >= 3000 {
+            panic!("sort");
+        }
+        l.cmp(r)
+    });
+    q.extend((0..1000).filter(|v| v % 2 == 0 && *v != 500));
+    let expected: Vec<u32> = q.iter().copied().collect();
+
+    let merging: Vec<u32> = (1000..2000).chain(std::iter::once(500)).collect();
+    assert!(catch_unwind(AssertUnwindSafe(
+        || q.par_extend(merging.par_iter().copied())
+    ))
+    .is_err());
+    assert!(q.root.black_height().is_some());
+    assert_eq!(q.len(), expected.len());
+    assert!(q.iter().eq(expected.iter()));
+
+    assert!(catch_unwind(AssertUnwindSafe(|| q.par_extend(vec![3000, 3001]))).is_err());
+    assert!(q.root.black_height().is_some());
+    assert!(q.iter().eq(expected.iter()));
+
+    // and the queue still takes values once nothing panics
+    q.par_extend(vec![1, 3001]);
+    assert_eq!(q.len(), expected.len() + 2);
+}
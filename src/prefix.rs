@@ -0,0 +1,240 @@
+//! Prefix queries for trees and maps keyed by strings
+//! or byte strings. Each query seeks straight to the
+//! first matching key rather than scanning the tree.
+
+use crate::helpers::{insert_from, insert_left_down};
+#[cfg(feature = "map")]
+use crate::mapper::Mapper;
+use crate::node::{counting, Node};
+#[cfg(feature = "map")]
+use crate::RBMap;
+use crate::RBTree;
+use std::iter::FusedIterator;
+
+/// Keys made up of bytes, whose PartialOrd ordering is
+/// the lexicographic ordering of those bytes, so that
+/// every key starting with a given prefix is stored
+/// in one contiguous run.
+pub trait PrefixKey {
+    /// Returns the bytes of the key.
+    fn prefix_bytes(&self) -> &[u8];
+}
+
+impl PrefixKey for str {
+    fn prefix_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PrefixKey for String {
+    fn prefix_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PrefixKey for [u8] {
+    fn prefix_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl PrefixKey for Vec<u8> {
+    fn prefix_bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<T: PrefixKey + ?Sized> PrefixKey for &T {
+    fn prefix_bytes(&self) -> &[u8] {
+        (**self).prefix_bytes()
+    }
+}
+
+// visits the values from the first with the given prefix
+// onwards, stopping at the first without it
+struct Walk<'a, T> {
+    prefix: Vec<u8>,
+    ordered: Vec<&'a Node<T>>,
+    key: fn(&T) -> &[u8],
+}
+
+impl<'a, T> Walk<'a, T> {
+    fn new(root: &'a Node<T>, prefix: &[u8], key: fn(&T) -> &[u8]) -> Walk<'a, T> {
+        let mut ordered = Vec::new();
+        insert_from(
+            root,
+            &prefix,
+            &|p: &&[u8], v: &T| (*p).cmp(key(v)),
+            &mut ordered,
+        );
+        Walk {
+            prefix: prefix.to_vec(),
+            ordered,
+            key,
+        }
+    }
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.ordered.pop()?;
+        let value = node.value().unwrap();
+        if !(self.key)(value).starts_with(&self.prefix) {
+            self.ordered.clear();
+            return None;
+        }
+        insert_left_down(node.get_right(), &mut self.ordered);
+        Some(value)
+    }
+}
+
+// the value with the longest key that is a prefix of bytes.
+// if the greatest key up to bytes is not a prefix of it,
+// no longer key than the part they share can be either
+fn longest_match<'a, T>(root: &'a Node<T>, bytes: &[u8], key: fn(&T) -> &[u8]) -> Option<&'a T> {
+    let mut bytes = bytes;
+    loop {
+        let found = root.floor(&bytes, &|b: &&[u8], v: &T| (*b).cmp(key(v)))?;
+        let found_key = key(found);
+        if bytes.starts_with(found_key) {
+            return Some(found);
+        }
+        let shared = bytes
+            .iter()
+            .zip(found_key)
+            .take_while(|(l, r)| l == r)
+            .count();
+        bytes = &bytes[..shared];
+    }
+}
+
+impl<T: PrefixKey + PartialOrd> RBTree<T> {
+    /// Returns an iterator over the values that start with
+    /// prefix, in order. Finding the first takes O(log n)
+    /// time, and the iterator stops at the first value that
+    /// does not match.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let words: RBTree<&str> = ["car", "card", "care", "cat", "dog"].iter().copied().collect();
+    /// assert_eq!(words.prefix_iter("car").collect::<Vec<_>>(), [&"car", &"card", &"care"]);
+    /// assert_eq!(words.prefix_count("ca"), 4);
+    /// assert_eq!(words.prefix_count("cow"), 0);
+    /// ```
+    pub fn prefix_iter<P: AsRef<[u8]> + ?Sized>(&self, prefix: &P) -> PrefixIter<'_, T> {
+        counting!(self.ops);
+        PrefixIter {
+            walk: Walk::new(&self.root, prefix.as_ref(), |v: &T| v.prefix_bytes()),
+        }
+    }
+
+    /// Returns the number of values that start with prefix.
+    pub fn prefix_count<P: AsRef<[u8]> + ?Sized>(&self, prefix: &P) -> usize {
+        self.prefix_iter(prefix).count()
+    }
+
+    /// Returns the longest value in the tree that is a
+    /// prefix of key (key itself included), if any.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let routes: RBTree<String> = ["/", "/api", "/api/v1"].iter().map(|r| r.to_string()).collect();
+    /// assert_eq!(routes.longest_prefix_match("/api/v1/users").unwrap(), "/api/v1");
+    /// assert_eq!(routes.longest_prefix_match("/api/v2").unwrap(), "/api");
+    /// assert_eq!(routes.longest_prefix_match("/static").unwrap(), "/");
+    /// assert_eq!(routes.longest_prefix_match("api"), None);
+    /// ```
+    pub fn longest_prefix_match<P: AsRef<[u8]> + ?Sized>(&self, key: &P) -> Option<&T> {
+        counting!(self.ops);
+        longest_match(&self.root, key.as_ref(), |v: &T| v.prefix_bytes())
+    }
+}
+
+#[cfg(feature = "map")]
+fn map_key<K: PrefixKey + PartialOrd, V>(m: &Mapper<K, V>) -> &[u8] {
+    m.key().prefix_bytes()
+}
+
+#[cfg(feature = "map")]
+impl<K: PrefixKey + PartialOrd, V> RBMap<K, V> {
+    /// Returns an iterator over the entries whose keys start
+    /// with prefix, in order. Finding the first takes O(log n)
+    /// time, and the iterator stops at the first key that
+    /// does not match.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut m = RBMap::new();
+    /// m.insert(b"\x01\x02".to_vec(), 'a');
+    /// m.insert(b"\x01\x02\x03".to_vec(), 'b');
+    /// m.insert(b"\x01\x03".to_vec(), 'c');
+    /// let values: Vec<_> = m.prefix_iter(b"\x01\x02").map(|(_, v)| *v).collect();
+    /// assert_eq!(values, ['a', 'b']);
+    /// assert_eq!(m.prefix_count(&[1u8][..]), 3);
+    /// ```
+    pub fn prefix_iter<P: AsRef<[u8]> + ?Sized>(&self, prefix: &P) -> PrefixMapIter<'_, K, V> {
+        counting!(self.map.ops);
+        PrefixMapIter {
+            walk: Walk::new(&self.map.root, prefix.as_ref(), map_key),
+        }
+    }
+
+    /// Returns the number of keys that start with prefix.
+    pub fn prefix_count<P: AsRef<[u8]> + ?Sized>(&self, prefix: &P) -> usize {
+        self.prefix_iter(prefix).count()
+    }
+
+    /// Returns the entry with the longest key that is a
+    /// prefix of key (key itself included), if any.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut m = RBMap::new();
+    /// m.insert("10.0.", "private");
+    /// m.insert("10.0.0.", "gateway");
+    /// assert_eq!(m.longest_prefix_match("10.0.0.1"), Some((&"10.0.0.", &"gateway")));
+    /// assert_eq!(m.longest_prefix_match("10.0.1.1"), Some((&"10.0.", &"private")));
+    /// assert_eq!(m.longest_prefix_match("192.168.0.1"), None);
+    /// ```
+    pub fn longest_prefix_match<P: AsRef<[u8]> + ?Sized>(&self, key: &P) -> Option<(&K, &V)> {
+        counting!(self.map.ops);
+        longest_match(&self.map.root, key.as_ref(), map_key).map(|m| m.pair())
+    }
+}
+
+/// An iterator over the values of an RBTree
+/// that start with a given prefix.
+pub struct PrefixIter<'a, T> {
+    walk: Walk<'a, T>,
+}
+
+impl<'a, T> Iterator for PrefixIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.walk.next()
+    }
+}
+
+impl<'a, T> FusedIterator for PrefixIter<'a, T> {}
+
+/// An iterator over the entries of an RBMap
+/// whose keys start with a given prefix.
+#[cfg(feature = "map")]
+pub struct PrefixMapIter<'a, K: PartialOrd, V> {
+    walk: Walk<'a, Mapper<K, V>>,
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V> Iterator for PrefixMapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.walk.next().map(|m| m.pair())
+    }
+}
+
+#[cfg(feature = "map")]
+impl<'a, K: PartialOrd, V> FusedIterator for PrefixMapIter<'a, K, V> {}
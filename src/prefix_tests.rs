@@ -0,0 +1,66 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::{RBMap, RBTree};
+
+use rand::{Rng, SeedableRng};
+
+// short keys over a small alphabet, so prefixes are shared often
+fn random_key(rng: &mut rand_chacha::ChaCha8Rng) -> Vec<u8> {
+    let len = rng.gen_range(0..5);
+    (0..len).map(|_| rng.gen_range(b'a'..b'd')).collect()
+}
+
+#[test]
+fn test_matches_scan() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(43);
+    let mut m = RBMap::new();
+    for i in 0..300 {
+        m.insert(random_key(&mut rng), i);
+    }
+    for _ in 0..500 {
+        let query = random_key(&mut rng);
+        let expected: Vec<_> = m.iter().filter(|(k, _)| k.starts_with(&query)).collect();
+        assert_eq!(m.prefix_iter(&query).collect::<Vec<_>>(), expected);
+        assert_eq!(m.prefix_count(&query), expected.len());
+        let longest = m
+            .iter()
+            .filter(|(k, _)| query.starts_with(k))
+            .max_by_key(|(k, _)| k.len());
+        assert_eq!(m.longest_prefix_match(&query), longest);
+    }
+}
+
+#[test]
+fn test_string_keys() {
+    let t: RBTree<String> = ["", "a", "ab", "abc", "abd", "b", "ba"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(t.prefix_count(""), 7);
+    assert_eq!(
+        t.prefix_iter("ab").collect::<Vec<_>>(),
+        ["ab", "abc", "abd"]
+    );
+    assert_eq!(t.prefix_iter("abcd").next(), None);
+    assert_eq!(t.prefix_iter("c").next(), None);
+    assert_eq!(t.longest_prefix_match("abe").unwrap(), "ab");
+    assert_eq!(t.longest_prefix_match("c").unwrap(), "");
+    // multi-byte characters compare by their bytes
+    let t: RBTree<&str> = ["é", "éa", "e"].iter().copied().collect();
+    assert_eq!(t.prefix_iter("é").collect::<Vec<_>>(), [&"é", &"éa"]);
+}
+
+#[test]
+fn test_byte_slice_keys() {
+    let data = [1u8, 2, 3, 4];
+    let t: RBTree<&[u8]> = vec![&data[..1], &data[..2], &data[1..]]
+        .into_iter()
+        .collect();
+    assert_eq!(t.prefix_count(&[1u8][..]), 2);
+    assert_eq!(t.longest_prefix_match(&data[..]), Some(&&data[..2]));
+    assert_eq!(
+        t.longest_prefix_match(&[2u8, 3, 4, 5][..]),
+        Some(&&data[1..])
+    );
+}
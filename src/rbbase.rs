@@ -0,0 +1,1037 @@
+use crate::helpers::{insert_left_down, ordered_insertion, retain, tree_view};
+use crate::node::Colour::Black;
+use crate::node::Node::Leaf;
+use crate::node::{count, counting, Node};
+use crate::order::TreeOrder;
+use crate::RBBase;
+
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+use std::fmt::{Debug, Display, Formatter, Result};
+use std::hash::{Hash, Hasher};
+use std::iter::{ExactSizeIterator, FromIterator, FusedIterator};
+use std::mem::take;
+
+impl<T: Debug, O> Debug for RBBase<T, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Debug, O> Display for RBBase<T, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "{:?}", self.ordered())
+    }
+}
+
+impl<T: Debug, O> RBBase<T, O> {
+    /// Renders the structure of the tree as an indented
+    /// ASCII diagram, one node per line with its colour,
+    /// and the left child of each node listed before the
+    /// right. Missing children are shown as `___` when
+    /// their sibling is present.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let t: RBTree<_> = [2, 1, 3, 4].iter().copied().collect();
+    /// let view = ["B:2", "|-- B:1", "`-- B:3", "    |-- ___", "    `-- R:4"];
+    /// assert_eq!(t.tree_view(), view.join("\n"));
+    /// assert_eq!(format!("{:?}", t), "{1, 2, 3, 4}");
+    /// ```
+    pub fn tree_view(&self) -> String {
+        tree_view(&self.root, &|v: &T| format!("{:?}", v))
+    }
+}
+
+impl<T, O> RBBase<T, O> {
+    /// Creates and returns a new, empty tree that
+    /// will order its entries using `order`.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBBase, RBTree};
+    /// use rb_tree::order::PartialOrdering;
+    ///
+    /// let mut t: RBTree<i32> = RBBase::with_order(PartialOrdering);
+    /// t.insert(3);
+    /// t.insert(2);
+    /// assert_eq!(t.take(&2).unwrap(), 2);
+    /// ```
+    pub fn with_order(order: O) -> RBBase<T, O> {
+        RBBase {
+            root: Leaf(Black),
+            contained: 0,
+            order,
+            #[cfg(feature = "stats")]
+            ops: Default::default(),
+        }
+    }
+
+    /// Clears all entries from the tree.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut tree = RBTree::new();
+    /// tree.insert(2);
+    /// tree.insert(5);
+    /// tree.clear();
+    /// assert_eq!(tree.len(), 0);
+    /// assert!(!tree.contains(&2));
+    /// ```
+    pub fn clear(&mut self) {
+        self.root = Leaf(Black);
+        self.contained = 0;
+    }
+
+    /// Clears the tree and returns all values
+    /// as an iterator in their order.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut tree = RBTree::new();
+    /// tree.insert(2);
+    /// tree.insert(5);
+    /// assert_eq!(tree.len(), 2);
+    /// let mut drain = tree.drain();
+    /// assert_eq!(drain.next().unwrap(), 2);
+    /// assert_eq!(drain.next().unwrap(), 5);
+    /// assert!(drain.next().is_none());
+    /// assert_eq!(tree.len(), 0);
+    /// ```
+    pub fn drain(&mut self) -> Drain<T> {
+        let remaining = self.contained;
+        self.contained = 0;
+        Drain {
+            root: take(&mut self.root),
+            remaining,
+        }
+    }
+
+    /// Returns a vector presenting the contained
+    /// elements of the tree in the order by which
+    /// they are prioritised (that is, in the in-order
+    /// tree traversal order).
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(3);
+    /// t.insert(1);
+    /// t.insert(2);
+    /// let order = t.ordered();
+    /// assert_eq!(*order[1], 2);
+    /// ```
+    pub fn ordered(&self) -> Vec<&T> {
+        let mut order = Vec::new();
+        ordered_insertion(&self.root, &mut order);
+        order
+    }
+
+    /// Returns the number of elements contained
+    /// in the tree.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(3);
+    /// t.insert(1);
+    /// t.insert(2);
+    /// assert_eq!(t.len(), 3);
+    /// t.remove(&2);
+    /// assert_eq!(t.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.contained
+    }
+
+    /// Returns true if there are no items
+    /// present in the tree, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// assert!(t.is_empty());
+    /// t.insert(3);
+    /// assert!(!t.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the tree contains the
+    /// specified item, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(2);
+    /// assert!(!t.contains(&3));
+    /// assert!(t.contains(&2));
+    /// ```
+    pub fn contains<K>(&self, val: &K) -> bool
+    where
+        O: TreeOrder<K, T>,
+    {
+        self.get(val).is_some()
+    }
+
+    /// Returns the item specified if contained,
+    /// None otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(1);
+    /// assert_eq!(*t.get(&1).unwrap(), 1);
+    /// assert_eq!(t.get(&2), None);
+    /// ```
+    pub fn get<K>(&self, val: &K) -> Option<&T>
+    where
+        O: TreeOrder<K, T>,
+    {
+        let order = &self.order;
+        counting!(self.ops);
+        self.root.get(val, &|l: &K, r: &T| order.compare(l, r))
+    }
+
+    pub(crate) fn get_mut<K>(&mut self, val: &K) -> Option<&mut T>
+    where
+        O: TreeOrder<K, T>,
+    {
+        let order = &self.order;
+        counting!(self.ops);
+        self.root.get_mut(val, &|l: &K, r: &T| order.compare(l, r))
+    }
+
+    /// Removes an item the tree. Returns the matching item
+    /// if it was contained in the tree, None otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(4);
+    /// t.insert(2);
+    /// assert_eq!(t.take(&2).unwrap(), 2);
+    /// assert_eq!(t.len(), 1);
+    /// assert_eq!(t.take(&2), None);
+    /// ```
+    pub fn take<K>(&mut self, val: &K) -> Option<T>
+    where
+        O: TreeOrder<K, T>,
+    {
+        let order = &self.order;
+        counting!(self.ops);
+        match self.root.remove(val, &|l: &K, r: &T| order.compare(l, r)) {
+            Some(v) => {
+                self.contained -= 1;
+                Some(v)
+            }
+            None => None,
+        }
+    }
+
+    /// Removes an item the tree. Returns true
+    /// if it was contained in the tree, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(4);
+    /// t.insert(2);
+    /// assert_eq!(t.remove(&2), true);
+    /// assert_eq!(t.len(), 1);
+    /// assert_eq!(t.remove(&2), false);
+    /// ```
+    pub fn remove<K>(&mut self, val: &K) -> bool
+    where
+        O: TreeOrder<K, T>,
+    {
+        self.take(val).is_some()
+    }
+
+    /// Removes the item at the front of the priority
+    /// queue that the tree represents if any elements
+    /// are present, or None otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(2);
+    /// t.insert(1);
+    /// t.insert(3);
+    /// assert_eq!(t.pop().unwrap(), 1);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        counting!(self.ops);
+        match self.root.pop(false) {
+            Some(v) => {
+                self.contained -= 1;
+                Some(v)
+            }
+            None => None,
+        }
+    }
+
+    /// Peeks the item at the front of the priority
+    /// queue that the tree represents if any elements
+    /// are present, or None otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(2);
+    /// t.insert(1);
+    /// t.insert(3);
+    /// assert_eq!(*t.peek().unwrap(), 1);
+    /// ```
+    pub fn peek(&self) -> Option<&T> {
+        self.root.peek(false)
+    }
+
+    /// Removes the item at the back of the priority
+    /// queue that the tree represents if any elements
+    /// are present, or None otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(2);
+    /// t.insert(1);
+    /// t.insert(3);
+    /// assert_eq!(t.pop_back().unwrap(), 3);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        counting!(self.ops);
+        match self.root.pop(true) {
+            Some(v) => {
+                self.contained -= 1;
+                Some(v)
+            }
+            None => None,
+        }
+    }
+
+    /// Peeks the item at the back of the priority
+    /// queue that the tree represents if any elements
+    /// are present, or None otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(2);
+    /// t.insert(1);
+    /// t.insert(3);
+    /// assert_eq!(*t.peek_back().unwrap(), 3);
+    /// ```
+    pub fn peek_back(&self) -> Option<&T> {
+        self.root.peek(true)
+    }
+
+    /// Removes and returns the item at the front of
+    /// the priority queue if pred returns true for it,
+    /// leaving the tree untouched otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t: RBTree<_> = [5, 1, 3].iter().copied().collect();
+    /// assert_eq!(t.pop_first_if(|v| *v < 2), Some(1));
+    /// assert_eq!(t.pop_first_if(|v| *v < 2), None);
+    /// assert_eq!(t.len(), 2);
+    /// ```
+    pub fn pop_first_if<F: FnOnce(&T) -> bool>(&mut self, pred: F) -> Option<T> {
+        if pred(self.peek()?) {
+            self.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Removes and returns the item at the back of
+    /// the priority queue if pred returns true for it,
+    /// leaving the tree untouched otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBQueue;
+    ///
+    /// let mut q = RBQueue::new(|l: &(u8, &str), r| l.0.cmp(&r.0));
+    /// q.insert((1, "low"));
+    /// q.insert((9, "urgent"));
+    /// let mut handled = Vec::new();
+    /// while let Some((_, job)) = q.pop_last_if(|j| j.0 > 5) {
+    ///     handled.push(job);
+    /// }
+    /// assert_eq!(handled, ["urgent"]);
+    /// assert_eq!(q.len(), 1);
+    /// ```
+    pub fn pop_last_if<F: FnOnce(&T) -> bool>(&mut self, pred: F) -> Option<T> {
+        if pred(self.peek_back()?) {
+            self.pop_back()
+        } else {
+            None
+        }
+    }
+
+    // the value at the front or back, which must not be
+    // changed in a way that alters its place in the order
+    #[cfg(feature = "map")]
+    pub(crate) fn peek_mut(&mut self, back: bool) -> Option<&mut T> {
+        self.root.peek_mut(back)
+    }
+
+    /// Returns an iterator over the elements
+    /// contained in this tree.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(3);
+    /// t.insert(1);
+    /// t.insert(5);
+    /// assert_eq!(t.iter().collect::<Vec<&usize>>(), vec!(&1, &3, &5));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.root, self.len())
+    }
+
+    /// Retains in this tree only those values for which
+    /// the passed closure returns true.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t: RBTree<usize> = (0..10).collect();
+    /// t.retain(|v| v % 2 == 0);
+    /// assert_eq!(t.iter().collect::<Vec<&usize>>(), vec!(&0, &2, &4, &6, &8));
+    /// ```
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        retain(&mut self.root, &mut self.contained, |v| f(v));
+    }
+}
+
+impl<T, O> RBBase<T, O> {
+    // builds a tree out of values already sorted by order
+    pub(crate) fn from_sorted(values: Vec<T>, order: O) -> RBBase<T, O> {
+        RBBase {
+            contained: values.len(),
+            root: Node::from_sorted(values),
+            order,
+            #[cfg(feature = "stats")]
+            ops: Default::default(),
+        }
+    }
+
+    // empties the tree, returning its values in order
+    pub(crate) fn take_sorted(&mut self) -> Vec<T> {
+        let mut values = Vec::with_capacity(self.contained);
+        take(&mut self.root).into_sorted(&mut values);
+        self.contained = 0;
+        values
+    }
+}
+
+impl<T, O: TreeOrder<T>> RBBase<T, O> {
+    /// Inserts a new element into the tree.
+    /// Returns true if this item was not already
+    /// in the tree, and false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// assert_eq!(t.insert("Hello".to_string()), true);
+    /// assert_eq!(t.insert("Hello".to_string()), false);
+    /// ```
+    pub fn insert(&mut self, val: T) -> bool {
+        self.replace(val).is_none()
+    }
+
+    /// Inserts a new element into the tree.
+    /// Returns None if this item was not already
+    /// in the tree, and the previously contained
+    /// item otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t = RBTree::new();
+    /// assert_eq!(t.replace("Hello".to_string()), None);
+    /// assert_eq!(t.replace("Hello".to_string()), Some("Hello".to_string()));
+    /// ```
+    pub fn replace(&mut self, val: T) -> Option<T> {
+        let order = &self.order;
+        counting!(self.ops);
+        match self.root.insert(val, &|l: &T, r: &T| order.compare(l, r)) {
+            Some(v) => Some(v),
+            None => {
+                self.contained += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns an iterator representing the
+    /// difference between the items in this tree
+    /// and those in another tree, i.e. the values
+    /// in `self` but not in `other`.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t1 = RBTree::new();
+    /// let mut t2 = RBTree::new();
+    /// (0..3).for_each(|v| {t1.insert(v);});
+    /// (2..5).for_each(|v| {t2.insert(v);});
+    /// assert_eq!(
+    ///     t1.difference(&t2).collect::<Vec<&usize>>(),
+    ///     vec!(&0, &1)
+    /// );
+    /// assert_eq!(
+    ///     t2.difference(&t1).collect::<Vec<&usize>>(),
+    ///     vec!(&3, &4)
+    /// );
+    /// ```
+    pub fn difference<'a>(&'a self, other: &'a RBBase<T, O>) -> Difference<'a, T, O> {
+        let mut iterl = self.iter();
+        let mut iterr = other.iter();
+        Difference {
+            nextl: iterl.next(),
+            nextr: iterr.next(),
+            left: iterl,
+            right: iterr,
+            tree: self,
+        }
+    }
+
+    /// Returns an iterator representing the
+    /// symmetric difference between the items
+    /// in this tree and those in another, i.e.
+    /// the values in `self` or `other` but not in both.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t1 = RBTree::new();
+    /// let mut t2 = RBTree::new();
+    /// (0..3).for_each(|v| {t1.insert(v);});
+    /// (2..5).for_each(|v| {t2.insert(v);});
+    /// assert_eq!(
+    ///     t1.symmetric_difference(&t2).collect::<Vec<&usize>>(),
+    ///     vec!(&0, &1, &3, &4)
+    /// );
+    /// assert_eq!(
+    ///     t2.symmetric_difference(&t1).collect::<Vec<&usize>>(),
+    ///     vec!(&0, &1, &3, &4)
+    /// );
+    /// ```
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a RBBase<T, O>,
+    ) -> SymmetricDifference<'a, T, O> {
+        let mut iterl = self.iter();
+        let mut iterr = other.iter();
+        SymmetricDifference {
+            nextl: iterl.next(),
+            nextr: iterr.next(),
+            left: iterl,
+            right: iterr,
+            tree: self,
+        }
+    }
+
+    /// Returns an iterator representing the intersection
+    /// of this tree and another, i.e. the values that
+    /// appear in both `self` and `other`.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t1 = RBTree::new();
+    /// let mut t2 = RBTree::new();
+    /// (0..3).for_each(|v| {t1.insert(v);});
+    /// (2..5).for_each(|v| {t2.insert(v);});
+    /// assert_eq!(
+    ///     t1.intersection(&t2).collect::<Vec<&usize>>(),
+    ///     vec!(&2)
+    /// );
+    /// ```
+    pub fn intersection<'a>(&'a self, other: &'a RBBase<T, O>) -> Intersection<'a, T, O> {
+        let mut iterl = self.iter();
+        let mut iterr = other.iter();
+        Intersection {
+            nextl: iterl.next(),
+            nextr: iterr.next(),
+            left: iterl,
+            right: iterr,
+            tree: self,
+        }
+    }
+
+    /// Returns an iterator representing the union
+    /// of this tree and another, i.e. the values
+    /// that appear in at least one of the trees.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t1 = RBTree::new();
+    /// let mut t2 = RBTree::new();
+    /// (0..3).for_each(|v| {t1.insert(v);});
+    /// (2..5).for_each(|v| {t2.insert(v);});
+    /// assert_eq!(
+    ///     t1.union(&t2).collect::<Vec<&usize>>(),
+    ///     vec!(&0, &1, &2, &3, &4)
+    /// );
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a RBBase<T, O>) -> Union<'a, T, O> {
+        let mut iterl = self.iter();
+        let mut iterr = other.iter();
+        Union {
+            nextl: iterl.next(),
+            nextr: iterr.next(),
+            left: iterl,
+            right: iterr,
+            tree: self,
+        }
+    }
+
+    /// Returns true if this tree and another are disjoint,
+    /// i.e. there are no values in `self` that appear in `other`
+    /// and vice versa, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t1 = RBTree::new();
+    /// let mut t2 = RBTree::new();
+    /// (0..3).for_each(|v| {t1.insert(v);});
+    /// (2..5).for_each(|v| {t2.insert(v);});
+    /// assert!(!t1.is_disjoint(&t2));
+    /// t2.pop(); // remove '2' from t2
+    /// assert!(t1.is_disjoint(&t2));
+    /// ```
+    pub fn is_disjoint(&self, other: &RBBase<T, O>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    /// Returns true if this tree is a subset of another,
+    /// i.e. at least all values in `self` also appear in
+    /// `other`, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t1 = RBTree::new();
+    /// let mut t2 = RBTree::new();
+    /// let mut t3 = RBTree::new();
+    /// (0..3).for_each(|v| {t1.insert(v);});
+    /// (2..10).for_each(|v| {t2.insert(v);});
+    /// (3..7).for_each(|v| {t3.insert(v);});
+    /// assert!(!t1.is_subset(&t2));
+    /// assert!(t3.is_subset(&t2));
+    /// ```
+    pub fn is_subset(&self, other: &RBBase<T, O>) -> bool {
+        self.intersection(other).count() == self.len()
+    }
+
+    /// Returns true if this tree is a superset of another,
+    /// i.e. at least all values in `other` also appear in
+    /// `self`, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let mut t1 = RBTree::new();
+    /// let mut t2 = RBTree::new();
+    /// let mut t3 = RBTree::new();
+    /// (0..3).for_each(|v| {t1.insert(v);});
+    /// (2..10).for_each(|v| {t2.insert(v);});
+    /// (3..7).for_each(|v| {t3.insert(v);});
+    /// assert!(!t2.is_superset(&t1));
+    /// assert!(t2.is_superset(&t3));
+    /// ```
+    pub fn is_superset(&self, other: &RBBase<T, O>) -> bool {
+        other.intersection(self).count() == other.len()
+    }
+}
+
+impl<T, O: Default> Default for RBBase<T, O> {
+    fn default() -> Self {
+        RBBase::with_order(O::default())
+    }
+}
+
+/// Trees are equal when they contain equal values in the
+/// same order, regardless of the shape of either tree.
+/// # Example:
+/// ```
+/// use rb_tree::RBTree;
+///
+/// let t1: RBTree<i32> = (0..10).collect();
+/// let t2: RBTree<i32> = (0..10).rev().collect();
+/// assert_eq!(t1, t2);
+/// assert_ne!(t1, (0..9).collect());
+/// ```
+impl<T: PartialEq, O> PartialEq for RBBase<T, O> {
+    fn eq(&self, other: &RBBase<T, O>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, O> Eq for RBBase<T, O> {}
+
+/// Trees are compared lexicographically by their values
+/// in order, as with std's BTreeSet.
+/// # Example:
+/// ```
+/// use rb_tree::RBTree;
+///
+/// let t1: RBTree<i32> = vec![1, 2, 3].into_iter().collect();
+/// let t2: RBTree<i32> = vec![1, 3].into_iter().collect();
+/// assert!(t1 < t2);
+/// ```
+impl<T: PartialOrd, O> PartialOrd for RBBase<T, O> {
+    fn partial_cmp(&self, other: &RBBase<T, O>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<T: Ord, O> Ord for RBBase<T, O> {
+    fn cmp(&self, other: &RBBase<T, O>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<T: Hash, O> Hash for RBBase<T, O> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for v in self.iter() {
+            v.hash(state);
+        }
+    }
+}
+
+pub struct IntoIter<T> {
+    root: Node<T>,
+    remaining: usize,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let next = self.root.pop(false)?;
+        self.remaining -= 1;
+        Some(next)
+    }
+}
+
+/// Provides the trait ExactSizeIterator for IntoIter<T>
+/// # Example:
+/// ```
+/// use rb_tree::RBTree;
+///
+/// let mut t = RBTree::new();
+/// t.insert(3);
+/// t.insert(1);
+/// t.insert(5);
+///
+/// let mut iterator = t.into_iter();
+/// assert_eq!(iterator.len(), 3);
+/// let _ = iterator.next();
+/// assert_eq!(iterator.len(), 2);
+/// ```
+impl<T> ExactSizeIterator for IntoIter<T> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T, O> IntoIterator for RBBase<T, O> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            root: self.root,
+            remaining: self.contained,
+        }
+    }
+}
+
+impl<T, O: TreeOrder<T> + Default> FromIterator<T> for RBBase<T, O> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = RBBase::default();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T, O: TreeOrder<T>> Extend<T> for RBBase<T, O> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for i in iter {
+            self.insert(i);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, O: TreeOrder<T>> Extend<&'a T> for RBBase<T, O> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        for &i in iter {
+            self.insert(i);
+        }
+    }
+}
+
+pub struct Drain<T> {
+    root: Node<T>,
+    remaining: usize,
+}
+
+impl<T> Iterator for Drain<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let next = self.root.pop(false)?;
+        self.remaining -= 1;
+        Some(next)
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<T> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<T> FusedIterator for Drain<T> {}
+
+pub struct Iter<'a, T> {
+    remaining: usize,
+    ordered: Vec<&'a Node<T>>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    // iterates over the remaining values below root in order
+    pub(crate) fn new(root: &'a Node<T>, remaining: usize) -> Iter<'a, T> {
+        let mut ordered = Vec::new();
+        insert_left_down(root, &mut ordered);
+        Iter { remaining, ordered }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let next = self.ordered.pop()?;
+        self.remaining -= 1;
+        insert_left_down(next.get_right(), &mut self.ordered);
+        Some(next.value().unwrap())
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+pub struct Difference<'a, T, O> {
+    nextl: Option<&'a T>,
+    nextr: Option<&'a T>,
+    left: Iter<'a, T>,
+    right: Iter<'a, T>,
+    tree: &'a RBBase<T, O>,
+}
+
+impl<'a, T, O: TreeOrder<T>> Iterator for Difference<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        // select and store the next next
+        let mut res = None;
+        'left: while let Some(vl) = self.nextl {
+            self.nextl = self.left.next();
+            while let Some(vr) = self.nextr {
+                count!(comparisons => self.tree.ops);
+                match self.tree.order.compare(vl, vr) {
+                    Less => {
+                        res = Some(vl);
+                        break 'left;
+                    }
+                    Equal => {
+                        self.nextr = self.right.next();
+                        continue 'left;
+                    }
+                    Greater => self.nextr = self.right.next(),
+                }
+            }
+            res = Some(vl);
+            break; // don't want to skip values
+        }
+
+        // return the current next value
+        res
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> FusedIterator for Difference<'a, T, O> {}
+
+pub struct SymmetricDifference<'a, T, O> {
+    nextl: Option<&'a T>,
+    nextr: Option<&'a T>,
+    left: Iter<'a, T>,
+    right: Iter<'a, T>,
+    tree: &'a RBBase<T, O>,
+}
+
+impl<'a, T, O: TreeOrder<T>> Iterator for SymmetricDifference<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        // select and store the next next
+        let mut res = None;
+        'left: while let Some(vl) = self.nextl {
+            if let Some(vr) = self.nextr {
+                count!(comparisons => self.tree.ops);
+                match self.tree.order.compare(vl, vr) {
+                    Less => {
+                        self.nextl = self.left.next();
+                        res = Some(vl);
+                        break 'left;
+                    }
+                    Equal => {
+                        self.nextl = self.left.next();
+                        self.nextr = self.right.next();
+                        continue 'left;
+                    }
+                    Greater => {
+                        self.nextr = self.right.next();
+                        res = Some(vr);
+                        break 'left;
+                    }
+                }
+            }
+
+            // don't want to skip values
+            self.nextl = self.left.next();
+            res = Some(vl);
+            break;
+        }
+        if res.is_none() {
+            res = self.nextr;
+            self.nextr = self.right.next();
+        }
+
+        // return the current next value
+        res
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> FusedIterator for SymmetricDifference<'a, T, O> {}
+
+pub struct Intersection<'a, T, O> {
+    nextl: Option<&'a T>,
+    nextr: Option<&'a T>,
+    left: Iter<'a, T>,
+    right: Iter<'a, T>,
+    tree: &'a RBBase<T, O>,
+}
+
+impl<'a, T, O: TreeOrder<T>> Iterator for Intersection<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        // select and store the next next
+        let mut res = None;
+        'left: while let Some(vl) = self.nextl {
+            while let Some(vr) = self.nextr {
+                count!(comparisons => self.tree.ops);
+                match self.tree.order.compare(vl, vr) {
+                    Less => {
+                        self.nextl = self.left.next();
+                        continue 'left;
+                    }
+                    Equal => {
+                        self.nextr = self.right.next();
+                        self.nextl = self.left.next();
+                        res = Some(vl);
+                        break 'left;
+                    }
+                    Greater => self.nextr = self.right.next(),
+                }
+            }
+            break; // don't bother iterating the remaining lefts
+        }
+
+        // return the current next value
+        res
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> FusedIterator for Intersection<'a, T, O> {}
+
+pub struct Union<'a, T, O> {
+    nextl: Option<&'a T>,
+    nextr: Option<&'a T>,
+    left: Iter<'a, T>,
+    right: Iter<'a, T>,
+    tree: &'a RBBase<T, O>,
+}
+
+impl<'a, T, O: TreeOrder<T>> Iterator for Union<'a, T, O> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        // select and store the next next
+        let mut res = None;
+        if let Some(vl) = self.nextl {
+            let compared = self.nextr.map(|vr| {
+                count!(comparisons => self.tree.ops);
+                (vr, self.tree.order.compare(vl, vr))
+            });
+            match compared {
+                Some((_, Less)) | None => {
+                    self.nextl = self.left.next();
+                    res = Some(vl);
+                }
+                Some((_, Equal)) => {
+                    self.nextr = self.right.next();
+                    self.nextl = self.left.next();
+                    res = Some(vl);
+                }
+                Some((vr, Greater)) => {
+                    self.nextr = self.right.next();
+                    res = Some(vr);
+                }
+            }
+        }
+        if res.is_none() {
+            res = self.nextr;
+            self.nextr = self.right.next();
+        }
+
+        // return the current next value
+        res
+    }
+}
+
+impl<'a, T, O: TreeOrder<T>> FusedIterator for Union<'a, T, O> {}
@@ -0,0 +1,358 @@
+use crate::node::{counting, Node, Refresh};
+use crate::rbbase;
+use crate::RBIntervalMap;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter, Result};
+use std::iter::{ExactSizeIterator, FromIterator, FusedIterator};
+use std::ops::{Bound, Range};
+
+// an interval, its value and the largest end of any
+// interval in the subtree it is the root of. empty
+// intervals overlap nothing so their ends are left out,
+// which keeps any_overlap's pruning sound
+#[derive(Clone)]
+pub struct Interval<K, V> {
+    range: Range<K>,
+    value: V,
+    max_end: Option<K>,
+}
+
+impl<K: PartialOrd, V> Interval<K, V> {
+    fn overlaps(&self, start: &K, end: &K) -> bool {
+        self.range.start < self.range.end && self.range.start < *end && self.range.end > *start
+    }
+}
+
+// keeps max_end up to date as the tree changes shape
+struct MaxEnd;
+
+impl<K: PartialOrd + Clone, V> Refresh<Interval<K, V>> for MaxEnd {
+    fn refresh(
+        &self,
+        interval: &mut Interval<K, V>,
+        left: Option<&Interval<K, V>>,
+        right: Option<&Interval<K, V>>,
+    ) {
+        let range = &interval.range;
+        let mut max_end = if range.start < range.end {
+            Some(&range.end)
+        } else {
+            None
+        };
+        for child in left.iter().chain(right.iter()) {
+            if let Some(end) = &child.max_end {
+                match max_end {
+                    Some(max) if end <= max => {}
+                    _ => max_end = Some(end),
+                }
+            }
+        }
+        interval.max_end = max_end.cloned();
+    }
+}
+
+// intervals are ordered by their start, then their end
+fn cmp_ranges<K: PartialOrd>(l: &Range<K>, r: &Range<K>) -> Ordering {
+    l.start
+        .partial_cmp(&r.start)
+        .unwrap()
+        .then_with(|| l.end.partial_cmp(&r.end).unwrap())
+}
+
+fn max_end<K, V>(node: &Node<Interval<K, V>>) -> Option<&K> {
+    node.value().and_then(|i| i.max_end.as_ref())
+}
+
+impl<K: PartialOrd + Clone, V> RBIntervalMap<K, V> {
+    /// Creates and returns a new, empty RBIntervalMap.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBIntervalMap;
+    ///
+    /// let mut m = RBIntervalMap::new();
+    /// m.insert(0..10, "a");
+    /// m.insert(5..15, "b");
+    /// assert_eq!(m.stab(&7).count(), 2);
+    /// ```
+    pub fn new() -> RBIntervalMap<K, V> {
+        RBIntervalMap {
+            root: Node::default(),
+            contained: 0,
+            #[cfg(feature = "stats")]
+            ops: Default::default(),
+        }
+    }
+
+    /// Clears all intervals from the map.
+    pub fn clear(&mut self) {
+        self.root = Node::default();
+        self.contained = 0;
+    }
+
+    /// Returns the number of intervals in the map.
+    pub fn len(&self) -> usize {
+        self.contained
+    }
+
+    /// Returns true if the map contains no intervals.
+    pub fn is_empty(&self) -> bool {
+        self.contained == 0
+    }
+
+    /// Associates value with the half-open interval range,
+    /// returning the value previously associated with
+    /// exactly that interval, if there was one. Empty
+    /// intervals can be stored but overlap nothing.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBIntervalMap;
+    ///
+    /// let mut m = RBIntervalMap::new();
+    /// assert_eq!(m.insert(0..10, 'a'), None);
+    /// assert_eq!(m.insert(0..5, 'b'), None);
+    /// assert_eq!(m.insert(0..10, 'c'), Some('a'));
+    /// assert_eq!(m.len(), 2);
+    /// ```
+    pub fn insert(&mut self, range: Range<K>, value: V) -> Option<V> {
+        let interval = Interval {
+            max_end: None,
+            range,
+            value,
+        };
+        let cmp = |l: &Interval<K, V>, r: &Interval<K, V>| cmp_ranges(&l.range, &r.range);
+        counting!(self.ops);
+        match self.root.insert_with(interval, &cmp, &MaxEnd) {
+            Some(old) => Some(old.value),
+            None => {
+                self.contained += 1;
+                None
+            }
+        }
+    }
+
+    /// Returns the value associated with exactly the
+    /// interval range, or None if there is none.
+    pub fn get(&self, range: &Range<K>) -> Option<&V> {
+        counting!(self.ops);
+        self.root
+            .get(range, &|r: &Range<K>, i: &Interval<K, V>| {
+                cmp_ranges(r, &i.range)
+            })
+            .map(|i| &i.value)
+    }
+
+    /// Removes exactly the interval range, returning
+    /// its value, or None if it was not in the map.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBIntervalMap;
+    ///
+    /// let mut m = RBIntervalMap::new();
+    /// m.insert(0..10, 'a');
+    /// assert_eq!(m.remove(&(0..9)), None);
+    /// assert_eq!(m.remove(&(0..10)), Some('a'));
+    /// assert!(m.is_empty());
+    /// ```
+    pub fn remove(&mut self, range: &Range<K>) -> Option<V> {
+        let cmp = |r: &Range<K>, i: &Interval<K, V>| cmp_ranges(r, &i.range);
+        counting!(self.ops);
+        let interval = self.root.remove_with(range, &cmp, &MaxEnd)?;
+        self.contained -= 1;
+        Some(interval.value)
+    }
+
+    /// Returns an iterator over the intervals that contain
+    /// point, ordered by their start. Finding the k of them
+    /// takes O(min(n, (k + 1) log n)) time, as each may sit
+    /// at the end of its own path down the tree.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBIntervalMap;
+    ///
+    /// let mut m = RBIntervalMap::new();
+    /// m.insert(0..10, 'a');
+    /// m.insert(5..15, 'b');
+    /// m.insert(10..20, 'c');
+    /// assert_eq!(m.stab(&10).map(|(_, v)| *v).collect::<Vec<_>>(), ['b', 'c']);
+    /// assert_eq!(m.stab(&20).count(), 0);
+    /// ```
+    pub fn stab(&self, point: &K) -> Overlapping<'_, K, V> {
+        let mut iter = Overlapping::new(point.clone(), Bound::Included(point.clone()));
+        iter.push_left_down(&self.root);
+        iter
+    }
+
+    /// Returns an iterator over the intervals that overlap
+    /// the half-open interval range, ordered by their start.
+    /// Like stab, finding the k of them takes
+    /// O(min(n, (k + 1) log n)) time.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBIntervalMap;
+    ///
+    /// let mut m = RBIntervalMap::new();
+    /// m.insert(0..10, 'a');
+    /// m.insert(5..15, 'b');
+    /// m.insert(10..20, 'c');
+    /// let found: Vec<_> = m.overlapping(8..10).map(|(_, v)| *v).collect();
+    /// assert_eq!(found, ['a', 'b']);
+    /// assert_eq!(m.overlapping(20..30).count(), 0);
+    /// ```
+    pub fn overlapping(&self, range: Range<K>) -> Overlapping<'_, K, V> {
+        let empty = range.start >= range.end;
+        let mut iter = Overlapping::new(range.start, Bound::Excluded(range.end));
+        if !empty {
+            iter.push_left_down(&self.root);
+        }
+        iter
+    }
+
+    /// Returns true if any interval overlaps the
+    /// half-open interval range, in O(log n) time.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBIntervalMap;
+    ///
+    /// let mut m = RBIntervalMap::new();
+    /// m.insert(0..10, ());
+    /// m.insert(20..30, ());
+    /// assert!(m.any_overlap(&(9..12)));
+    /// assert!(!m.any_overlap(&(10..20)));
+    /// ```
+    pub fn any_overlap(&self, range: &Range<K>) -> bool {
+        if range.start >= range.end {
+            return false;
+        }
+        let mut cur = &self.root;
+        while let Some(i) = cur.value() {
+            if i.overlaps(&range.start, &range.end) {
+                return true;
+            }
+            // if the left subtree reaches past the start of range
+            // and holds no overlap, neither does the right subtree
+            cur = match max_end(cur.get_left()) {
+                Some(end) if *end > range.start => cur.get_left(),
+                _ => cur.get_right(),
+            };
+        }
+        false
+    }
+
+    /// An iterator that visits all intervals and their
+    /// values, ordered by their start then their end.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: rbbase::Iter::new(&self.root, self.contained),
+        }
+    }
+}
+
+impl<K: PartialOrd + Clone, V> Default for RBIntervalMap<K, V> {
+    fn default() -> Self {
+        RBIntervalMap::new()
+    }
+}
+
+impl<K: PartialOrd + Clone + Debug, V: Debug> Debug for RBIntervalMap<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialOrd + Clone, V> FromIterator<(Range<K>, V)> for RBIntervalMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = RBIntervalMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: PartialOrd + Clone, V> Extend<(Range<K>, V)> for RBIntervalMap<K, V> {
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    iter: rbbase::Iter<'a, Interval<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<(&'a Range<K>, &'a V)> {
+        self.iter.next().map(|i| (&i.range, &i.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+/// Visits the intervals that end after `after` and start
+/// before (or at, for stabbing queries) `before`.
+pub struct Overlapping<'a, K, V> {
+    after: K,
+    before: Bound<K>,
+    ordered: Vec<&'a Node<Interval<K, V>>>,
+}
+
+impl<'a, K: PartialOrd, V> Overlapping<'a, K, V> {
+    fn new(after: K, before: Bound<K>) -> Overlapping<'a, K, V> {
+        Overlapping {
+            after,
+            before,
+            ordered: Vec::new(),
+        }
+    }
+
+    // like insert_left_down, but skips subtrees
+    // in which every interval ends too early
+    fn push_left_down(&mut self, start: &'a Node<Interval<K, V>>) {
+        let mut cur = start;
+        while let Some(end) = max_end(cur) {
+            if *end <= self.after {
+                break;
+            }
+            self.ordered.push(cur);
+            cur = cur.get_left();
+        }
+    }
+
+    fn starts_in_time(&self, start: &K) -> bool {
+        match &self.before {
+            Bound::Included(b) => start <= b,
+            Bound::Excluded(b) => start < b,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<'a, K: PartialOrd, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<(&'a Range<K>, &'a V)> {
+        while let Some(node) = self.ordered.pop() {
+            let interval = node.value().unwrap();
+            if !self.starts_in_time(&interval.range.start) {
+                // everything left to visit starts later still
+                self.ordered.clear();
+                return None;
+            }
+            self.push_left_down(node.get_right());
+            let range = &interval.range;
+            if range.start < range.end && range.end > self.after {
+                return Some((range, &interval.value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, K: PartialOrd, V> FusedIterator for Overlapping<'a, K, V> {}
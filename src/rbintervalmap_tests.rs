@@ -0,0 +1,75 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::RBIntervalMap;
+
+use rand::{Rng, SeedableRng};
+use std::ops::Range;
+
+// checks that the tree is balanced and its intervals ordered
+fn assert_valid(m: &RBIntervalMap<u32, u32>) {
+    assert!(m.root.black_height().is_some() || m.is_empty());
+    let stored: Vec<_> = m.iter().map(|(r, _)| r.clone()).collect();
+    assert_eq!(stored.len(), m.len());
+    assert!(stored
+        .windows(2)
+        .all(|w| (w[0].start, w[0].end) < (w[1].start, w[1].end)));
+}
+
+fn brute(model: &[(Range<u32>, u32)], keep: impl Fn(&Range<u32>) -> bool) -> Vec<u32> {
+    let mut found: Vec<_> = model.iter().filter(|(r, _)| keep(r)).collect();
+    found.sort_by_key(|(r, _)| (r.start, r.end));
+    found.into_iter().map(|(_, v)| *v).collect()
+}
+
+#[test]
+fn test_queries_match_brute_force() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(36);
+    let mut m = RBIntervalMap::new();
+    let mut model: Vec<(Range<u32>, u32)> = Vec::new();
+    for i in 0..4000 {
+        let start = rng.gen_range(0..100);
+        let range = start..start + rng.gen_range(0..20);
+        if rng.gen_bool(0.6) {
+            let old = model.iter().position(|(r, _)| *r == range);
+            let expected = old.map(|p| model.remove(p).1);
+            assert_eq!(m.insert(range.clone(), i), expected);
+            model.push((range, i));
+        } else {
+            let old = model.iter().position(|(r, _)| *r == range);
+            assert_eq!(m.remove(&range), old.map(|p| model.remove(p).1));
+        }
+        assert_valid(&m);
+
+        let p = rng.gen_range(0..120);
+        let stabbed: Vec<_> = m.stab(&p).map(|(_, v)| *v).collect();
+        assert_eq!(stabbed, brute(&model, |r| r.start <= p && p < r.end));
+
+        let a = rng.gen_range(0..120);
+        let q = a..a + rng.gen_range(0..10);
+        let overlapping: Vec<_> = m.overlapping(q.clone()).map(|(_, v)| *v).collect();
+        let expected = brute(&model, |r| {
+            q.start < q.end && r.start < r.end && r.start < q.end && q.start < r.end
+        });
+        assert_eq!(overlapping, expected);
+        assert_eq!(m.any_overlap(&q), !expected.is_empty());
+    }
+}
+
+#[test]
+fn test_empty_intervals() {
+    let mut m = RBIntervalMap::new();
+    m.insert(5..5, 'a');
+    m.insert(0..10, 'b');
+    assert_eq!(m.len(), 2);
+    assert_eq!(m.get(&(5..5)), Some(&'a'));
+    assert_eq!(m.stab(&5).map(|(_, v)| *v).collect::<Vec<_>>(), ['b']);
+    assert_eq!(m.overlapping(3..3).count(), 0);
+    assert!(!m.any_overlap(&(3..3)));
+    assert_eq!(
+        m.overlapping(4..6).map(|(_, v)| *v).collect::<Vec<_>>(),
+        ['b']
+    );
+    m.remove(&(0..10));
+    assert!(!m.any_overlap(&(0..10)));
+}
@@ -1,23 +1,16 @@
-use crate::helpers::write_to_level;
+use crate::helpers::{retain, tree_view};
 use crate::mapper::Mapper;
-use crate::rbtree;
 use crate::{RBMap, RBTree};
 
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter, Result};
+use std::hash::{Hash, Hasher};
 use std::iter::{ExactSizeIterator, FromIterator, FusedIterator};
+use std::marker::PhantomData;
 
 impl<K: PartialOrd + Debug, V: Debug> Debug for RBMap<K, V> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let mut levels = Vec::new();
-        write_to_level(&self.map.root, "".to_string(), 0, &mut levels);
-        let mut f_string = "".to_string();
-        for i in 0..levels.len() {
-            f_string += &levels[i];
-            if i != levels.len() - 1 {
-                f_string += "\n";
-            }
-        }
-        write!(f, "{}", f_string)
+        f.debug_map().entries(self.iter()).finish()
     }
 }
 
@@ -27,6 +20,29 @@ impl<K: PartialOrd + Debug, V: Debug> Display for RBMap<K, V> {
     }
 }
 
+impl<K: PartialOrd + Debug, V: Debug> RBMap<K, V> {
+    /// Renders the structure of the map as an indented
+    /// ASCII diagram, as RBTree's tree_view does, with
+    /// each entry shown as `key => value`.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut m = RBMap::new();
+    /// m.insert(2, 'b');
+    /// m.insert(1, 'a');
+    /// let view = ["B:2 => 'b'", "|-- R:1 => 'a'", "`-- ___"];
+    /// assert_eq!(m.tree_view(), view.join("\n"));
+    /// assert_eq!(format!("{:?}", m), "{1: 'a', 2: 'b'}");
+    /// ```
+    pub fn tree_view(&self) -> String {
+        tree_view(&self.map.root, &|m: &Mapper<K, V>| {
+            let (k, v) = m.pair();
+            format!("{:?} => {:?}", k, v)
+        })
+    }
+}
+
 impl<K: PartialOrd, V> RBMap<K, V> {
     /// Creates and returns a new, empty RBMap
     /// # Example:
@@ -471,6 +487,105 @@ impl<K: PartialOrd, V> RBMap<K, V> {
         self.map.pop_back().map(|v| v.consume())
     }
 
+    /// Removes and returns the pair with the smallest key
+    /// if pred returns true for it, leaving the map
+    /// untouched otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut deadlines = RBMap::new();
+    /// deadlines.insert(3, "build");
+    /// deadlines.insert(8, "deploy");
+    /// let now = 5;
+    /// assert_eq!(deadlines.pop_first_if(|at, _| *at <= now), Some((3, "build")));
+    /// assert_eq!(deadlines.pop_first_if(|at, _| *at <= now), None);
+    /// assert_eq!(deadlines.len(), 1);
+    /// ```
+    pub fn pop_first_if<F: FnOnce(&K, &V) -> bool>(&mut self, pred: F) -> Option<(K, V)> {
+        self.map
+            .pop_first_if(|m| {
+                let (k, v) = m.pair();
+                pred(k, v)
+            })
+            .map(|m| m.consume())
+    }
+
+    /// Removes and returns the pair with the largest key
+    /// if pred returns true for it, leaving the map
+    /// untouched otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut scores = RBMap::new();
+    /// scores.insert(40, "bronze");
+    /// scores.insert(90, "gold");
+    /// let cutoff = 80;
+    /// assert_eq!(scores.pop_last_if(|s, _| *s >= cutoff), Some((90, "gold")));
+    /// assert_eq!(scores.pop_last_if(|s, _| *s >= cutoff), None);
+    /// assert_eq!(scores.len(), 1);
+    /// ```
+    pub fn pop_last_if<F: FnOnce(&K, &V) -> bool>(&mut self, pred: F) -> Option<(K, V)> {
+        self.map
+            .pop_last_if(|m| {
+                let (k, v) = m.pair();
+                pred(k, v)
+            })
+            .map(|m| m.consume())
+    }
+
+    /// Returns a handle to the entry with the smallest
+    /// key, through which its value can be changed or
+    /// the entry removed, or None if the map is empty.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut stock = RBMap::new();
+    /// stock.insert("apples", 1);
+    /// stock.insert("pears", 4);
+    /// let mut first = stock.first_entry().unwrap();
+    /// *first.get_mut() -= 1;
+    /// if *first.get() == 0 {
+    ///     assert_eq!(first.remove_entry(), ("apples", 0));
+    /// }
+    /// assert_eq!(stock.len(), 1);
+    /// ```
+    pub fn first_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        self.occupied(false)
+    }
+
+    /// Returns a handle to the entry with the largest
+    /// key, through which its value can be changed or
+    /// the entry removed, or None if the map is empty.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut m: RBMap<_, _> = (0..4).map(|k| (k, k * 10)).collect();
+    /// let mut last = m.last_entry().unwrap();
+    /// assert_eq!(*last.key(), 3);
+    /// assert_eq!(last.insert(99), 30);
+    /// assert_eq!(m.get(&3), Some(&99));
+    /// ```
+    pub fn last_entry(&mut self) -> Option<OccupiedEntry<'_, K, V>> {
+        self.occupied(true)
+    }
+
+    fn occupied(&mut self, back: bool) -> Option<OccupiedEntry<'_, K, V>> {
+        let map: *mut RBMap<K, V> = self;
+        // SAFETY: map comes from a live &mut, and the reference
+        // made here ends as soon as the entry is found
+        let entry: *mut Mapper<K, V> = unsafe { (*map).map.peek_mut(back)? };
+        Some(OccupiedEntry {
+            map,
+            entry,
+            back,
+            _borrow: PhantomData,
+        })
+    }
+
     /// Removes all key-value pairs that do not return true for the
     /// provided method.
     /// # Example:
@@ -488,13 +603,10 @@ impl<K: PartialOrd, V> RBMap<K, V> {
     /// assert_eq!(pairs.next(), None);
     /// ```
     pub fn retain<F: FnMut(&K, &mut V) -> bool>(&mut self, mut logic: F) {
-        let mut rep = RBMap::new();
-        for (key, mut val) in self.drain() {
-            if logic(&key, &mut val) {
-                rep.insert(key, val);
-            }
-        }
-        std::mem::swap(self, &mut rep);
+        retain(&mut self.map.root, &mut self.map.contained, |m| {
+            let (key, val) = m.mut_pair();
+            logic(key, val)
+        });
     }
 
     /// An iterator that visits all key-value
@@ -514,7 +626,7 @@ impl<K: PartialOrd, V> RBMap<K, V> {
     /// assert_eq!(pairs.next().unwrap(), (&3, &9));
     /// assert_eq!(pairs.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter<K, V> {
+    pub fn iter(&self) -> Iter<'_, K, V> {
         Iter {
             pos: 0,
             ordered: self.ordered(),
@@ -541,9 +653,11 @@ impl<K: PartialOrd, V> RBMap<K, V> {
     /// assert_eq!(pairs.next().unwrap(), (&3, &18));
     /// assert_eq!(pairs.next(), None);
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<K, V> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut ordered = Vec::with_capacity(self.len());
+        self.map.root.ordered_mut(&mut ordered);
         IterMut {
-            iter: self.map.iter(),
+            iter: ordered.into_iter(),
         }
     }
 
@@ -564,7 +678,7 @@ impl<K: PartialOrd, V> RBMap<K, V> {
     /// assert_eq!(*vals.next().unwrap(), 9);
     /// assert_eq!(vals.next(), None);
     /// ```
-    pub fn values(&self) -> Values<K, V> {
+    pub fn values(&self) -> Values<'_, K, V> {
         Values {
             pos: 0,
             ordered: self.ordered(),
@@ -591,7 +705,7 @@ impl<K: PartialOrd, V> RBMap<K, V> {
     /// assert_eq!(*vals.next().unwrap(), 18);
     /// assert_eq!(vals.next(), None);
     /// ```
-    pub fn values_mut(&mut self) -> ValuesMut<K, V> {
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
         ValuesMut {
             iter: self.iter_mut(),
         }
@@ -614,7 +728,7 @@ impl<K: PartialOrd, V> RBMap<K, V> {
     /// assert_eq!(*keys.next().unwrap(), 3);
     /// assert_eq!(keys.next(), None);
     /// ```
-    pub fn keys(&self) -> Keys<K, V> {
+    pub fn keys(&self) -> Keys<'_, K, V> {
         Keys {
             pos: 0,
             ordered: self.ordered(),
@@ -633,7 +747,7 @@ impl<K: PartialOrd, V> RBMap<K, V> {
     /// *val = 3;
     /// assert_eq!(*map.get(&1).unwrap(), 3);
     /// ```
-    pub fn entry(&mut self, key: K) -> Entry<K, V> {
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
         Entry { map: self, key }
     }
 
@@ -725,6 +839,51 @@ impl<K: PartialOrd, V> Default for RBMap<K, V> {
     }
 }
 
+/// Maps are equal when they contain equal key-value
+/// pairs, regardless of the shape of either tree.
+/// # Example:
+/// ```
+/// use rb_tree::RBMap;
+///
+/// let m1: RBMap<i32, char> = vec![(1, 'a'), (2, 'b')].into_iter().collect();
+/// let mut m2 = RBMap::new();
+/// m2.insert(2, 'b');
+/// m2.insert(1, 'a');
+/// assert_eq!(m1, m2);
+/// m2.insert(2, 'c');
+/// assert_ne!(m1, m2);
+/// ```
+impl<K: PartialOrd, V: PartialEq> PartialEq for RBMap<K, V> {
+    fn eq(&self, other: &RBMap<K, V>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Ord, V: Eq> Eq for RBMap<K, V> {}
+
+/// Maps are compared lexicographically by their
+/// key-value pairs in key order, as with std's BTreeMap.
+impl<K: PartialOrd, V: PartialOrd> PartialOrd for RBMap<K, V> {
+    fn partial_cmp(&self, other: &RBMap<K, V>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<K: Ord, V: Ord> Ord for RBMap<K, V> {
+    fn cmp(&self, other: &RBMap<K, V>) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+impl<K: PartialOrd + Hash, V: Hash> Hash for RBMap<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for pair in self.iter() {
+            pair.hash(state);
+        }
+    }
+}
+
 pub struct IntoIter<K: PartialOrd, V> {
     tree: RBTree<Mapper<K, V>>,
 }
@@ -906,24 +1065,14 @@ impl<'a, K: PartialOrd, V> ExactSizeIterator for ValuesMut<'a, K, V> {
 impl<'a, K: PartialOrd, V> FusedIterator for ValuesMut<'a, K, V> {}
 
 pub struct IterMut<'a, K: PartialOrd, V> {
-    iter: rbtree::Iter<'a, Mapper<K, V>>,
+    iter: std::vec::IntoIter<&'a mut Mapper<K, V>>,
 }
 
 impl<'a, K: PartialOrd, V> Iterator for IterMut<'a, K, V> {
     type Item = (&'a K, &'a mut V);
 
     fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
-        let next = self.iter.next();
-        match next {
-            Some(iv) => {
-                let v = unsafe {
-                    let ptr = iv as *const Mapper<K, V>;
-                    &mut *(ptr as *mut Mapper<K, V>)
-                };
-                Some(v.mut_pair())
-            }
-            None => None,
-        }
+        self.iter.next().map(|v| v.mut_pair())
     }
 }
 
@@ -1018,3 +1167,71 @@ impl<'a, K: PartialOrd + Copy, V: Default> Entry<'a, K, V> {
         self.map.get_mut(&self.key).unwrap()
     }
 }
+
+/// A handle to the first or last entry of an RBMap,
+/// as returned by first_entry and last_entry. The entry
+/// is found once, when the handle is made, so reading
+/// and changing it through the handle costs O(1); only
+/// removing it walks down the tree again, to unlink and
+/// rebalance around its node.
+pub struct OccupiedEntry<'a, K: PartialOrd, V> {
+    // both are raw so that neither invalidates the other. the
+    // handle holds the map's unique borrow for 'a, so nothing
+    // else can move or change the entry while it lives
+    map: *mut RBMap<K, V>,
+    entry: *mut Mapper<K, V>,
+    back: bool,
+    _borrow: PhantomData<&'a mut RBMap<K, V>>,
+}
+
+// SAFETY: the handle acts as the &mut RBMap it was made from
+unsafe impl<'a, K: PartialOrd + Send, V: Send> Send for OccupiedEntry<'a, K, V> {}
+unsafe impl<'a, K: PartialOrd + Sync, V: Sync> Sync for OccupiedEntry<'a, K, V> {}
+
+impl<'a, K: PartialOrd, V> OccupiedEntry<'a, K, V> {
+    /// Returns a reference to the entry's key.
+    pub fn key(&self) -> &K {
+        // SAFETY: see the struct's fields
+        unsafe { (*self.entry).key() }
+    }
+
+    /// Returns a reference to the entry's value.
+    pub fn get(&self) -> &V {
+        // SAFETY: see the struct's fields
+        unsafe { (*self.entry).as_ref() }
+    }
+
+    /// Returns a mutable reference to the entry's value.
+    pub fn get_mut(&mut self) -> &mut V {
+        // SAFETY: see the struct's fields
+        unsafe { (*self.entry).as_mut() }
+    }
+
+    /// Converts the handle into a mutable reference
+    /// to the entry's value, borrowed from the map.
+    pub fn into_mut(self) -> &'a mut V {
+        // SAFETY: see the struct's fields. the handle is
+        // consumed, so this is the only way left to the entry
+        unsafe { (*self.entry).as_mut() }
+    }
+
+    /// Sets the entry's value, returning the old one.
+    pub fn insert(&mut self, val: V) -> V {
+        std::mem::replace(self.get_mut(), val)
+    }
+
+    /// Removes the entry from the map, returning its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry from the map, returning its
+    /// key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        // SAFETY: see the struct's fields. entry is not used
+        // again once the map is borrowed to pop it
+        let map = unsafe { &mut (*self.map).map };
+        let popped = if self.back { map.pop_back() } else { map.pop() };
+        popped.unwrap().consume()
+    }
+}
@@ -1,7 +1,13 @@
+use crate::helpers::retain;
 use crate::mapper::SimpleMapper;
-use crate::{Comparator, ComparatorWrapper, RBMapWithCmp, RBTreeWithCmp};
+use crate::order::{ByComparator, ByRef};
+#[cfg(feature = "set")]
+use crate::RBTree;
+use crate::{rbbase, Comparator, RBBase, RBMapWithCmp, RBSet};
+use std::cmp::Ordering;
 use std::fmt;
-use std::iter::FusedIterator;
+use std::hash::{Hash, Hasher};
+use std::iter::{ExactSizeIterator, FromIterator, FusedIterator};
 
 impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// Creates and returns a new, empty RBMapWithCmp
@@ -11,14 +17,69 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     ///
     /// let mut map = RBMapWithCmp::new(TestComparator);
     /// map.insert("Hello", "World");
-    /// assert_eq!(map.remove("Hello").unwrap(), "World");
+    /// assert_eq!(map.remove(&"Hello").unwrap(), "World");
     /// ```
     pub fn new(cmp: F) -> RBMapWithCmp<K, V, F> {
         RBMapWithCmp {
-            map: RBTreeWithCmp::new(ComparatorWrapper::new(cmp)),
+            map: RBBase::with_order(ByComparator::new(cmp)),
         }
     }
 
+    /// Creates a set of references to the keys
+    /// contained in this map, ordered by the
+    /// map's comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert("Hello", "World");
+    /// map.insert("Foo", "Bar");
+    /// let kset = map.keyset();
+    /// assert!(kset.contains(&&"Hello"));
+    /// assert!(kset.contains(&&"Foo"));
+    /// assert!(!kset.contains(&&"Bar"));
+    /// ```
+    pub fn keyset(&self) -> RBBase<&K, ByRef<'_, ByComparator<K, F>>> {
+        RBBase::from_sorted(self.keys().collect(), ByRef::new(&self.map.order))
+    }
+
+    /// Creates a set from the keys in this
+    /// map, using the map's comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert("Hello", "World");
+    /// map.insert("Foo", "Bar");
+    /// let kset = map.into_keyset();
+    /// assert_eq!(kset.iter().collect::<Vec<_>>(), [&"Foo", &"Hello"]);
+    /// ```
+    pub fn into_keyset(mut self) -> RBSet<K, F> {
+        let keys = self.map.take_sorted().into_iter().map(|m| m.consume().0);
+        RBSet {
+            map: RBBase::from_sorted(keys.collect(), self.map.order),
+        }
+    }
+
+    /// Clears all entries from the RBMapWithCmp
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert("Hello", "world");
+    /// map.insert("Foo", "bar");
+    /// assert_eq!(map.len(), 2);
+    /// map.clear();
+    /// assert_eq!(map.len(), 0);
+    /// assert!(map.remove(&"Hello").is_none());
+    /// ```
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
     /// Returns true if the map contains an entry
     /// for key, false otherwise.
     /// # Example:
@@ -26,14 +87,32 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// use rb_tree::{RBMapWithCmp, TestComparator};
     ///
     /// let mut map = RBMapWithCmp::new(TestComparator);
-    /// assert!(!map.contains_key("Hello"));
+    /// assert!(!map.contains_key(&"Hello"));
     /// map.insert("Hello", "world");
     /// assert!(map.contains_key(&"Hello"));
     /// ```
-    pub fn contains_key(&self, key: K) -> bool {
-        match self.map.get(&SimpleMapper::new(key, None)) {
-            None => false,
-            Some(v) => v.is_some(),
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains(key)
+    }
+
+    /// Clears the map and returns an iterator
+    /// over all key-value pairs that were contained
+    /// in the order given by the comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert("Hello", "world");
+    /// map.insert("Foo", "bar");
+    /// let mut drain = map.drain();
+    /// assert_eq!(drain.next().unwrap(), ("Foo", "bar"));
+    /// assert_eq!(drain.next().unwrap(), ("Hello", "world"));
+    /// assert!(drain.next().is_none());
+    /// ```
+    pub fn drain(&mut self) -> Drain<K, V> {
+        Drain {
+            iter: self.map.drain(),
         }
     }
 
@@ -46,14 +125,12 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// use rb_tree::{RBMapWithCmp, TestComparator};
     ///
     /// let mut map = RBMapWithCmp::new(TestComparator);
-    /// assert!(map.get("Hello").is_none());
+    /// assert!(map.get(&"Hello").is_none());
     /// map.insert("Hello", "world");
     /// assert_eq!(map.get(&"Hello").unwrap(), &"world");
     /// ```
-    pub fn get(&self, key: K) -> Option<&V> {
-        self.map
-            .get(&SimpleMapper::new(key, None))
-            .map(|v| v.as_ref())
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(key).map(|v| v.as_ref())
     }
 
     /// Returns an option containing a reference
@@ -65,14 +142,12 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// use rb_tree::{RBMapWithCmp, TestComparator};
     ///
     /// let mut map = RBMapWithCmp::new(TestComparator);
-    /// assert!(map.get("Hello").is_none());
+    /// assert!(map.get(&"Hello").is_none());
     /// map.insert("Hello", "world");
     /// assert_eq!(map.get_pair(&"Hello").unwrap(), (&"Hello", &"world"));
     /// ```
-    pub fn get_pair(&self, key: K) -> Option<(&K, &V)> {
-        self.map
-            .get(&SimpleMapper::new(key, None))
-            .map(|v| (v.key(), v.as_ref()))
+    pub fn get_pair(&self, key: &K) -> Option<(&K, &V)> {
+        self.map.get(key).map(|v| (v.key(), v.as_ref()))
     }
 
     /// Returns an option containing a reference
@@ -85,14 +160,13 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// use rb_tree::{RBMapWithCmp, TestComparator};
     ///
     /// let mut map = RBMapWithCmp::new(TestComparator);
-    /// assert!(map.get("Hello").is_none());
+    /// assert!(map.get_pair_mut(&"Hello").is_none());
     /// map.insert("Hello", "world");
-    /// assert_eq!(map.get_pair(&"Hello").unwrap(), (&"Hello", &"world"));
+    /// *map.get_pair_mut(&"Hello").unwrap().1 = "world!";
+    /// assert_eq!(map.get_pair(&"Hello").unwrap(), (&"Hello", &"world!"));
     /// ```
-    pub fn get_pair_mut(&mut self, key: K) -> Option<(&K, &mut V)> {
-        self.map
-            .get_mut(&SimpleMapper::new(key, None))
-            .map(|v| v.mut_pair())
+    pub fn get_pair_mut(&mut self, key: &K) -> Option<(&K, &mut V)> {
+        self.map.get_mut(key).map(|v| v.mut_pair())
     }
 
     /// Returns an option containing a mutable
@@ -104,15 +178,97 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// use rb_tree::{RBMapWithCmp, TestComparator};
     ///
     /// let mut map = RBMapWithCmp::new(TestComparator);
-    /// assert!(map.get("Hello").is_none());
+    /// assert!(map.get(&"Hello").is_none());
     /// map.insert("Hello", "world");
     /// *map.get_mut(&"Hello").unwrap() = "world!";
     /// assert_eq!(map.get(&"Hello").unwrap(), &"world!");
     /// ```
-    pub fn get_mut(&mut self, key: K) -> Option<&mut V> {
-        self.map
-            .get_mut(&SimpleMapper::new(key, None))
-            .map(|v| v.as_mut())
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.map.get_mut(key).map(|v| v.as_mut())
+    }
+
+    /// Returns an option containing a reference to the
+    /// value associated with the key that the comparator
+    /// places first.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.peek(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.peek().unwrap(), &"World");
+    /// ```
+    pub fn peek(&self) -> Option<&V> {
+        self.map.peek().map(|v| v.as_ref())
+    }
+
+    /// Returns an option containing a reference to the
+    /// value associated with the key that the comparator
+    /// places last.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.peek_back(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.peek_back().unwrap(), &"Foo");
+    /// ```
+    pub fn peek_back(&self) -> Option<&V> {
+        self.map.peek_back().map(|v| v.as_ref())
+    }
+
+    /// Returns an option containing a pair with a reference to
+    /// the key that the comparator places first and a reference
+    /// to its associated value.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.peek_pair(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.peek_pair().unwrap(), (&2, &"World"));
+    /// ```
+    pub fn peek_pair(&self) -> Option<(&K, &V)> {
+        self.map.peek().map(|v| (v.key(), v.as_ref()))
+    }
+
+    /// Returns an option containing a pair with a reference to
+    /// the key that the comparator places last and a reference
+    /// to its associated value.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.peek_pair_back(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.peek_pair_back().unwrap(), (&7, &"Foo"));
+    /// ```
+    pub fn peek_pair_back(&self) -> Option<(&K, &V)> {
+        self.map.peek_back().map(|v| (v.key(), v.as_ref()))
     }
 
     /// Inserts a value to associate with the given key
@@ -160,7 +316,7 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// assert_eq!(map.len(), 1);
     /// map.insert(2, 4);
     /// assert_eq!(map.len(), 2);
-    /// map.remove(2);
+    /// map.remove(&2);
     /// assert_eq!(map.len(), 1);
     /// ```
     pub fn len(&self) -> usize {
@@ -175,18 +331,139 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// use rb_tree::{RBMapWithCmp, TestComparator};
     ///
     /// let mut map = RBMapWithCmp::new(TestComparator);
-    /// assert!(map.remove(2).is_none());
+    /// assert!(map.remove(&2).is_none());
     /// map.insert(2, 4);
-    /// assert_eq!(map.remove(2).unwrap(), 4);
+    /// assert_eq!(map.remove(&2).unwrap(), 4);
     /// ```
-    pub fn remove(&mut self, key: K) -> Option<V> {
-        self.map
-            .take(&SimpleMapper::new(key, None))
-            .map(|v| v.consume().1)
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.map.take(key).map(|v| v.consume().1)
+    }
+
+    /// Removes the key-value pair associated with key,
+    /// if one exists, and returns it, or None if the pair
+    /// did not exist.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert!(map.remove_entry(&2).is_none());
+    /// map.insert(2, 4);
+    /// assert_eq!(map.remove_entry(&2).unwrap(), (2, 4));
+    /// ```
+    pub fn remove_entry(&mut self, key: &K) -> Option<(K, V)> {
+        self.map.take(key).map(|v| v.consume())
+    }
+
+    /// Removes the pair associated with the key that the
+    /// comparator places first and returns the associated value.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.pop(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.pop().unwrap(), "World");
+    /// assert_eq!(map.pop().unwrap(), "Hello");
+    /// ```
+    pub fn pop(&mut self) -> Option<V> {
+        self.map.pop().map(|v| v.consume().1)
+    }
+
+    /// Removes the pair associated with the key that the
+    /// comparator places last and returns the associated value.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.pop_back(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.pop_back().unwrap(), "Foo");
+    /// assert_eq!(map.pop_back().unwrap(), "Bar");
+    /// ```
+    pub fn pop_back(&mut self) -> Option<V> {
+        self.map.pop_back().map(|v| v.consume().1)
+    }
+
+    /// Removes the pair associated with the key that the
+    /// comparator places first and returns it.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.pop_pair(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.pop_pair().unwrap(), (2, "World"));
+    /// assert_eq!(map.pop_pair().unwrap(), (5, "Hello"));
+    /// ```
+    pub fn pop_pair(&mut self) -> Option<(K, V)> {
+        self.map.pop().map(|v| v.consume())
+    }
+
+    /// Removes the pair associated with the key that the
+    /// comparator places last and returns it.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// assert_eq!(map.pop_pair_back(), None);
+    ///
+    /// map.insert(5, "Hello");
+    /// map.insert(2, "World");
+    /// map.insert(7, "Foo");
+    /// map.insert(6, "Bar");
+    ///
+    /// assert_eq!(map.pop_pair_back().unwrap(), (7, "Foo"));
+    /// assert_eq!(map.pop_pair_back().unwrap(), (6, "Bar"));
+    /// ```
+    pub fn pop_pair_back(&mut self) -> Option<(K, V)> {
+        self.map.pop_back().map(|v| v.consume())
+    }
+
+    /// Removes all key-value pairs that do not return true for the
+    /// provided method.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert(1, 1);
+    /// map.insert(2, 4);
+    /// map.insert(3, 9);
+    /// map.retain(|_, v| *v % 2 == 0);
+    ///
+    /// let mut pairs = map.drain();
+    /// assert_eq!(pairs.next().unwrap(), (2, 4));
+    /// assert_eq!(pairs.next(), None);
+    /// ```
+    pub fn retain<P: FnMut(&K, &mut V) -> bool>(&mut self, mut logic: P) {
+        retain(&mut self.map.root, &mut self.map.contained, |m| {
+            let (key, val) = m.mut_pair();
+            logic(key, val)
+        });
     }
 
     /// An iterator that visits all key-value
-    /// pairs in their key's partialord order.
+    /// pairs in the order given by the comparator.
     /// # Example:
     /// ```
     /// use rb_tree::{RBMapWithCmp, TestComparator};
@@ -202,72 +479,499 @@ impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
     /// assert_eq!(pairs.next().unwrap(), (&3, &9));
     /// assert_eq!(pairs.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter<K, V> {
+    pub fn iter(&self) -> Iter<'_, K, V> {
         Iter {
-            pos: 0,
-            ordered: self.ordered(),
+            iter: self.map.iter(),
+        }
+    }
+
+    /// An iterator that visits all key-value
+    /// pairs in the order given by the comparator
+    /// and presents the value only as mutable.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert(1, 1);
+    /// map.insert(2, 4);
+    /// map.insert(3, 9);
+    ///
+    /// map.iter_mut().for_each(|(_, v)| *v *= 2);
+    ///
+    /// let mut pairs = map.iter();
+    /// assert_eq!(pairs.next().unwrap(), (&1, &2));
+    /// assert_eq!(pairs.next().unwrap(), (&2, &8));
+    /// assert_eq!(pairs.next().unwrap(), (&3, &18));
+    /// assert_eq!(pairs.next(), None);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        let mut ordered = Vec::with_capacity(self.len());
+        self.map.root.ordered_mut(&mut ordered);
+        IterMut {
+            iter: ordered.into_iter(),
+        }
+    }
+
+    /// An iterator that visits all values
+    /// in the order given by the comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert(1, 1);
+    /// map.insert(2, 4);
+    /// map.insert(3, 9);
+    ///
+    /// let mut vals = map.values();
+    /// assert_eq!(*vals.next().unwrap(), 1);
+    /// assert_eq!(*vals.next().unwrap(), 4);
+    /// assert_eq!(*vals.next().unwrap(), 9);
+    /// assert_eq!(vals.next(), None);
+    /// ```
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { iter: self.iter() }
+    }
+
+    /// An iterator that visits all values
+    /// in the order given by the comparator
+    /// and presents them as mutable.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert(1, 1);
+    /// map.insert(2, 4);
+    /// map.insert(3, 9);
+    ///
+    /// map.values_mut().for_each(|v| *v *= 2);
+    ///
+    /// let mut vals = map.values();
+    /// assert_eq!(*vals.next().unwrap(), 2);
+    /// assert_eq!(*vals.next().unwrap(), 8);
+    /// assert_eq!(*vals.next().unwrap(), 18);
+    /// assert_eq!(vals.next(), None);
+    /// ```
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            iter: self.iter_mut(),
         }
     }
 
+    /// An iterator that visits all keys
+    /// in the order given by the comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert(1, 1);
+    /// map.insert(2, 4);
+    /// map.insert(3, 9);
+    ///
+    /// let mut keys = map.keys();
+    /// assert_eq!(*keys.next().unwrap(), 1);
+    /// assert_eq!(*keys.next().unwrap(), 2);
+    /// assert_eq!(*keys.next().unwrap(), 3);
+    /// assert_eq!(keys.next(), None);
+    /// ```
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { iter: self.iter() }
+    }
+
+    /// Provides an interface for ensuring values
+    /// are allocated to the given key.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    ///
+    /// let val = map.entry(1).or_insert(2);
+    /// *val = 3;
+    /// assert_eq!(*map.get(&1).unwrap(), 3);
+    /// ```
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, F> {
+        Entry { map: self, key }
+    }
+
+    /// Returns a vector of the key-value pairs
+    /// in the order given by the comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert(2, 'b');
+    /// map.insert(1, 'a');
+    /// assert_eq!(map.ordered(), [(&1, &'a'), (&2, &'b')]);
+    /// ```
     pub fn ordered(&self) -> Vec<(&K, &V)> {
-        self.map.iter().map(|m| (m.key(), m.as_ref())).collect()
+        self.iter().collect()
     }
+}
 
-    pub fn clear(&mut self) {
-        self.map.clear();
+#[cfg(feature = "set")]
+impl<K, V: PartialOrd, F: Comparator<K>> RBMapWithCmp<K, V, F> {
+    /// Creates an RBTree set of the values
+    /// contained in this map.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert("Hello", "World");
+    /// map.insert("Foo", "Bar");
+    /// let vset = map.valueset();
+    /// assert!(vset.contains(&&"World"));
+    /// assert!(vset.contains(&&"Bar"));
+    /// assert!(!vset.contains(&&"Foo"));
+    /// ```
+    pub fn valueset(&self) -> RBTree<&V> {
+        self.values().collect()
+    }
+
+    /// Creates a set of keys, ordered by the map's
+    /// comparator, and a set of values from the given map.
+    ///
+    /// Note: any mapping information is lost
+    /// when this operation is performed.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert("Hello", "World");
+    /// map.insert("Foo", "Bar");
+    /// let (kset, vset) = map.into_sets();
+    /// assert_eq!(kset.iter().collect::<Vec<_>>(), [&"Foo", &"Hello"]);
+    /// assert!(vset.contains(&"World"));
+    /// assert!(vset.contains(&"Bar"));
+    /// assert!(!vset.contains(&"Foo"));
+    /// ```
+    pub fn into_sets(mut self) -> (RBSet<K, F>, RBTree<V>) {
+        let mut keys = Vec::with_capacity(self.len());
+        let mut vset = RBTree::new();
+        for (key, value) in self.map.take_sorted().into_iter().map(|m| m.consume()) {
+            keys.push(key);
+            vset.insert(value);
+        }
+        let kset = RBSet {
+            map: RBBase::from_sorted(keys, self.map.order),
+        };
+        (kset, vset)
+    }
+
+    /// Creates an RBTree set from the values
+    /// contained in this map.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBMapWithCmp, TestComparator};
+    ///
+    /// let mut map = RBMapWithCmp::new(TestComparator);
+    /// map.insert("Hello", "World");
+    /// map.insert("Foo", "Bar");
+    /// let vset = map.into_valueset();
+    /// assert!(vset.contains(&"World"));
+    /// assert!(vset.contains(&"Bar"));
+    /// assert!(!vset.contains(&"Foo"));
+    /// ```
+    pub fn into_valueset(self) -> RBTree<V> {
+        self.into_iter().map(|(_, v)| v).collect()
+    }
+}
+
+impl<K, V, F: Comparator<K> + Default> Default for RBMapWithCmp<K, V, F> {
+    fn default() -> Self {
+        RBMapWithCmp::new(F::default())
+    }
+}
+
+/// Maps are equal when they contain equal key-value
+/// pairs in the same order.
+/// # Example:
+/// ```
+/// use rb_tree::{RBMapWithCmp, TestComparator};
+///
+/// let mut m1 = RBMapWithCmp::new(TestComparator);
+/// let mut m2 = RBMapWithCmp::new(TestComparator);
+/// m1.insert(1, 'a');
+/// m1.insert(2, 'b');
+/// m2.insert(2, 'b');
+/// m2.insert(1, 'a');
+/// assert_eq!(m1, m2);
+/// ```
+impl<K: PartialEq, V: PartialEq, F: Comparator<K>> PartialEq for RBMapWithCmp<K, V, F> {
+    fn eq(&self, other: &RBMapWithCmp<K, V, F>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<K: Eq, V: Eq, F: Comparator<K>> Eq for RBMapWithCmp<K, V, F> {}
+
+impl<K: PartialOrd, V: PartialOrd, F: Comparator<K>> PartialOrd for RBMapWithCmp<K, V, F> {
+    fn partial_cmp(&self, other: &RBMapWithCmp<K, V, F>) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<K: Ord, V: Ord, F: Comparator<K>> Ord for RBMapWithCmp<K, V, F> {
+    fn cmp(&self, other: &RBMapWithCmp<K, V, F>) -> Ordering {
+        self.iter().cmp(other.iter())
     }
 }
 
-pub struct IntoIter<K, V, F: 'static + Comparator<K>> {
-    tree: RBTreeWithCmp<SimpleMapper<K, V>, ComparatorWrapper<K, F>>,
+impl<K: Hash, V: Hash, F: Comparator<K>> Hash for RBMapWithCmp<K, V, F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for pair in self.iter() {
+            pair.hash(state);
+        }
+    }
 }
 
-impl<K, V, F: Comparator<K>> Iterator for IntoIter<K, V, F> {
+pub struct IntoIter<K, V> {
+    iter: rbbase::IntoIter<SimpleMapper<K, V>>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
     type Item = (K, V);
 
     fn next(&mut self) -> Option<(K, V)> {
-        self.tree.pop().map(|v| v.consume())
+        self.iter.next().map(|v| v.consume())
     }
 }
 
-impl<K, V, F: Comparator<K>> ExactSizeIterator for IntoIter<K, V, F> {
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {
     fn len(&self) -> usize {
-        self.tree.len()
+        self.iter.len()
     }
 }
 
-impl<K, V, F: Comparator<K>> FusedIterator for IntoIter<K, V, F> {}
+impl<K, V> FusedIterator for IntoIter<K, V> {}
 
 impl<K, V, F: Comparator<K>> IntoIterator for RBMapWithCmp<K, V, F> {
     type Item = (K, V);
-    type IntoIter = IntoIter<K, V, F>;
+    type IntoIter = IntoIter<K, V>;
 
-    fn into_iter(self) -> IntoIter<K, V, F> {
-        IntoIter { tree: self.map }
+    fn into_iter(self) -> IntoIter<K, V> {
+        IntoIter {
+            iter: self.map.into_iter(),
+        }
+    }
+}
+
+impl<K, V, F: Comparator<K> + Default> FromIterator<(K, V)> for RBMapWithCmp<K, V, F> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = RBMapWithCmp::default();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, F: Comparator<K>> Extend<(K, V)> for RBMapWithCmp<K, V, F> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, val) in iter {
+            self.insert(key, val);
+        }
+    }
+}
+
+impl<'a, K: Copy + 'a, V: Copy + 'a, F: Comparator<K>> Extend<(&'a K, &'a V)>
+    for RBMapWithCmp<K, V, F>
+{
+    fn extend<I: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: I) {
+        for (&key, &val) in iter {
+            self.insert(key, val);
+        }
     }
 }
 
 pub struct Iter<'a, K, V> {
-    pos: usize,
-    ordered: Vec<(&'a K, &'a V)>,
+    iter: rbbase::Iter<'a, SimpleMapper<K, V>>,
 }
 
 impl<'a, K, V> Iterator for Iter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<(&'a K, &'a V)> {
-        match self.ordered.get(self.pos) {
-            Some(v) => {
-                self.pos += 1;
-                Some(*v)
+        self.iter.next().map(|v| (v.key(), v.as_ref()))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+pub struct Keys<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<&'a K> {
+        self.iter.next().map(|(k, _)| k)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for Keys<'a, K, V> {}
+
+pub struct Values<'a, K, V> {
+    iter: Iter<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<&'a V> {
+        self.iter.next().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
+
+pub struct ValuesMut<'a, K, V> {
+    iter: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<&'a mut V> {
+        self.iter.next().map(|(_, v)| v)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for ValuesMut<'a, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    iter: std::vec::IntoIter<&'a mut SimpleMapper<K, V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        self.iter.next().map(|v| v.mut_pair())
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K, V> FusedIterator for IterMut<'a, K, V> {}
+
+pub struct Drain<K, V> {
+    iter: rbbase::Drain<SimpleMapper<K, V>>,
+}
+
+impl<K, V> Iterator for Drain<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.iter.next().map(|v| v.consume())
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<K, V> FusedIterator for Drain<K, V> {}
+
+pub struct Entry<'a, K, V, F: Comparator<K>> {
+    map: &'a mut RBMapWithCmp<K, V, F>,
+    key: K,
+}
+
+/// Follows a similar implementation to std::collections::HashMap,
+/// in terms of behaviour, only differs in types used.
+/// For further detail about any given method, please refer
+/// to the documentation of HashMap::Entry.
+/// For the time being only copyable keys can utilise
+/// these methods
+impl<'a, K: Copy, V, F: Comparator<K>> Entry<'a, K, V, F> {
+    pub fn insert(self, val: V) -> (&'a K, &'a mut V) {
+        match self.map.remove_entry(&self.key) {
+            Some((k, _)) => {
+                self.map.insert(k, val);
+            }
+            None => {
+                self.map.insert(self.key, val);
             }
-            None => None,
         }
+        self.map.get_pair_mut(&self.key).unwrap()
+    }
+
+    pub fn and_modify<P>(self, f: P) -> Entry<'a, K, V, F>
+    where
+        P: FnOnce(&mut V),
+    {
+        if let Some(v) = self.map.get_mut(&self.key) {
+            f(v);
+        }
+        self
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        if !self.map.contains_key(&self.key) {
+            self.map.insert(self.key, default);
+        }
+        self.map.get_mut(&self.key).unwrap()
+    }
+
+    pub fn or_insert_with<P>(self, default: P) -> &'a mut V
+    where
+        P: FnOnce() -> V,
+    {
+        if !self.map.contains_key(&self.key) {
+            self.map.insert(self.key, default());
+        }
+        self.map.get_mut(&self.key).unwrap()
+    }
+}
+
+impl<'a, K: Copy, V: Default, F: Comparator<K>> Entry<'a, K, V, F> {
+    pub fn or_default(self) -> &'a mut V {
+        if !self.map.contains_key(&self.key) {
+            self.map.insert(self.key, V::default());
+        }
+        self.map.get_mut(&self.key).unwrap()
     }
 }
 
 impl<K: fmt::Debug, V: fmt::Debug, F: Comparator<K>> fmt::Debug for RBMapWithCmp<K, V, F> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self.ordered())
+        f.debug_map().entries(self.iter()).finish()
     }
 }
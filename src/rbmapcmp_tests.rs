@@ -1,4 +1,5 @@
-use crate::{RBMapWithCmp, TestComparator};
+use crate::{Comparator, RBMapWithCmp, TestComparator};
+use std::cmp::Ordering;
 
 #[test]
 fn test_print() {
@@ -7,7 +8,7 @@ fn test_print() {
     t.insert(3, 3);
     t.insert(1, 1);
     t.insert(4, 4);
-    assert_eq!(format!("{:?}", t), "[(1, 1), (2, 2), (3, 3), (4, 4)]");
+    assert_eq!(format!("{:?}", t), "{1: 1, 2: 2, 3: 3, 4: 4}");
     assert_eq!(t.len(), 4);
 }
 
@@ -48,3 +49,43 @@ fn test_rev() {
         count -= 1;
     }
 }
+
+struct Reverse;
+
+impl Comparator<i32> for Reverse {
+    fn cmp(&self) -> Box<dyn Fn(&i32, &i32) -> Ordering> {
+        Box::new(|a: &i32, b: &i32| b.cmp(a))
+    }
+}
+
+#[test]
+fn test_parity() {
+    let mut m = RBMapWithCmp::new(Reverse);
+    m.extend((0..10).map(|k| (k, k * k)));
+    assert_eq!(m.len(), 10);
+    assert!(m.contains_key(&3));
+    assert_eq!(m.get(&3), Some(&9));
+    assert_eq!(m.get_pair(&4), Some((&4, &16)));
+    assert_eq!(m.peek_pair(), Some((&9, &81)));
+    assert_eq!(m.peek_pair_back(), Some((&0, &0)));
+
+    m.iter_mut().for_each(|(_, v)| *v += 1);
+    *m.get_mut(&5).unwrap() = 0;
+    *m.entry(6).or_insert(100) += 1;
+    *m.entry(20).or_default() += 2;
+    assert_eq!(m.get(&5), Some(&0));
+    assert_eq!(m.get(&6), Some(&38));
+    assert_eq!(m.get(&20), Some(&2));
+
+    m.retain(|k, _| k % 2 == 0);
+    assert_eq!(m.keys().copied().collect::<Vec<_>>(), [20, 8, 6, 4, 2, 0]);
+    assert_eq!(m.keyset().ordered(), [&&20, &&8, &&6, &&4, &&2, &&0]);
+    assert_eq!(m.pop_pair(), Some((20, 2)));
+    assert_eq!(m.pop_pair_back(), Some((0, 1)));
+    assert_eq!(m.remove_entry(&4), Some((4, 17)));
+    assert_eq!(m.remove(&4), None);
+
+    let drained: Vec<_> = m.drain().collect();
+    assert_eq!(drained, [(8, 65), (6, 38), (2, 5)]);
+    assert!(m.is_empty());
+}
@@ -0,0 +1,517 @@
+use crate::helpers::{insert_from, insert_left_down};
+use crate::mapper::Mapper;
+use crate::node::{counting, Node};
+use crate::rbmap;
+use crate::{RBMap, RBRangeMap, RBRangeSet};
+use std::cmp::Ordering::{Equal, Less};
+use std::fmt::{Debug, Formatter, Result};
+use std::iter::{ExactSizeIterator, FromIterator, FusedIterator};
+use std::ops::{Bound, Range};
+
+// ranges are stored by their start, alongside their end
+type Entry<K, V> = Mapper<K, (K, V)>;
+
+fn cmp_start<K: PartialOrd, V>(point: &K, entry: &Entry<K, V>) -> std::cmp::Ordering {
+    point.partial_cmp(entry.key()).unwrap()
+}
+
+impl<K: PartialOrd + Clone, V> RBRangeMap<K, V> {
+    /// Creates and returns a new, empty RBRangeMap.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeMap;
+    ///
+    /// let mut m = RBRangeMap::new();
+    /// m.insert(0..10, "a");
+    /// assert_eq!(m.get(&5), Some(&"a"));
+    /// assert_eq!(m.get(&10), None);
+    /// ```
+    pub fn new() -> RBRangeMap<K, V> {
+        RBRangeMap { map: RBMap::new() }
+    }
+
+    /// Clears all ranges from the map.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns the number of disjoint ranges in the map.
+    /// Adjacent ranges holding equal values are merged,
+    /// so count as one.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the map contains no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the value of the range containing point,
+    /// or None if no range contains it.
+    pub fn get(&self, point: &K) -> Option<&V> {
+        self.get_range(point).map(|(_, v)| v)
+    }
+
+    /// Returns the range containing point along
+    /// with its value, or None if no range contains it.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeMap;
+    ///
+    /// let mut m = RBRangeMap::new();
+    /// m.insert(0..5, 'a');
+    /// m.insert(5..10, 'a');
+    /// assert_eq!(m.get_range(&7), Some((0..10, &'a')));
+    /// ```
+    pub fn get_range(&self, point: &K) -> Option<(Range<K>, &V)> {
+        counting!(self.map.map.ops);
+        let (start, (end, value)) = self.map.map.root.floor(point, &cmp_start)?.pair();
+        if end > point {
+            Some((start.clone()..end.clone(), value))
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if some range contains point.
+    pub fn contains(&self, point: &K) -> bool {
+        self.get(point).is_some()
+    }
+
+    /// Returns an iterator over the stored ranges that
+    /// overlap range, along with their values, ordered
+    /// by their start. The first and last ranges
+    /// returned may extend beyond range, and an empty
+    /// or inverted range overlaps nothing.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeMap;
+    ///
+    /// let mut m = RBRangeMap::new();
+    /// m.insert(0..10, 'a');
+    /// m.insert(10..20, 'b');
+    /// m.insert(30..40, 'c');
+    /// let found: Vec<_> = m.overlapping(5..30).collect();
+    /// assert_eq!(found, [(0..10, &'a'), (10..20, &'b')]);
+    /// ```
+    pub fn overlapping(&self, range: Range<K>) -> Overlapping<'_, K, V> {
+        // an empty range overlaps nothing, even where it is contained
+        if range.start >= range.end {
+            return Overlapping {
+                end: Bound::Unbounded,
+                ordered: Vec::new(),
+            };
+        }
+        self.reaching(range.start, Bound::Excluded(range.end), false)
+    }
+
+    /// Returns an iterator over the parts of range
+    /// that are not covered by any stored range.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeMap;
+    ///
+    /// let mut m = RBRangeMap::new();
+    /// m.insert(10..20, ());
+    /// m.insert(25..30, ());
+    /// assert_eq!(m.gaps(0..40).collect::<Vec<_>>(), [0..10, 20..25, 30..40]);
+    /// assert_eq!(m.gaps(12..18).count(), 0);
+    /// ```
+    pub fn gaps(&self, range: Range<K>) -> Gaps<'_, K, V> {
+        Gaps {
+            cursor: range.start.clone(),
+            end: range.end.clone(),
+            iter: self.overlapping(range),
+        }
+    }
+
+    /// An iterator that visits every stored range
+    /// and its value, ordered by their start.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.map.iter(),
+        }
+    }
+
+    // every range, in order
+    fn all(&self) -> Overlapping<'_, K, V> {
+        let mut iter = Overlapping {
+            end: Bound::Unbounded,
+            ordered: Vec::new(),
+        };
+        insert_left_down(&self.map.map.root, &mut iter.ordered);
+        iter
+    }
+
+    // the ranges that start before end and that end after
+    // point, or at it when touching is set
+    fn reaching(&self, point: K, end: Bound<K>, touching: bool) -> Overlapping<'_, K, V> {
+        counting!(self.map.map.ops);
+        let root = &self.map.map.root;
+        let mut iter = Overlapping {
+            end,
+            ordered: Vec::new(),
+        };
+        // the range starting before point may reach past it. when
+        // touching, ranges starting at point are found below anyway,
+        // so look strictly before it for one that ends there
+        let before = |p: &K, e: &Entry<K, V>| match cmp_start(p, e) {
+            Equal if touching => Less,
+            order => order,
+        };
+        let from = match root.floor(&point, &before) {
+            Some(entry) if entry.as_ref().0 > point || (touching && entry.as_ref().0 == point) => {
+                entry.key()
+            }
+            _ => &point,
+        };
+        insert_from(root, from, &cmp_start, &mut iter.ordered);
+        iter
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone + PartialEq> RBRangeMap<K, V> {
+    /// Associates value with every point in range,
+    /// overwriting (and splitting, where needed) the
+    /// ranges it overlaps. Ranges left adjacent or
+    /// overlapping with equal values are merged into one.
+    /// Empty ranges are ignored.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeMap;
+    ///
+    /// let mut m = RBRangeMap::new();
+    /// m.insert(0..10, 'a');
+    /// m.insert(4..6, 'b');
+    /// assert_eq!(m.iter().collect::<Vec<_>>(), [(0..4, &'a'), (4..6, &'b'), (6..10, &'a')]);
+    /// m.insert(4..6, 'a');
+    /// assert_eq!(m.iter().collect::<Vec<_>>(), [(0..10, &'a')]);
+    /// ```
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        let Range { mut start, mut end } = range;
+        for (s, (e, v)) in self.take(&start, &end, true) {
+            if v == value {
+                // only the first and last ranges taken can
+                // reach past the new one
+                if s < start {
+                    start = s;
+                }
+                if e > end {
+                    end = e;
+                }
+                continue;
+            }
+            if s < start {
+                self.map.insert(s, (start.clone(), v.clone()));
+            }
+            if e > end {
+                self.map.insert(end.clone(), (e, v));
+            }
+        }
+        self.map.insert(start, (end, value));
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone> RBRangeMap<K, V> {
+    /// Removes every point in range from the map,
+    /// splitting the ranges that extend beyond it.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeMap;
+    ///
+    /// let mut m = RBRangeMap::new();
+    /// m.insert(0..10, 'a');
+    /// m.remove(3..5);
+    /// assert_eq!(m.iter().collect::<Vec<_>>(), [(0..3, &'a'), (5..10, &'a')]);
+    /// ```
+    pub fn remove(&mut self, range: Range<K>) {
+        if range.start >= range.end {
+            return;
+        }
+        for (s, (e, v)) in self.take(&range.start, &range.end, false) {
+            if s < range.start {
+                self.map.insert(s, (range.start.clone(), v.clone()));
+            }
+            if e > range.end {
+                self.map.insert(range.end.clone(), (e, v));
+            }
+        }
+    }
+
+    // removes and returns the ranges overlapping start..end,
+    // along with those adjacent to it when touching is set
+    fn take(&mut self, start: &K, end: &K, touching: bool) -> Vec<(K, (K, V))> {
+        let end = if touching {
+            Bound::Included(end.clone())
+        } else {
+            Bound::Excluded(end.clone())
+        };
+        let starts: Vec<K> = self
+            .reaching(start.clone(), end, touching)
+            .map(|(r, _)| r.start)
+            .collect();
+        starts
+            .iter()
+            .map(|s| self.map.remove_entry(s).unwrap())
+            .collect()
+    }
+}
+
+impl<K: PartialOrd + Clone, V> Default for RBRangeMap<K, V> {
+    fn default() -> Self {
+        RBRangeMap::new()
+    }
+}
+
+impl<K: PartialOrd + Clone + Debug, V: Debug> Debug for RBRangeMap<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone + PartialEq> FromIterator<(Range<K>, V)>
+    for RBRangeMap<K, V>
+{
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = RBRangeMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone + PartialEq> Extend<(Range<K>, V)> for RBRangeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+impl<K: PartialOrd + Clone> RBRangeSet<K> {
+    /// Creates and returns a new, empty RBRangeSet.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeSet;
+    ///
+    /// let mut s = RBRangeSet::new();
+    /// s.insert(0..10);
+    /// s.insert(10..20);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [0..20]);
+    /// ```
+    pub fn new() -> RBRangeSet<K> {
+        RBRangeSet {
+            map: RBRangeMap::new(),
+        }
+    }
+
+    /// Clears all ranges from the set.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns the number of disjoint,
+    /// non-adjacent ranges in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set contains no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns true if some range contains point.
+    pub fn contains(&self, point: &K) -> bool {
+        self.map.contains(point)
+    }
+
+    /// Returns the range containing point,
+    /// or None if no range contains it.
+    pub fn get_range(&self, point: &K) -> Option<Range<K>> {
+        self.map.get_range(point).map(|(r, _)| r)
+    }
+
+    /// Adds every point in range to the set, merging
+    /// it with the ranges it overlaps or touches.
+    pub fn insert(&mut self, range: Range<K>) {
+        self.map.insert(range, ());
+    }
+
+    /// Removes every point in range from the set,
+    /// splitting the ranges that extend beyond it.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeSet;
+    ///
+    /// let mut s = RBRangeSet::new();
+    /// s.insert(0..10);
+    /// s.remove(0..2);
+    /// s.remove(8..12);
+    /// assert_eq!(s.iter().collect::<Vec<_>>(), [2..8]);
+    /// ```
+    pub fn remove(&mut self, range: Range<K>) {
+        self.map.remove(range);
+    }
+
+    /// Returns an iterator over the stored ranges
+    /// that overlap range, ordered by their start.
+    pub fn overlapping(&self, range: Range<K>) -> Ranges<'_, K> {
+        Ranges {
+            iter: self.map.overlapping(range),
+        }
+    }
+
+    /// Returns an iterator over the parts of range
+    /// that are not in the set.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBRangeSet;
+    ///
+    /// let mut s = RBRangeSet::new();
+    /// s.insert(10..20);
+    /// assert_eq!(s.gaps(0..15).collect::<Vec<_>>(), [0..10]);
+    /// ```
+    pub fn gaps(&self, range: Range<K>) -> Gaps<'_, K, ()> {
+        self.map.gaps(range)
+    }
+
+    /// An iterator that visits every stored range in order.
+    pub fn iter(&self) -> Ranges<'_, K> {
+        Ranges {
+            iter: self.map.all(),
+        }
+    }
+}
+
+impl<K: PartialOrd + Clone> Default for RBRangeSet<K> {
+    fn default() -> Self {
+        RBRangeSet::new()
+    }
+}
+
+impl<K: PartialOrd + Clone + Debug> Debug for RBRangeSet<K> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialOrd + Clone> FromIterator<Range<K>> for RBRangeSet<K> {
+    fn from_iter<I: IntoIterator<Item = Range<K>>>(iter: I) -> Self {
+        let mut set = RBRangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K: PartialOrd + Clone> Extend<Range<K>> for RBRangeSet<K> {
+    fn extend<I: IntoIterator<Item = Range<K>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+pub struct Iter<'a, K: PartialOrd, V> {
+    iter: rbmap::Iter<'a, K, (K, V)>,
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for Iter<'a, K, V> {
+    type Item = (Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<(Range<K>, &'a V)> {
+        self.iter
+            .next()
+            .map(|(start, (end, value))| (start.clone()..end.clone(), value))
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> ExactSizeIterator for Iter<'a, K, V> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> FusedIterator for Iter<'a, K, V> {}
+
+/// Visits the stored ranges, in order, until
+/// one starts at or after the end of the query.
+pub struct Overlapping<'a, K: PartialOrd, V> {
+    end: Bound<K>,
+    ordered: Vec<&'a Node<Entry<K, V>>>,
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for Overlapping<'a, K, V> {
+    type Item = (Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<(Range<K>, &'a V)> {
+        let node = self.ordered.pop()?;
+        let (start, (end, value)) = node.value().unwrap().pair();
+        let in_time = match &self.end {
+            Bound::Included(e) => start <= e,
+            Bound::Excluded(e) => start < e,
+            Bound::Unbounded => true,
+        };
+        if !in_time {
+            self.ordered.clear();
+            return None;
+        }
+        insert_left_down(node.get_right(), &mut self.ordered);
+        Some((start.clone()..end.clone(), value))
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> FusedIterator for Overlapping<'a, K, V> {}
+
+/// Visits the parts of a range not covered by the map.
+pub struct Gaps<'a, K: PartialOrd, V> {
+    cursor: K,
+    end: K,
+    iter: Overlapping<'a, K, V>,
+}
+
+impl<'a, K: PartialOrd + Clone, V> Iterator for Gaps<'a, K, V> {
+    type Item = Range<K>;
+
+    fn next(&mut self) -> Option<Range<K>> {
+        while self.cursor < self.end {
+            match self.iter.next() {
+                Some((covered, _)) => {
+                    let gap = self.cursor.clone()..covered.start;
+                    if covered.end > self.cursor {
+                        self.cursor = covered.end;
+                    }
+                    if gap.start < gap.end {
+                        return Some(gap);
+                    }
+                }
+                None => {
+                    let gap = self.cursor.clone()..self.end.clone();
+                    self.cursor = self.end.clone();
+                    return Some(gap);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<'a, K: PartialOrd + Clone, V> FusedIterator for Gaps<'a, K, V> {}
+
+/// Visits the ranges of an RBRangeSet in order.
+pub struct Ranges<'a, K: PartialOrd> {
+    iter: Overlapping<'a, K, ()>,
+}
+
+impl<'a, K: PartialOrd + Clone> Iterator for Ranges<'a, K> {
+    type Item = Range<K>;
+
+    fn next(&mut self) -> Option<Range<K>> {
+        self.iter.next().map(|(range, _)| range)
+    }
+}
+
+impl<'a, K: PartialOrd + Clone> FusedIterator for Ranges<'a, K> {}
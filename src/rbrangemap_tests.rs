@@ -0,0 +1,103 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::{RBRangeMap, RBRangeSet};
+
+use rand::{Rng, SeedableRng};
+
+const POINTS: u32 = 100;
+
+// checks the map against a model holding the value of every point,
+// and that its ranges are disjoint and coalesced
+fn assert_matches(m: &RBRangeMap<u32, u8>, model: &[Option<u8>]) {
+    for (p, expected) in model.iter().enumerate() {
+        assert_eq!(m.get(&(p as u32)), expected.as_ref());
+    }
+    let ranges: Vec<_> = m.iter().collect();
+    assert_eq!(ranges.len(), m.len());
+    for (r, _) in ranges.iter() {
+        assert!(r.start < r.end);
+    }
+    for w in ranges.windows(2) {
+        assert!(w[0].0.end <= w[1].0.start);
+        assert!(w[0].0.end < w[1].0.start || w[0].1 != w[1].1);
+    }
+}
+
+#[test]
+fn test_matches_model() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(37);
+    let mut m = RBRangeMap::new();
+    let mut model = vec![None; POINTS as usize];
+    for _ in 0..3000 {
+        let start = rng.gen_range(0..POINTS);
+        let end = rng.gen_range(start..=POINTS);
+        if rng.gen_bool(0.6) {
+            let value = rng.gen_range(0..3);
+            m.insert(start..end, value);
+            model[start as usize..end as usize].fill(Some(value));
+        } else {
+            m.remove(start..end);
+            model[start as usize..end as usize].fill(None);
+        }
+        assert_matches(&m, &model);
+
+        let a = rng.gen_range(0..POINTS);
+        let b = rng.gen_range(a..=POINTS);
+        let covered: Vec<u32> = m.overlapping(a..b).flat_map(|(r, _)| r).collect();
+        let gaps: Vec<u32> = m.gaps(a..b).flatten().collect();
+        for p in a..b {
+            assert_eq!(covered.contains(&p), model[p as usize].is_some());
+            assert_eq!(gaps.contains(&p), model[p as usize].is_none());
+        }
+        assert!(gaps.iter().all(|p| (a..b).contains(p)));
+        assert!(m.gaps(a..b).all(|g| g.start < g.end));
+    }
+}
+
+#[test]
+#[allow(clippy::reversed_empty_ranges)]
+fn test_empty_queries() {
+    let mut m = RBRangeMap::new();
+    m.insert(0..10, 'a');
+    assert_eq!(m.overlapping(5..5).count(), 0);
+    assert_eq!(m.overlapping(7..3).count(), 0);
+    assert_eq!(m.overlapping(0..0).count(), 0);
+    assert_eq!(m.gaps(7..3).count(), 0);
+    assert_eq!(m.overlapping(9..10).collect::<Vec<_>>(), [(0..10, &'a')]);
+
+    let mut s = RBRangeSet::new();
+    s.insert(0..10);
+    assert_eq!(s.overlapping(5..5).count(), 0);
+    assert_eq!(s.overlapping(7..3).count(), 0);
+    assert_eq!(s.overlapping(3..7).next(), Some(0..10));
+}
+
+#[test]
+fn test_set() {
+    let mut s: RBRangeSet<u32> = vec![0..5, 10..15, 5..8].into_iter().collect();
+    assert_eq!(s.iter().collect::<Vec<_>>(), [0..8, 10..15]);
+    assert_eq!(s.get_range(&6), Some(0..8));
+    assert!(!s.contains(&8));
+    s.insert(8..10);
+    assert_eq!(s.len(), 1);
+    s.remove(3..12);
+    assert_eq!(s.iter().collect::<Vec<_>>(), [0..3, 12..15]);
+    assert_eq!(s.overlapping(2..13).collect::<Vec<_>>(), [0..3, 12..15]);
+    assert_eq!(s.gaps(0..20).collect::<Vec<_>>(), [3..12, 15..20]);
+    s.clear();
+    assert!(s.is_empty());
+}
+
+#[test]
+fn test_debug() {
+    let mut m = RBRangeMap::new();
+    m.insert(0..2, 'a');
+    m.insert(5..7, 'b');
+    assert_eq!(format!("{:?}", m), "{0..2: 'a', 5..7: 'b'}");
+    let mut s = RBRangeSet::new();
+    s.insert(0..2);
+    s.insert(2..4);
+    s.insert(5..7);
+    assert_eq!(format!("{:?}", s), "{0..4, 5..7}");
+}
@@ -0,0 +1,402 @@
+use crate::helpers::insert_left_down;
+use crate::node::{counting, Node, Refresh};
+use crate::RBSeq;
+use std::cell::Cell;
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::fmt::{Debug, Formatter, Result};
+use std::iter::{ExactSizeIterator, FromIterator, FusedIterator};
+use std::mem::take;
+use std::ops::{Bound, RangeBounds};
+
+// a value, the number of values in the subtree it is the
+// root of and how many of those are in its left subtree
+#[derive(Clone)]
+pub struct Positioned<T> {
+    value: T,
+    size: usize,
+    left: usize,
+}
+
+impl<T> Positioned<T> {
+    fn new(value: T) -> Positioned<T> {
+        Positioned {
+            value,
+            size: 1,
+            left: 0,
+        }
+    }
+}
+
+// keeps the sizes up to date as the tree changes shape
+struct Sizes;
+
+impl<T> Refresh<Positioned<T>> for Sizes {
+    fn refresh(
+        &self,
+        value: &mut Positioned<T>,
+        left: Option<&Positioned<T>>,
+        right: Option<&Positioned<T>>,
+    ) {
+        value.left = left.map_or(0, |l| l.size);
+        value.size = value.left + 1 + right.map_or(0, |r| r.size);
+    }
+}
+
+fn size<T>(node: &Node<Positioned<T>>) -> usize {
+    node.value().map_or(0, |p| p.size)
+}
+
+// every tree operation calls its comparison once on each node
+// on the way down, so the index being looked for can be made
+// relative to each subtree as the search descends into it
+fn locate<T>(idx: &Cell<usize>, node: &Positioned<T>) -> std::cmp::Ordering {
+    let i = idx.get();
+    if i < node.left {
+        Less
+    } else if i == node.left {
+        Equal
+    } else {
+        idx.set(i - node.left - 1);
+        Greater
+    }
+}
+
+impl<T> RBSeq<T> {
+    /// Creates and returns a new, empty RBSeq.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBSeq;
+    ///
+    /// let mut seq = RBSeq::new();
+    /// seq.push_back('b');
+    /// seq.insert(0, 'a');
+    /// assert_eq!(seq.get(1), Some(&'b'));
+    /// ```
+    pub fn new() -> RBSeq<T> {
+        RBSeq {
+            root: Node::default(),
+            #[cfg(feature = "stats")]
+            ops: Default::default(),
+        }
+    }
+
+    /// Removes every value from the sequence.
+    pub fn clear(&mut self) {
+        self.root = Node::default();
+    }
+
+    /// Returns the number of values in the sequence.
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    /// Returns true if the sequence contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_leaf()
+    }
+
+    /// Returns the value at position idx,
+    /// or None if idx is out of bounds.
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        let idx = Cell::new(idx);
+        counting!(self.ops);
+        self.root
+            .get(&(), &|_: &(), p: &Positioned<T>| locate(&idx, p))
+            .map(|p| &p.value)
+    }
+
+    /// Returns a mutable reference to the value
+    /// at position idx, or None if idx is out of bounds.
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        let idx = Cell::new(idx);
+        counting!(self.ops);
+        self.root
+            .get_mut(&(), &|_: &(), p: &Positioned<T>| locate(&idx, p))
+            .map(|p| &mut p.value)
+    }
+
+    /// Returns the first value in the sequence, if there is one.
+    pub fn front(&self) -> Option<&T> {
+        self.root.peek(false).map(|p| &p.value)
+    }
+
+    /// Returns the last value in the sequence, if there is one.
+    pub fn back(&self) -> Option<&T> {
+        self.root.peek(true).map(|p| &p.value)
+    }
+
+    /// Inserts value at position idx, moving every
+    /// value after it one position along.
+    /// # Panics:
+    /// Panics if idx is greater than the length of the sequence.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBSeq;
+    ///
+    /// let mut seq: RBSeq<_> = "hlo".chars().collect();
+    /// seq.insert(1, 'e');
+    /// seq.insert(2, 'l');
+    /// assert_eq!(seq.iter().collect::<String>(), "hello");
+    /// ```
+    pub fn insert(&mut self, idx: usize, value: T) {
+        let len = self.len();
+        assert!(
+            idx <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            idx,
+            len
+        );
+        let idx = Cell::new(idx);
+        // values at idx are moved right, so the new value
+        // always goes to the left of them
+        let cmp = |p: &Positioned<T>, _: &Positioned<T>| match locate(&idx, p) {
+            Greater => Less,
+            _ => Greater,
+        };
+        counting!(self.ops);
+        self.root.insert_with(Positioned::new(value), &cmp, &Sizes);
+    }
+
+    /// Appends value to the back of the sequence.
+    pub fn push_back(&mut self, value: T) {
+        self.insert(self.len(), value);
+    }
+
+    /// Prepends value to the front of the sequence.
+    pub fn push_front(&mut self, value: T) {
+        self.insert(0, value);
+    }
+
+    /// Removes and returns the value at position idx,
+    /// moving every value after it one position back.
+    /// # Panics:
+    /// Panics if idx is out of bounds.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBSeq;
+    ///
+    /// let mut seq: RBSeq<_> = (0..5).collect();
+    /// assert_eq!(seq.remove(1), 1);
+    /// assert_eq!(seq.iter().copied().collect::<Vec<_>>(), [0, 2, 3, 4]);
+    /// ```
+    pub fn remove(&mut self, idx: usize) -> T {
+        let len = self.len();
+        assert!(
+            idx < len,
+            "removal index (is {}) should be < len (is {})",
+            idx,
+            len
+        );
+        let idx = Cell::new(idx);
+        let cmp = |_: &(), p: &Positioned<T>| locate(&idx, p);
+        counting!(self.ops);
+        self.root.remove_with(&(), &cmp, &Sizes).unwrap().value
+    }
+
+    /// Removes and returns the first value, if there is one.
+    pub fn pop_front(&mut self) -> Option<T> {
+        counting!(self.ops);
+        self.root.pop_with(false, &Sizes).map(|p| p.value)
+    }
+
+    /// Removes and returns the last value, if there is one.
+    pub fn pop_back(&mut self) -> Option<T> {
+        counting!(self.ops);
+        self.root.pop_with(true, &Sizes).map(|p| p.value)
+    }
+
+    /// Splits the sequence in two at idx in O(log n)
+    /// time, returning the values from idx onwards.
+    /// # Panics:
+    /// Panics if idx is greater than the length of the sequence.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBSeq;
+    ///
+    /// let mut seq: RBSeq<_> = (0..5).collect();
+    /// let back = seq.split_off(3);
+    /// assert_eq!(seq.iter().copied().collect::<Vec<_>>(), [0, 1, 2]);
+    /// assert_eq!(back.iter().copied().collect::<Vec<_>>(), [3, 4]);
+    /// ```
+    pub fn split_off(&mut self, idx: usize) -> RBSeq<T> {
+        let len = self.len();
+        assert!(
+            idx <= len,
+            "split index (is {}) should be <= len (is {})",
+            idx,
+            len
+        );
+        let idx = Cell::new(idx);
+        // the value at idx goes to the back half
+        let back = |p: &Positioned<T>| match locate(&idx, p) {
+            Greater => Greater,
+            _ => Less,
+        };
+        let (front, _, back) = {
+            counting!(self.ops);
+            take(&mut self.root).split_with(&back, &Sizes)
+        };
+        self.root = front;
+        RBSeq {
+            root: back,
+            #[cfg(feature = "stats")]
+            ops: Default::default(),
+        }
+    }
+
+    /// Moves every value of other to the back of
+    /// this sequence in O(log n) time, leaving other empty.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBSeq;
+    ///
+    /// let mut seq: RBSeq<_> = (0..3).collect();
+    /// let mut other: RBSeq<_> = (3..6).collect();
+    /// seq.append(&mut other);
+    /// assert!(other.is_empty());
+    /// assert_eq!(seq.iter().copied().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+    /// ```
+    pub fn append(&mut self, other: &mut RBSeq<T>) {
+        counting!(self.ops);
+        if let Some(mid) = other.root.pop_with(false, &Sizes) {
+            let front = take(&mut self.root);
+            self.root = Node::join_with(front, mid, take(&mut other.root), &Sizes);
+        }
+    }
+
+    /// An iterator that visits every value in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.range(..)
+    }
+
+    /// An iterator that visits the values at the
+    /// positions in range in order, in O(log n + k) time.
+    /// # Panics:
+    /// Panics if range starts after it ends
+    /// or ends after the end of the sequence.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBSeq;
+    ///
+    /// let seq: RBSeq<_> = (0..10).collect();
+    /// assert_eq!(seq.range(3..6).copied().collect::<Vec<_>>(), [3, 4, 5]);
+    /// assert_eq!(seq.range(8..).len(), 2);
+    /// ```
+    pub fn range<R: RangeBounds<usize>>(&self, range: R) -> Iter<'_, T> {
+        let len = self.len();
+        let start = match range.start_bound() {
+            Bound::Included(s) => *s,
+            Bound::Excluded(s) => s + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(e) => e + 1,
+            Bound::Excluded(e) => *e,
+            Bound::Unbounded => len,
+        };
+        assert!(
+            start <= end,
+            "range start (is {}) should be <= end (is {})",
+            start,
+            end
+        );
+        assert!(
+            end <= len,
+            "range end (is {}) should be <= len (is {})",
+            end,
+            len
+        );
+        let mut ordered = Vec::new();
+        let mut cur = &self.root;
+        let mut idx = start;
+        // keeps the nodes at or after start whose
+        // left subtrees are still to be visited
+        while let Some(p) = cur.value() {
+            if idx <= p.left {
+                ordered.push(cur);
+                cur = cur.get_left();
+            } else {
+                idx -= p.left + 1;
+                cur = cur.get_right();
+            }
+        }
+        Iter {
+            remaining: end - start,
+            ordered,
+        }
+    }
+}
+
+impl<T> Default for RBSeq<T> {
+    fn default() -> Self {
+        RBSeq::new()
+    }
+}
+
+impl<T: Debug> Debug for RBSeq<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for RBSeq<T> {
+    fn eq(&self, other: &RBSeq<T>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for RBSeq<T> {}
+
+impl<T> FromIterator<T> for RBSeq<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values = iter.into_iter().map(Positioned::new).collect();
+        RBSeq {
+            root: Node::from_sorted_with(values, &Sizes),
+            #[cfg(feature = "stats")]
+            ops: Default::default(),
+        }
+    }
+}
+
+impl<T> Extend<T> for RBSeq<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut other: RBSeq<T> = iter.into_iter().collect();
+        self.append(&mut other);
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RBSeq<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+pub struct Iter<'a, T> {
+    remaining: usize,
+    ordered: Vec<&'a Node<Positioned<T>>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let next = self.ordered.pop()?;
+        self.remaining -= 1;
+        insert_left_down(next.get_right(), &mut self.ordered);
+        next.value().map(|p| &p.value)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {
+    fn len(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
@@ -0,0 +1,80 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::RBSeq;
+
+use rand::{Rng, SeedableRng};
+
+// checks the sequence is balanced and holds the same values as model
+fn assert_matches(seq: &RBSeq<u32>, model: &[u32]) {
+    assert!(seq.root.black_height().is_some());
+    assert!(!seq.root.is_red());
+    assert_eq!(seq.len(), model.len());
+    assert!(seq.iter().eq(model.iter()));
+}
+
+#[test]
+fn test_matches_vec() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(38);
+    let mut seq = RBSeq::new();
+    let mut model = Vec::new();
+    for i in 0..2000 {
+        match rng.gen_range(0..8) {
+            0..=3 => {
+                let idx = rng.gen_range(0..=model.len());
+                seq.insert(idx, i);
+                model.insert(idx, i);
+            }
+            4 | 5 if !model.is_empty() => {
+                let idx = rng.gen_range(0..model.len());
+                assert_eq!(seq.remove(idx), model.remove(idx));
+            }
+            6 => {
+                // split somewhere and join the halves back up,
+                // possibly after growing one of them
+                let idx = rng.gen_range(0..=model.len());
+                let mut back = seq.split_off(idx);
+                assert_matches(&seq, &model[..idx]);
+                assert_matches(&back, &model[idx..]);
+                let extra = rng.gen_range(0..40);
+                back.extend(i..i + extra);
+                model.extend(i..i + extra);
+                seq.append(&mut back);
+                assert!(back.is_empty());
+            }
+            _ => {
+                let a = rng.gen_range(0..=model.len());
+                let b = rng.gen_range(a..=model.len());
+                assert!(seq.range(a..b).eq(model[a..b].iter()));
+                assert_eq!(seq.range(a..b).len(), b - a);
+                assert_eq!(seq.get(a), model.get(a));
+            }
+        }
+        assert_matches(&seq, &model);
+    }
+}
+
+#[test]
+fn test_ends() {
+    let mut seq: RBSeq<u32> = RBSeq::new();
+    assert_eq!(seq.pop_front(), None);
+    seq.push_back(2);
+    seq.push_front(1);
+    seq.push_back(3);
+    assert_eq!(seq.front(), Some(&1));
+    assert_eq!(seq.back(), Some(&3));
+    *seq.get_mut(1).unwrap() = 20;
+    assert_eq!(seq.pop_front(), Some(1));
+    assert_eq!(seq.pop_back(), Some(3));
+    assert_matches(&seq, &[20]);
+    assert_eq!(seq.get(1), None);
+    seq.clear();
+    assert!(seq.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn test_remove_out_of_bounds() {
+    let mut seq: RBSeq<u32> = (0..3).collect();
+    seq.remove(3);
+}
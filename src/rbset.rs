@@ -1,6 +1,11 @@
-use crate::helpers::write_to_level;
+use crate::order::ByComparator;
+use crate::rbbase::{Difference, Drain, Intersection, IntoIter, Iter, SymmetricDifference, Union};
 use crate::{Comparator, RBSet, RBTreeWithCmp};
+use std::cmp::Ordering;
 use std::fmt::{Debug, Display, Formatter, Result};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
 
 impl<K, F: Comparator<K>> RBSet<K, F> {
     /// Creates and returns a new, empty RBSet
@@ -18,6 +23,21 @@ impl<K, F: Comparator<K>> RBSet<K, F> {
         }
     }
 
+    /// Creates an RBSet ordered by the given
+    /// Comparator from the values in iter.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let set = RBSet::from_iter_with(TestComparator{}, vec![3, 1, 2]);
+    /// assert_eq!(set.ordered(), [&1, &2, &3]);
+    /// ```
+    pub fn from_iter_with<I: IntoIterator<Item = K>>(f: F, iter: I) -> RBSet<K, F> {
+        RBSet {
+            map: RBTreeWithCmp::from_iter_with(f, iter),
+        }
+    }
+
     /// # Example:
     /// ```
     /// use rb_tree::{RBSet, TestComparator};
@@ -44,6 +64,72 @@ impl<K, F: Comparator<K>> RBSet<K, F> {
         self.map.take(key)
     }
 
+    /// Returns true if the set contains the
+    /// specified item, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut set = RBSet::new(TestComparator{});
+    /// set.insert(2);
+    /// assert!(!set.contains(&3));
+    /// assert!(set.contains(&2));
+    /// ```
+    pub fn contains(&self, key: &K) -> bool {
+        self.map.contains(key)
+    }
+
+    /// Returns the item specified if contained,
+    /// None otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut set = RBSet::new(TestComparator{});
+    /// set.insert(1);
+    /// assert_eq!(*set.get(&1).unwrap(), 1);
+    /// assert_eq!(set.get(&2), None);
+    /// ```
+    pub fn get(&self, key: &K) -> Option<&K> {
+        self.map.get(key)
+    }
+
+    /// Clears all entries from the RBSet
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut set = RBSet::new(TestComparator{});
+    /// set.insert(2);
+    /// set.insert(5);
+    /// set.clear();
+    /// assert_eq!(set.len(), 0);
+    /// assert!(!set.contains(&2));
+    /// ```
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Clears the set and returns an iterator
+    /// over all values that were contained, in
+    /// the order given by the comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut set = RBSet::new(TestComparator{});
+    /// set.insert(2);
+    /// set.insert(1);
+    /// let mut drain = set.drain();
+    /// assert_eq!(drain.next().unwrap(), 1);
+    /// assert_eq!(drain.next().unwrap(), 2);
+    /// assert!(drain.next().is_none());
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn drain(&mut self) -> Drain<K> {
+        self.map.drain()
+    }
+
     /// Returns the number of entries stored in this RBSet.
     /// # Example:
     /// ```
@@ -66,8 +152,21 @@ impl<K, F: Comparator<K>> RBSet<K, F> {
         self.len() == 0
     }
 
-    fn ordered(&self) -> Vec<&K> {
-        self.map.iter().collect()
+    /// Returns a vector presenting the contained
+    /// elements of the RBSet in the order by which
+    /// they are prioritised.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut set = RBSet::new(TestComparator{});
+    /// set.insert(3);
+    /// set.insert(1);
+    /// set.insert(2);
+    /// assert_eq!(set.ordered(), [&1, &2, &3]);
+    /// ```
+    pub fn ordered(&self) -> Vec<&K> {
+        self.map.ordered()
     }
 
     /// # Example:
@@ -84,6 +183,56 @@ impl<K, F: Comparator<K>> RBSet<K, F> {
         self.map.pop()
     }
 
+    /// Returns a reference to the first item the
+    /// comparator orders, or None if the set is empty.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut t = RBSet::new(TestComparator{});
+    /// assert_eq!(t.peek(), None);
+    /// t.insert(2);
+    /// t.insert(1);
+    /// t.insert(3);
+    /// assert_eq!(t.peek().unwrap(), &1);
+    /// ```
+    pub fn peek(&self) -> Option<&K> {
+        self.map.peek()
+    }
+
+    /// Removes the last item the comparator orders,
+    /// or returns None if the set is empty.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut t = RBSet::new(TestComparator{});
+    /// t.insert(2);
+    /// t.insert(1);
+    /// t.insert(3);
+    /// assert_eq!(t.pop_back().unwrap(), 3);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<K> {
+        self.map.pop_back()
+    }
+
+    /// Returns a reference to the last item the
+    /// comparator orders, or None if the set is empty.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut t = RBSet::new(TestComparator{});
+    /// assert_eq!(t.peek_back(), None);
+    /// t.insert(2);
+    /// t.insert(1);
+    /// t.insert(3);
+    /// assert_eq!(t.peek_back().unwrap(), &3);
+    /// ```
+    pub fn peek_back(&self) -> Option<&K> {
+        self.map.peek_back()
+    }
+
     /// # Example:
     /// ```
     /// use rb_tree::{RBSet, TestComparator};
@@ -99,45 +248,158 @@ impl<K, F: Comparator<K>> RBSet<K, F> {
     /// assert_eq!(pairs.next().unwrap(), &3);
     /// assert_eq!(pairs.next(), None);
     /// ```
-    pub fn iter(&self) -> Iter<K> {
-        Iter {
-            pos: 0,
-            ordered: self.ordered(),
-        }
+    pub fn iter(&self) -> Iter<'_, K> {
+        self.map.iter()
+    }
+
+    /// Retains only the values for which the given
+    /// method returns true.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let mut set = RBSet::from_iter_with(TestComparator{}, 0..10);
+    /// set.retain(|v| v % 3 == 0);
+    /// assert_eq!(set.ordered(), [&0, &3, &6, &9]);
+    /// ```
+    pub fn retain<P: FnMut(&K) -> bool>(&mut self, logic: P) {
+        self.map.retain(logic);
+    }
+
+    /// Returns an iterator over the values in this set
+    /// but not in `other`, in the order given by the comparator.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let s1 = RBSet::from_iter_with(TestComparator{}, 0..3);
+    /// let s2 = RBSet::from_iter_with(TestComparator{}, 2..5);
+    /// assert_eq!(s1.difference(&s2).collect::<Vec<_>>(), [&0, &1]);
+    /// ```
+    pub fn difference<'a>(
+        &'a self,
+        other: &'a RBSet<K, F>,
+    ) -> Difference<'a, K, ByComparator<K, F>> {
+        self.map.difference(&other.map)
+    }
+
+    /// Returns an iterator over the values in exactly
+    /// one of this set and `other`.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let s1 = RBSet::from_iter_with(TestComparator{}, 0..3);
+    /// let s2 = RBSet::from_iter_with(TestComparator{}, 2..5);
+    /// assert_eq!(
+    ///     s1.symmetric_difference(&s2).collect::<Vec<_>>(),
+    ///     [&0, &1, &3, &4]
+    /// );
+    /// ```
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a RBSet<K, F>,
+    ) -> SymmetricDifference<'a, K, ByComparator<K, F>> {
+        self.map.symmetric_difference(&other.map)
+    }
+
+    /// Returns an iterator over the values in both
+    /// this set and `other`.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let s1 = RBSet::from_iter_with(TestComparator{}, 0..3);
+    /// let s2 = RBSet::from_iter_with(TestComparator{}, 2..5);
+    /// assert_eq!(s1.intersection(&s2).collect::<Vec<_>>(), [&2]);
+    /// ```
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a RBSet<K, F>,
+    ) -> Intersection<'a, K, ByComparator<K, F>> {
+        self.map.intersection(&other.map)
+    }
+
+    /// Returns an iterator over the values in either
+    /// this set or `other`, each visited once.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let s1 = RBSet::from_iter_with(TestComparator{}, 0..3);
+    /// let s2 = RBSet::from_iter_with(TestComparator{}, 2..5);
+    /// assert_eq!(s1.union(&s2).collect::<Vec<_>>(), [&0, &1, &2, &3, &4]);
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a RBSet<K, F>) -> Union<'a, K, ByComparator<K, F>> {
+        self.map.union(&other.map)
+    }
+
+    /// Returns true if this set and `other` have
+    /// no values in common, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let s1 = RBSet::from_iter_with(TestComparator{}, 0..3);
+    /// let mut s2 = RBSet::from_iter_with(TestComparator{}, 2..5);
+    /// assert!(!s1.is_disjoint(&s2));
+    /// s2.pop();
+    /// assert!(s1.is_disjoint(&s2));
+    /// ```
+    pub fn is_disjoint(&self, other: &RBSet<K, F>) -> bool {
+        self.map.is_disjoint(&other.map)
     }
-}
 
-pub struct Iter<'a, K> {
-    pos: usize,
-    ordered: Vec<&'a K>,
+    /// Returns true if all values in this set
+    /// also appear in `other`, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let s1 = RBSet::from_iter_with(TestComparator{}, 0..3);
+    /// let s2 = RBSet::from_iter_with(TestComparator{}, 2..10);
+    /// let s3 = RBSet::from_iter_with(TestComparator{}, 3..7);
+    /// assert!(!s1.is_subset(&s2));
+    /// assert!(s3.is_subset(&s2));
+    /// ```
+    pub fn is_subset(&self, other: &RBSet<K, F>) -> bool {
+        self.map.is_subset(&other.map)
+    }
+
+    /// Returns true if all values in `other`
+    /// also appear in this set, false otherwise.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let s1 = RBSet::from_iter_with(TestComparator{}, 0..3);
+    /// let s2 = RBSet::from_iter_with(TestComparator{}, 2..10);
+    /// let s3 = RBSet::from_iter_with(TestComparator{}, 3..7);
+    /// assert!(!s2.is_superset(&s1));
+    /// assert!(s2.is_superset(&s3));
+    /// ```
+    pub fn is_superset(&self, other: &RBSet<K, F>) -> bool {
+        self.map.is_superset(&other.map)
+    }
 }
 
-impl<'a, K> Iterator for Iter<'a, K> {
-    type Item = &'a K;
+impl<K, F: Comparator<K> + Default> Default for RBSet<K, F> {
+    fn default() -> Self {
+        RBSet::new(F::default())
+    }
+}
 
-    fn next(&mut self) -> Option<&'a K> {
-        match self.ordered.get(self.pos) {
-            Some(v) => {
-                self.pos += 1;
-                Some(*v)
-            }
-            None => None,
-        }
+impl<K: Debug, F: Comparator<K>> RBSet<K, F> {
+    /// Renders the structure of the set as an indented
+    /// ASCII diagram, as RBTree's tree_view does.
+    pub fn tree_view(&self) -> String {
+        self.map.tree_view()
     }
 }
 
 impl<K: Debug, F: Comparator<K>> Debug for RBSet<K, F> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let mut levels = Vec::new();
-        write_to_level(&self.map.root, "".to_string(), 0, &mut levels);
-        let mut f_string = "".to_string();
-        for i in 0..levels.len() {
-            f_string += &levels[i];
-            if i != levels.len() - 1 {
-                f_string += "\n";
-            }
-        }
-        write!(f, "{}", f_string)
+        f.debug_set().entries(self.iter()).finish()
     }
 }
 
@@ -147,23 +409,174 @@ impl<K: Debug, F: Comparator<K>> Display for RBSet<K, F> {
     }
 }
 
-pub struct IntoIter<K, F: Comparator<K>> {
-    tree: RBSet<K, F>,
+/// Sets are equal when they contain equal values
+/// in the same order.
+/// # Example:
+/// ```
+/// use rb_tree::{RBSet, TestComparator};
+///
+/// let mut s1 = RBSet::new(TestComparator);
+/// let mut s2 = RBSet::new(TestComparator);
+/// s1.insert(1);
+/// s1.insert(2);
+/// s2.insert(2);
+/// s2.insert(1);
+/// assert_eq!(s1, s2);
+/// s2.insert(3);
+/// assert!(s1 < s2);
+/// ```
+impl<K: PartialEq, F: Comparator<K>> PartialEq for RBSet<K, F> {
+    fn eq(&self, other: &RBSet<K, F>) -> bool {
+        self.map == other.map
+    }
 }
 
-impl<K, F: Comparator<K>> Iterator for IntoIter<K, F> {
-    type Item = K;
+impl<K: Eq, F: Comparator<K>> Eq for RBSet<K, F> {}
+
+impl<K: PartialOrd, F: Comparator<K>> PartialOrd for RBSet<K, F> {
+    fn partial_cmp(&self, other: &RBSet<K, F>) -> Option<Ordering> {
+        self.map.partial_cmp(&other.map)
+    }
+}
+
+impl<K: Ord, F: Comparator<K>> Ord for RBSet<K, F> {
+    fn cmp(&self, other: &RBSet<K, F>) -> Ordering {
+        self.map.cmp(&other.map)
+    }
+}
 
-    fn next(&mut self) -> Option<K> {
-        self.tree.pop()
+impl<K: Hash, F: Comparator<K>> Hash for RBSet<K, F> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.map.hash(state);
     }
 }
 
 impl<K, F: Comparator<K>> IntoIterator for RBSet<K, F> {
     type Item = K;
-    type IntoIter = IntoIter<K, F>;
+    type IntoIter = IntoIter<K>;
+
+    fn into_iter(self) -> IntoIter<K> {
+        self.map.into_iter()
+    }
+}
+
+impl<K, F: Comparator<K> + Default> FromIterator<K> for RBSet<K, F> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        RBSet::from_iter_with(F::default(), iter)
+    }
+}
+
+impl<K, F: Comparator<K>> Extend<K> for RBSet<K, F> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        self.map.extend(iter);
+    }
+}
 
-    fn into_iter(self) -> IntoIter<K, F> {
-        IntoIter { tree: self }
+impl<'a, K: Copy + 'a, F: Comparator<K>> Extend<&'a K> for RBSet<K, F> {
+    fn extend<I: IntoIterator<Item = &'a K>>(&mut self, iter: I) {
+        self.map.extend(iter);
     }
 }
+
+// forwards each set operator to the one on the underlying tree,
+// so both operands must share the same Comparator type
+macro_rules! set_op {
+    ($(#[$doc:meta])* $op:ident, $fn:ident, $assign:ident, $assign_fn:ident) => {
+        $(#[$doc])*
+        impl<'a, 'b, K: Clone, F: Comparator<K> + Clone> $op<&'b RBSet<K, F>> for &'a RBSet<K, F> {
+            type Output = RBSet<K, F>;
+
+            fn $fn(self, rhs: &'b RBSet<K, F>) -> RBSet<K, F> {
+                RBSet {
+                    map: (&self.map).$fn(&rhs.map),
+                }
+            }
+        }
+
+        impl<K, F: Comparator<K>> $op<RBSet<K, F>> for RBSet<K, F> {
+            type Output = RBSet<K, F>;
+
+            fn $fn(self, rhs: RBSet<K, F>) -> RBSet<K, F> {
+                RBSet {
+                    map: self.map.$fn(rhs.map),
+                }
+            }
+        }
+
+        impl<K, F: Comparator<K>> $assign<RBSet<K, F>> for RBSet<K, F> {
+            fn $assign_fn(&mut self, rhs: RBSet<K, F>) {
+                self.map.$assign_fn(rhs.map);
+            }
+        }
+
+        impl<'b, K: Clone, F: Comparator<K>> $assign<&'b RBSet<K, F>> for RBSet<K, F> {
+            fn $assign_fn(&mut self, rhs: &'b RBSet<K, F>) {
+                self.map.$assign_fn(&rhs.map);
+            }
+        }
+    };
+}
+
+set_op!(
+    /// Returns the union of two sets as a new set.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let a = RBSet::from_iter_with(TestComparator, 0..3);
+    /// let b = RBSet::from_iter_with(TestComparator, 2..5);
+    /// assert_eq!((&a | &b).ordered(), [&0, &1, &2, &3, &4]);
+    /// ```
+    BitOr,
+    bitor,
+    BitOrAssign,
+    bitor_assign
+);
+
+set_op!(
+    /// Returns the intersection of two sets as a new set.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let a = RBSet::from_iter_with(TestComparator, 0..3);
+    /// let b = RBSet::from_iter_with(TestComparator, 2..5);
+    /// assert_eq!((&a & &b).ordered(), [&2]);
+    /// ```
+    BitAnd,
+    bitand,
+    BitAndAssign,
+    bitand_assign
+);
+
+set_op!(
+    /// Returns the difference of two sets as a new set.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let a = RBSet::from_iter_with(TestComparator, 0..3);
+    /// let b = RBSet::from_iter_with(TestComparator, 2..5);
+    /// assert_eq!((&a - &b).ordered(), [&0, &1]);
+    /// ```
+    Sub,
+    sub,
+    SubAssign,
+    sub_assign
+);
+
+set_op!(
+    /// Returns the symmetric difference of two sets as a new set.
+    /// # Example:
+    /// ```
+    /// use rb_tree::{RBSet, TestComparator};
+    ///
+    /// let a = RBSet::from_iter_with(TestComparator, 0..3);
+    /// let b = RBSet::from_iter_with(TestComparator, 2..5);
+    /// assert_eq!((&a ^ &b).ordered(), [&0, &1, &3, &4]);
+    /// ```
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign
+);
@@ -1,4 +1,5 @@
-use crate::{RBSet, TestComparator};
+use crate::{Comparator, RBSet, TestComparator};
+use std::cmp::Ordering;
 
 #[test]
 fn test_print() {
@@ -9,9 +10,10 @@ fn test_print() {
     t.insert(4);
     assert_eq!(format!("{}", t), "[1, 2, 3, 4]");
     assert_eq!(t.len(), 4);
+    assert_eq!(format!("{:?}", t), "{1, 2, 3, 4}");
     assert_eq!(
-        format!("{:?}", t),
-        "B:2\n2->B:1 2->B:3\n1->___ 1->___ 3->___ 3->R:4\n4->___ 4->___"
+        t.tree_view(),
+        ["B:2", "|-- B:1", "`-- B:3", "    |-- ___", "    `-- R:4"].join("\n")
     );
 }
 
@@ -28,3 +30,44 @@ fn test_iter() {
         assert_eq!(count, *i);
     }
 }
+
+#[derive(Clone)]
+struct Reverse;
+
+impl Comparator<i32> for Reverse {
+    fn cmp(&self) -> Box<dyn Fn(&i32, &i32) -> Ordering> {
+        Box::new(|a: &i32, b: &i32| b.cmp(a))
+    }
+}
+
+#[test]
+fn test_parity() {
+    let mut a = RBSet::from_iter_with(Reverse, 0..6);
+    let mut b = RBSet::new(Reverse);
+    b.extend(&[4, 5, 6, 7]);
+    assert!(a.contains(&3));
+    assert_eq!(a.peek(), Some(&5));
+    assert_eq!(a.peek_back(), Some(&0));
+    assert_eq!(a.pop_back(), Some(0));
+    assert_eq!(
+        a.union(&b).copied().collect::<Vec<_>>(),
+        [7, 6, 5, 4, 3, 2, 1]
+    );
+    assert_eq!(a.intersection(&b).copied().collect::<Vec<_>>(), [5, 4]);
+    assert_eq!(a.difference(&b).copied().collect::<Vec<_>>(), [3, 2, 1]);
+    assert_eq!(a.symmetric_difference(&b).count(), 5);
+    assert!(!a.is_disjoint(&b));
+
+    let both = a.clone() & b.clone();
+    assert!(both.is_subset(&a) && both.is_subset(&b));
+    assert!(b.is_superset(&both));
+    a -= &b;
+    assert!(a.is_disjoint(&b));
+
+    a.retain(|v| v % 2 == 1);
+    assert_eq!(a.drain().collect::<Vec<_>>(), [3, 1]);
+    assert!(a.is_empty());
+
+    let c: RBSet<i32, TestComparator> = vec![3, 1, 2].into_iter().collect();
+    assert_eq!(c.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+}
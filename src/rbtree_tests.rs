@@ -2,6 +2,8 @@ use crate::node::Colour::*;
 use crate::node::Node;
 use crate::{RBMap, RBTree};
 
+use rand::{Rng, SeedableRng};
+
 #[test]
 fn test_print() {
     let mut t = RBTree::new();
@@ -12,9 +14,33 @@ fn test_print() {
     println!("{:?}", t);
     assert_eq!(format!("{}", t), "[1.0, 1.2, 2.0, 3.0]");
     assert_eq!(t.len(), 4);
+    assert_eq!(format!("{:?}", t), "{1.0, 1.2, 2.0, 3.0}");
+    assert_eq!(
+        t.tree_view(),
+        [
+            "B:2.0",
+            "|-- B:1.0",
+            "|   |-- ___",
+            "|   `-- R:1.2",
+            "`-- B:3.0"
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn test_map_print() {
+    let mut m = RBMap::new();
+    assert_eq!(format!("{:?}", m), "{}");
+    assert_eq!(m.tree_view(), "___");
+    m.insert("b", 2);
+    m.insert("a", 1);
+    assert_eq!(format!("{}", m), "[(\"a\", 1), (\"b\", 2)]");
+    assert_eq!(format!("{:?}", m), "{\"a\": 1, \"b\": 2}");
+    assert_eq!(format!("{:#?}", m), "{\n    \"a\": 1,\n    \"b\": 2,\n}");
     assert_eq!(
-        format!("{:?}", t),
-        "B:2.0\n2.0->B:1.0 2.0->B:3.0\n1.0->___ 1.0->R:1.2 3.0->___ 3.0->___\n1.2->___ 1.2->___"
+        m.tree_view(),
+        ["B:\"b\" => 2", "|-- R:\"a\" => 1", "`-- ___"].join("\n")
     );
 }
 
@@ -38,6 +64,7 @@ fn test_ordered() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_contains_and_is_empty() {
     let mut t = RBTree::new();
     assert_eq!(t.is_empty(), true);
@@ -234,11 +261,19 @@ fn test_insertion_transfer_children() {
     // now insert the value that should cause the reform
     t.insert(1.125);
     assert_eq!(
-        format!("{:?}", t),
-        "B:1.5\n1.5->R:1.0 1.5->R:2.0\n\
-        1.0->___ 1.0->B:1.25 2.0->B:1.75 2.0->B:3.0\n\
-        1.25->R:1.125 1.25->___ 1.75->___ 1.75->___ 3.0->___ 3.0->___\n\
-        1.125->___ 1.125->___"
+        t.tree_view(),
+        [
+            "B:1.5",
+            "|-- R:1.0",
+            "|   |-- ___",
+            "|   `-- B:1.25",
+            "|       |-- R:1.125",
+            "|       `-- ___",
+            "`-- R:2.0",
+            "    |-- B:1.75",
+            "    `-- B:3.0"
+        ]
+        .join("\n")
     );
 }
 
@@ -248,39 +283,43 @@ fn test_complex_insertion() {
     t.insert(8);
     t.insert(5);
     t.insert(18);
-    assert_eq!(
-        format!("{:?}", t),
-        "B:8\n\
-        8->R:5 8->R:18\n\
-        5->___ 5->___ 18->___ 18->___"
-    );
+    assert_eq!(t.tree_view(), ["B:8", "|-- R:5", "`-- R:18"].join("\n"));
     t.insert(15);
     t.insert(17);
     assert_eq!(
-        format!("{:?}", t),
-        "B:8\n\
-        8->B:5 8->B:17\n\
-        5->___ 5->___ 17->R:15 17->R:18\n\
-        15->___ 15->___ 18->___ 18->___"
+        t.tree_view(),
+        ["B:8", "|-- B:5", "`-- B:17", "    |-- R:15", "    `-- R:18"].join("\n")
     );
     t.insert(25);
     t.insert(40);
     assert_eq!(
-        format!("{:?}", t),
-        "B:8\n\
-        8->B:5 8->R:17\n\
-        5->___ 5->___ 17->B:15 17->B:25\n\
-        15->___ 15->___ 25->R:18 25->R:40\n\
-        18->___ 18->___ 40->___ 40->___"
+        t.tree_view(),
+        [
+            "B:8",
+            "|-- B:5",
+            "`-- R:17",
+            "    |-- B:15",
+            "    `-- B:25",
+            "        |-- R:18",
+            "        `-- R:40"
+        ]
+        .join("\n")
     );
     t.insert(80);
     assert_eq!(
-        format!("{:?}", t),
-        "B:17\n\
-        17->R:8 17->R:25\n\
-        8->B:5 8->B:15 25->B:18 25->B:40\n\
-        5->___ 5->___ 15->___ 15->___ 18->___ 18->___ 40->___ 40->R:80\n\
-        80->___ 80->___"
+        t.tree_view(),
+        [
+            "B:17",
+            "|-- R:8",
+            "|   |-- B:5",
+            "|   `-- B:15",
+            "`-- R:25",
+            "    |-- B:18",
+            "    `-- B:40",
+            "        |-- ___",
+            "        `-- R:80"
+        ]
+        .join("\n")
     );
 
     let mut t = RBTree::new();
@@ -295,12 +334,21 @@ fn test_complex_insertion() {
     t.insert(16);
     t.insert(17);
     assert_eq!(
-        format!("{:?}", t),
-        "B:14\n\
-        14->R:7 14->R:16\n\
-        7->B:4 7->B:12 16->B:15 16->B:18\n\
-        4->R:3 4->R:5 12->___ 12->___ 15->___ 15->___ 18->R:17 18->___\n\
-        3->___ 3->___ 5->___ 5->___ 17->___ 17->___"
+        t.tree_view(),
+        [
+            "B:14",
+            "|-- R:7",
+            "|   |-- B:4",
+            "|   |   |-- R:3",
+            "|   |   `-- R:5",
+            "|   `-- B:12",
+            "`-- R:16",
+            "    |-- B:15",
+            "    `-- B:18",
+            "        |-- R:17",
+            "        `-- ___"
+        ]
+        .join("\n")
     )
 }
 
@@ -343,12 +391,9 @@ fn test_remove_root() {
     t.insert(1.0);
     t.insert(3.0);
     assert_eq!(t.take(&2.0).unwrap(), 2.0);
-    assert_eq!(
-        format!("{:?}", t),
-        "B:3.0\n3.0->R:1.0 3.0->___\n1.0->___ 1.0->___"
-    );
+    assert_eq!(t.tree_view(), ["B:3.0", "|-- R:1.0", "`-- ___"].join("\n"));
     assert_eq!(t.take(&3.0).unwrap(), 3.0);
-    assert_eq!(format!("{:?}", t), "B:1.0\n1.0->___ 1.0->___");
+    assert_eq!(t.tree_view(), ["B:1.0"].join("\n"));
 
     let mut t = RBTree::new();
     t.insert(2.0);
@@ -358,20 +403,28 @@ fn test_remove_root() {
     t.insert(4.0);
     assert_eq!(t.take(&2.0).unwrap(), 2.0);
     assert_eq!(
-        format!("{:?}", t),
-        "B:3.0\n\
-        3.0->B:1.0 3.0->B:4.0\n\
-        1.0->___ 1.0->R:1.5 4.0->___ 4.0->___\n\
-        1.5->___ 1.5->___"
+        t.tree_view(),
+        [
+            "B:3.0",
+            "|-- B:1.0",
+            "|   |-- ___",
+            "|   `-- R:1.5",
+            "`-- B:4.0"
+        ]
+        .join("\n")
     );
     t.insert(3.5);
     assert_eq!(t.take(&3.0).unwrap(), 3.0);
     assert_eq!(
-        format!("{:?}", t),
-        "B:3.5\n\
-        3.5->B:1.0 3.5->B:4.0\n\
-        1.0->___ 1.0->R:1.5 4.0->___ 4.0->___\n\
-        1.5->___ 1.5->___"
+        t.tree_view(),
+        [
+            "B:3.5",
+            "|-- B:1.0",
+            "|   |-- ___",
+            "|   `-- R:1.5",
+            "`-- B:4.0"
+        ]
+        .join("\n")
     );
 }
 
@@ -390,11 +443,15 @@ fn test_removal_no_double_black() {
     assert_eq!(t.take(&1.0).unwrap(), 1.0);
     println!("{:?}", t);
     assert_eq!(
-        format!("{:?}", t),
-        "B:2.0\n\
-        2.0->B:1.5 2.0->B:3.0\n\
-        1.5->___ 1.5->___ 3.0->R:2.5 3.0->___\n\
-        2.5->___ 2.5->___"
+        t.tree_view(),
+        [
+            "B:2.0",
+            "|-- B:1.5",
+            "`-- B:3.0",
+            "    |-- R:2.5",
+            "    `-- ___"
+        ]
+        .join("\n")
     );
 }
 
@@ -406,12 +463,7 @@ fn test_removal_simple_case() {
     t.insert(40);
     t.insert(10);
     assert_eq!(t.take(&10).unwrap(), 10);
-    assert_eq!(
-        format!("{:?}", t),
-        "B:30\n\
-        30->B:20 30->B:40\n\
-        20->___ 20->___ 40->___ 40->___"
-    );
+    assert_eq!(t.tree_view(), ["B:30", "|-- B:20", "`-- B:40"].join("\n"));
 }
 
 #[test]
@@ -429,12 +481,19 @@ fn test_black_leaf_removal() {
     t.remove(&92); // adding & removing causes colour change
     assert_eq!(t.take(&90).unwrap(), 90);
     assert_eq!(
-        format!("{:?}", t),
-        "B:65\n\
-        65->R:50 65->B:80\n\
-        50->B:10 50->B:60 80->R:70 80->___\n\
-        10->___ 10->___ 60->___ 60->R:62 70->___ 70->___\n\
-        62->___ 62->___"
+        t.tree_view(),
+        [
+            "B:65",
+            "|-- R:50",
+            "|   |-- B:10",
+            "|   `-- B:60",
+            "|       |-- ___",
+            "|       `-- R:62",
+            "`-- B:80",
+            "    |-- R:70",
+            "    `-- ___"
+        ]
+        .join("\n")
     );
 }
 
@@ -457,11 +516,15 @@ fn test_remove_accumulative_changes() {
     t.remove(&80);
     assert_eq!(t.take(&70).unwrap(), 70);
     assert_eq!(
-        format!("{:?}", t),
-        "B:50\n\
-        50->B:10 50->R:62\n\
-        10->___ 10->___ 62->B:60 62->B:65\n\
-        60->___ 60->___ 65->___ 65->___"
+        t.tree_view(),
+        [
+            "B:50",
+            "|-- B:10",
+            "`-- R:62",
+            "    |-- B:60",
+            "    `-- B:65"
+        ]
+        .join("\n")
     );
 }
 
@@ -477,11 +540,15 @@ fn test_removal_case2_inner() {
     assert_eq!(t.take(&20).unwrap(), 20);
     println!("{:?}", t);
     assert_eq!(
-        format!("{:?}", t),
-        "B:35\n\
-        35->B:30 35->B:40\n\
-        30->___ 30->___ 40->___ 40->R:50\n\
-        50->___ 50->___"
+        t.tree_view(),
+        [
+            "B:35",
+            "|-- B:30",
+            "`-- B:40",
+            "    |-- ___",
+            "    `-- R:50"
+        ]
+        .join("\n")
     );
 
     let mut t = RBTree::new();
@@ -493,11 +560,8 @@ fn test_removal_case2_inner() {
     t.insert(6);
     assert_eq!(t.take(&2).unwrap(), 2);
     assert_eq!(
-        format!("{:?}", t),
-        "B:8\n\
-        8->R:6 8->B:9\n\
-        6->B:5 6->B:7 9->___ 9->___\n\
-        5->___ 5->___ 7->___ 7->___"
+        t.tree_view(),
+        ["B:8", "|-- R:6", "|   |-- B:5", "|   `-- B:7", "`-- B:9"].join("\n")
     )
 }
 
@@ -511,12 +575,7 @@ fn test_removal_case2_outer() {
     println!("{:?}", t);
     assert_eq!(t.take(&20).unwrap(), 20);
     println!("{:?}", t);
-    assert_eq!(
-        format!("{:?}", t),
-        "B:40\n\
-        40->B:30 40->B:50\n\
-        30->___ 30->___ 50->___ 50->___"
-    );
+    assert_eq!(t.tree_view(), ["B:40", "|-- B:30", "`-- B:50"].join("\n"));
 
     let mut t = RBTree::new();
     t.insert(12);
@@ -534,33 +593,62 @@ fn test_removal_case2_outer() {
     t.insert(14);
     assert_eq!(t.take(&5).unwrap(), 5);
     assert_eq!(
-        format!("{:?}", t),
-        "B:10\n\
-        10->B:6 10->B:12\n\
-        6->B:3 6->B:7 12->B:11 12->R:15\n\
-        3->___ 3->R:4 7->___ 7->R:8 11->___ 11->___ 15->B:13 15->B:17\n\
-        4->___ 4->___ 8->___ 8->___ 13->___ 13->R:14 17->___ 17->___\n\
-        14->___ 14->___"
+        t.tree_view(),
+        [
+            "B:10",
+            "|-- B:6",
+            "|   |-- B:3",
+            "|   |   |-- ___",
+            "|   |   `-- R:4",
+            "|   `-- B:7",
+            "|       |-- ___",
+            "|       `-- R:8",
+            "`-- B:12",
+            "    |-- B:11",
+            "    `-- R:15",
+            "        |-- B:13",
+            "        |   |-- ___",
+            "        |   `-- R:14",
+            "        `-- B:17"
+        ]
+        .join("\n")
     );
     assert_eq!(t.take(&6).unwrap(), 6);
     assert_eq!(t.take(&7).unwrap(), 7);
     assert_eq!(
-        format!("{:?}", t),
-        "B:10\n\
-        10->B:4 10->B:12\n\
-        4->B:3 4->B:8 12->B:11 12->R:15\n\
-        3->___ 3->___ 8->___ 8->___ 11->___ 11->___ 15->B:13 15->B:17\n\
-        13->___ 13->R:14 17->___ 17->___\n\
-        14->___ 14->___"
+        t.tree_view(),
+        [
+            "B:10",
+            "|-- B:4",
+            "|   |-- B:3",
+            "|   `-- B:8",
+            "`-- B:12",
+            "    |-- B:11",
+            "    `-- R:15",
+            "        |-- B:13",
+            "        |   |-- ___",
+            "        |   `-- R:14",
+            "        `-- B:17"
+        ]
+        .join("\n")
     );
     assert_eq!(t.take(&4).unwrap(), 4);
     assert_eq!(
-        format!("{:?}", t),
-        "B:12\n\
-        12->B:10 12->B:15\n\
-        10->B:8 10->B:11 15->B:13 15->B:17\n\
-        8->R:3 8->___ 11->___ 11->___ 13->___ 13->R:14 17->___ 17->___\n\
-        3->___ 3->___ 14->___ 14->___"
+        t.tree_view(),
+        [
+            "B:12",
+            "|-- B:10",
+            "|   |-- B:8",
+            "|   |   |-- R:3",
+            "|   |   `-- ___",
+            "|   `-- B:11",
+            "`-- B:15",
+            "    |-- B:13",
+            "    |   |-- ___",
+            "    |   `-- R:14",
+            "    `-- B:17"
+        ]
+        .join("\n")
     );
 }
 
@@ -578,11 +666,8 @@ fn test_removal_case3_red_parent() {
     t.remove(&7);
     assert_eq!(t.take(&3).unwrap(), 3);
     assert_eq!(
-        format!("{:?}", t),
-        "B:2\n\
-        2->B:1 2->B:4\n\
-        1->___ 1->___ 4->___ 4->R:6\n\
-        6->___ 6->___"
+        t.tree_view(),
+        ["B:2", "|-- B:1", "`-- B:4", "    |-- ___", "    `-- R:6"].join("\n")
     )
 }
 
@@ -597,11 +682,17 @@ fn test_removeal_case4() {
     t.insert(6);
     assert_eq!(t.take(&1).unwrap(), 1);
     assert_eq!(
-        format!("{:?}", t),
-        "B:4\n\
-        4->B:2 4->B:5\n\
-        2->___ 2->R:3 5->___ 5->R:6\n\
-        3->___ 3->___ 6->___ 6->___"
+        t.tree_view(),
+        [
+            "B:4",
+            "|-- B:2",
+            "|   |-- ___",
+            "|   `-- R:3",
+            "`-- B:5",
+            "    |-- ___",
+            "    `-- R:6"
+        ]
+        .join("\n")
     );
 }
 
@@ -636,12 +727,17 @@ fn test_gets_correct_descendant() {
     t.insert(7.3);
     t.remove(&7.0);
     assert_eq!(
-        format!("{:?}", t),
-        "B:9.0\n\
-        9.0->R:7.3 9.0->B:10.0\n\
-        7.3->B:1.0 7.3->B:8.0 10.0->___ 10.0->___\n\
-        1.0->___ 1.0->R:6.0 8.0->___ 8.0->___\n\
-        6.0->___ 6.0->___"
+        t.tree_view(),
+        [
+            "B:9.0",
+            "|-- R:7.3",
+            "|   |-- B:1.0",
+            "|   |   |-- ___",
+            "|   |   `-- R:6.0",
+            "|   `-- B:8.0",
+            "`-- B:10.0"
+        ]
+        .join("\n")
     );
 
     t.remove(&8.0);
@@ -649,11 +745,15 @@ fn test_gets_correct_descendant() {
     t.insert(2.0);
     t.remove(&6.0);
     assert_eq!(
-        format!("{:?}", t),
-        "B:9.0\n\
-        9.0->B:2.0 9.0->B:10.0\n\
-        2.0->R:1.0 2.0->___ 10.0->___ 10.0->___\n\
-        1.0->___ 1.0->___"
+        t.tree_view(),
+        [
+            "B:9.0",
+            "|-- B:2.0",
+            "|   |-- R:1.0",
+            "|   `-- ___",
+            "`-- B:10.0"
+        ]
+        .join("\n")
     );
 }
 
@@ -1059,3 +1159,189 @@ fn test_extend() {
 
     assert_eq!(len, expected.len());
 }
+
+#[test]
+fn test_set_operators() {
+    use std::collections::BTreeSet;
+
+    let a_vals = [1, 3, 4, 8, 9, 12, 15, 20];
+    let b_vals = [0, 3, 5, 8, 12, 13, 21];
+    let a: RBTree<i32> = a_vals.iter().copied().collect();
+    let b: RBTree<i32> = b_vals.iter().copied().collect();
+    let sa: BTreeSet<i32> = a_vals.iter().copied().collect();
+    let sb: BTreeSet<i32> = b_vals.iter().copied().collect();
+
+    let check = |t: &RBTree<i32>, s: BTreeSet<i32>| {
+        assert!(t.root.black_height().is_some());
+        assert_eq!(t.len(), s.len());
+        assert!(t.iter().eq(s.iter()));
+    };
+    check(&(&a | &b), &sa | &sb);
+    check(&(&a & &b), &sa & &sb);
+    check(&(&a - &b), &sa - &sb);
+    check(&(&b - &a), &sb - &sa);
+    check(&(&a ^ &b), &sa ^ &sb);
+    check(&(a.clone() | b.clone()), &sa | &sb);
+    check(&(a.clone() & b.clone()), &sa & &sb);
+    check(&(a.clone() - b.clone()), &sa - &sb);
+    check(&(a.clone() ^ b.clone()), &sa ^ &sb);
+    check(&(&a | &RBTree::new()), sa.clone());
+    check(&(&RBTree::new() & &b), BTreeSet::new());
+
+    let mut t = a.clone();
+    t |= &b;
+    check(&t, &sa | &sb);
+    t -= b.clone();
+    check(&t, &sa - &sb);
+    t ^= &a;
+    check(&t, &sa & &sb);
+    t &= a;
+    check(&t, &sa & &sb);
+    t.insert(2);
+    assert!(t.root.black_height().is_some());
+}
+
+#[test]
+fn test_eq_ord_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashMap;
+    use std::hash::{Hash, Hasher};
+
+    fn hash<H: Hash>(h: &H) -> u64 {
+        let mut state = DefaultHasher::new();
+        h.hash(&mut state);
+        state.finish()
+    }
+
+    // same contents built into differently shaped trees
+    let up: RBTree<i32> = (0..20).collect();
+    let down: RBTree<i32> = (0..20).rev().collect();
+    let mut popped: RBTree<i32> = (0..30).collect();
+    while popped.len() > 20 {
+        popped.pop_back();
+    }
+    assert_eq!(up, down);
+    assert_eq!(up, popped);
+    assert_eq!(hash(&up), hash(&down));
+    assert_eq!(hash(&up), hash(&popped));
+
+    let short: RBTree<i32> = (0..19).collect();
+    let shifted: RBTree<i32> = (1..21).collect();
+    assert_ne!(up, short);
+    assert!(short < up);
+    assert!(up < shifted);
+    assert!(RBTree::<i32>::new() < short);
+
+    // sets of sets
+    let mut nested = RBTree::new();
+    nested.insert(shifted.clone());
+    nested.insert(up.clone());
+    nested.insert(down.clone());
+    assert_eq!(nested.len(), 2);
+    assert_eq!(nested.peek(), Some(&up));
+
+    let mut counts = HashMap::new();
+    for t in [&up, &down, &popped, &short] {
+        *counts.entry(t.clone()).or_insert(0) += 1;
+    }
+    assert_eq!(counts[&up], 3);
+    assert_eq!(counts[&short], 1);
+
+    let m1: RBMap<i32, char> = (0..5).map(|v| (v, 'a')).collect();
+    let m2: RBMap<i32, char> = (0..5).rev().map(|v| (v, 'a')).collect();
+    let mut m3 = m2.clone();
+    assert_eq!(m1, m2);
+    assert_eq!(hash(&m1), hash(&m2));
+    m3.insert(4, 'b');
+    assert_ne!(m1, m3);
+    assert!(m1 < m3);
+    m3.insert(3, 'Z');
+    assert!(m3 < m1);
+}
+
+#[test]
+fn test_pop_keeps_balance() {
+    for n in 1..200 {
+        let mut t: RBTree<i32> = (0..n).map(|i| (i * 37) % 211).collect();
+        while !t.is_empty() {
+            t.pop();
+            assert!(t.is_empty() || t.root.black_height().is_some());
+            t.pop_back();
+            assert!(t.is_empty() || t.root.black_height().is_some());
+        }
+    }
+}
+
+#[test]
+fn test_remove_keeps_balance() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(1);
+    let mut t: RBTree<u8> = RBTree::new();
+    for _ in 0..20000 {
+        let key = rng.gen_range(0..64u8);
+        if rng.gen_bool(0.5) {
+            t.insert(key);
+        } else {
+            t.remove(&key);
+        }
+        assert!(t.is_empty() || t.root.black_height().is_some());
+    }
+}
+
+#[test]
+fn test_pop_if() {
+    let mut t: RBTree<_> = (0..10).collect();
+    assert_eq!(t.pop_first_if(|v| *v > 0), None);
+    assert_eq!(t.pop_first_if(|v| *v == 0), Some(0));
+    assert_eq!(t.pop_last_if(|v| *v % 2 == 0), None);
+    assert_eq!(t.pop_last_if(|v| *v % 2 == 1), Some(9));
+    assert_eq!(t.len(), 8);
+    assert!(t.iter().eq((1..9).collect::<Vec<_>>().iter()));
+
+    let mut empty = RBTree::<i32>::new();
+    assert_eq!(empty.pop_first_if(|_| true), None);
+    assert_eq!(empty.pop_last_if(|_| true), None);
+
+    let mut m: RBMap<_, _> = (0..10).map(|k| (k, k * k)).collect();
+    while m.pop_last_if(|_, v| *v > 40).is_some() {}
+    assert_eq!(m.len(), 7);
+    assert_eq!(m.pop_first_if(|k, v| k == v), Some((0, 0)));
+    assert_eq!(m.pop_first_if(|k, v| k != v), None);
+    assert_eq!(m.pop_first_if(|k, v| k == v), Some((1, 1)));
+    assert_eq!(m.peek_pair(), Some((&2, &4)));
+}
+
+#[test]
+fn test_first_last_entry() {
+    let mut m = RBMap::<i32, i32>::new();
+    assert!(m.first_entry().is_none());
+    assert!(m.last_entry().is_none());
+
+    m.extend((0..20).map(|k| (k, 0)));
+    for i in 0..20 {
+        let mut e = m.first_entry().unwrap();
+        assert_eq!(*e.key(), i);
+        *e.get_mut() += 1;
+        assert_eq!(e.insert(5), 1);
+        assert_eq!(e.remove_entry(), (i, 5));
+        assert_eq!(m.len(), 19 - i as usize);
+    }
+    assert!(m.is_empty());
+
+    m.insert(1, 1);
+    m.insert(2, 1);
+    *m.last_entry().unwrap().into_mut() += 1;
+    assert_eq!(m.get(&2), Some(&2));
+    assert_eq!(m.last_entry().unwrap().remove(), 2);
+    assert_eq!(*m.last_entry().unwrap().get(), 1);
+
+    // one handle read, changed and removed in turn
+    let mut m: RBMap<_, _> = (0..100).map(|k| (k, k)).collect();
+    let mut e = m.last_entry().unwrap();
+    assert_eq!((*e.key(), *e.get()), (99, 99));
+    *e.get_mut() += 1;
+    assert_eq!(e.insert(7), 100);
+    assert_eq!(*e.get(), 7);
+    assert_eq!(e.remove_entry(), (99, 7));
+    assert_eq!(m.len(), 99);
+    assert!(m.map.root.black_height().is_some());
+}
@@ -1,9 +1,5 @@
-use crate::helpers::{insert_left_down, ordered_insertion, write_to_level};
-use crate::node::Colour::Black;
-use crate::node::Node;
-use crate::node::Node::Leaf;
+use crate::order::ByComparator;
 use crate::{Comparator, RBTreeWithCmp};
-use std::fmt::{Debug, Display, Formatter, Result};
 
 impl<T, F: Comparator<T>> RBTreeWithCmp<T, F> {
     /// Creates and returns a new RBTreeWithCmp.
@@ -17,261 +13,21 @@ impl<T, F: Comparator<T>> RBTreeWithCmp<T, F> {
     /// assert_eq!(t.take(&2).unwrap(), 2);
     /// ```
     pub fn new(f: F) -> RBTreeWithCmp<T, F> {
-        RBTreeWithCmp {
-            root: Leaf(Black),
-            contained: 0,
-            cmp: f,
-        }
-    }
-
-    pub fn clear(&mut self) {
-        self.root = Leaf(Black);
-        self.contained = 0;
-    }
-
-    pub fn get(&self, val: &T) -> Option<&T> {
-        self.root.get(val, &self.cmp.cmp())
-    }
-
-    #[cfg(feature = "map")]
-    pub(crate) fn get_mut(&mut self, val: &T) -> Option<&mut T> {
-        self.root.get_mut(val, &self.cmp.cmp())
-    }
-
-    /// Inserts a new element into the RBTreeWithCmp.
-    /// Returns true if this item was not already
-    /// in the tree, and false otherwise.
-    /// # Example:
-    /// ```
-    /// use rb_tree::{RBTreeWithCmp, TestComparator};
-    ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// assert_eq!(t.insert("Hello".to_string()), true);
-    /// assert_eq!(t.insert("Hello".to_string()), false);
-    /// ```
-    pub fn insert(&mut self, val: T) -> bool {
-        match self.root.insert(val, &self.cmp.cmp()) {
-            Some(_) => false,
-            None => {
-                self.contained += 1;
-                true
-            }
-        }
-    }
-
-    /// Returns a vector presenting the contained
-    /// elements of the RBTreeWithCmp in the order by which
-    /// they are prioritised (that is, in the in-order
-    /// tree traversal order).
-    /// # Example:
-    /// ```
-    /// use rb_tree::{RBTreeWithCmp, TestComparator};
-    ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// t.insert(3);
-    /// t.insert(1);
-    /// t.insert(2);
-    /// let order = t.ordered();
-    /// assert_eq!(*order[1], 2);
-    /// ```
-    pub fn ordered(&self) -> Vec<&T> {
-        let mut order = Vec::new();
-        ordered_insertion(&self.root, &mut order);
-        order
-    }
-
-    /// Returns the number of elements contained in the tree.
-    /// # Example:
-    /// ```
-    /// use rb_tree::{RBTreeWithCmp, TestComparator};
-    ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// t.insert(3);
-    /// t.insert(1);
-    /// t.insert(2);
-    /// assert_eq!(t.len(), 3);
-    /// t.remove(&2);
-    /// assert_eq!(t.len(), 2);
-    /// ```
-    pub fn len(&self) -> usize {
-        self.contained
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
-    }
-
-    /// Removes an item the tree. Returns the matching item
-    /// if it was contained in the tree, None otherwise.
-    /// # Example:
-    /// ```
-    /// use rb_tree::{RBTreeWithCmp, TestComparator};
-    ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// t.insert(4);
-    /// t.insert(2);
-    /// assert_eq!(t.take(&2).unwrap(), 2);
-    /// assert_eq!(t.len(), 1);
-    /// assert_eq!(t.take(&2), None);
-    /// ```
-    pub fn take(&mut self, val: &T) -> Option<T> {
-        match self.root.remove(val, &self.cmp.cmp()) {
-            Some(v) => {
-                self.contained -= 1;
-                Some(v)
-            }
-            None => None,
-        }
-    }
-
-    /// Removes an item the tree. Returns true
-    /// if it was contained in the tree, false otherwise.
-    /// # Example:
-    /// ```
-    /// use rb_tree::{RBTreeWithCmp, TestComparator};
-    ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// t.insert(4);
-    /// t.insert(2);
-    /// assert_eq!(t.remove(&2), true);
-    /// assert_eq!(t.len(), 1);
-    /// assert_eq!(t.remove(&2), false);
-    /// ```
-    pub fn remove(&mut self, val: &T) -> bool {
-        match self.root.remove(val, &self.cmp.cmp()) {
-            Some(_) => {
-                self.contained -= 1;
-                true
-            }
-            None => false,
-        }
-    }
-
-    /// Removes the item at the front of the priority
-    /// queue that the RBTree represents if any elements
-    /// are present, or None otherwise.
-    /// # Example:
-    /// ```
-    /// use rb_tree::{RBTreeWithCmp, TestComparator};
-    ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// t.insert(2);
-    /// t.insert(1);
-    /// t.insert(3);
-    /// assert_eq!(t.pop().unwrap(), 1);
-    /// ```
-    pub fn pop(&mut self) -> Option<T> {
-        match self.root.pop(false) {
-            Some(v) => {
-                self.contained -= 1;
-                Some(v)
-            }
-            None => None,
-        }
-    }
-
-    /// Returns an iterator over the elements
-    /// contained in this RBTreeWithCmp.
-    /// # Example:
-    /// ```
-    /// use rb_tree::{RBTreeWithCmp, TestComparator};
-    ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// t.insert(2);
-    /// t.insert(3);
-    /// t.insert(1);
-    /// t.insert(4);
-    /// let mut count = 0;
-    /// for i in t.iter() {
-    ///     count += 1;
-    ///     assert_eq!(count, *i);
-    /// }
-    /// ```
-    pub fn iter(&self) -> Iter<T> {
-        let mut ordered = Vec::new();
-        insert_left_down(&self.root, &mut ordered);
-        Iter {
-            remaining: self.len(),
-            ordered,
-        }
+        RBTreeWithCmp::with_order(ByComparator::new(f))
     }
 
+    /// Creates an RBTreeWithCmp ordered by the given
+    /// Comparator from the values in iter.
     /// # Example:
     /// ```
     /// use rb_tree::{RBTreeWithCmp, TestComparator};
     ///
-    /// let mut t = RBTreeWithCmp::new(TestComparator{});
-    /// assert_eq!(t.replace("Hello".to_string()), None);
-    /// assert_eq!(t.replace("Hello".to_string()), Some("Hello".to_string()));
+    /// let t = RBTreeWithCmp::from_iter_with(TestComparator, vec![3, 1, 2]);
+    /// assert_eq!(t.ordered(), [&1, &2, &3]);
     /// ```
-    pub fn replace(&mut self, val: T) -> Option<T> {
-        match self.root.insert(val, &self.cmp.cmp()) {
-            Some(v) => Some(v),
-            None => {
-                self.contained += 1;
-                None
-            }
-        }
-    }
-}
-
-pub struct Iter<'a, T> {
-    remaining: usize,
-    ordered: Vec<&'a Node<T>>,
-}
-
-impl<'a, T> Iterator for Iter<'a, T> {
-    type Item = &'a T;
-
-    fn next(&mut self) -> Option<&'a T> {
-        let next = match self.ordered.pop() {
-            Some(n) => n,
-            None => return None,
-        };
-        self.remaining -= 1;
-        insert_left_down(next.get_right(), &mut self.ordered);
-        Some(next.value().unwrap())
-    }
-}
-
-impl<T: Debug, F: Comparator<T>> Debug for RBTreeWithCmp<T, F> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        let mut levels = Vec::new();
-        write_to_level(&self.root, "".to_string(), 0, &mut levels);
-        let mut f_string = "".to_string();
-        for i in 0..levels.len() {
-            f_string += &levels[i];
-            if i != levels.len() - 1 {
-                f_string += "\n";
-            }
-        }
-        write!(f, "{}", f_string)
-    }
-}
-
-impl<T: Debug, F: Comparator<T>> Display for RBTreeWithCmp<T, F> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        write!(f, "{:?}", self.ordered())
-    }
-}
-
-pub struct IntoIter<T, F: Comparator<T>> {
-    tree: RBTreeWithCmp<T, F>,
-}
-
-impl<T, F: Comparator<T>> Iterator for IntoIter<T, F> {
-    type Item = T;
-
-    fn next(&mut self) -> Option<T> {
-        self.tree.pop()
-    }
-}
-
-impl<T, F: Comparator<T>> IntoIterator for RBTreeWithCmp<T, F> {
-    type Item = T;
-    type IntoIter = IntoIter<T, F>;
-
-    fn into_iter(self) -> IntoIter<T, F> {
-        IntoIter { tree: self }
+    pub fn from_iter_with<I: IntoIterator<Item = T>>(f: F, iter: I) -> RBTreeWithCmp<T, F> {
+        let mut tree = RBTreeWithCmp::new(f);
+        tree.extend(iter);
+        tree
     }
 }
@@ -1,4 +1,7 @@
-use crate::{RBTreeWithCmp, TestComparator};
+use crate::cmp::{ByKey, Natural, NullsFirst, NullsLast, Reverse, Then, TotalFloat};
+use crate::new_c_queue;
+use crate::order::descending;
+use crate::{Comparator, RBQueue, RBTreeWithCmp, TestComparator};
 
 #[test]
 fn test_print() {
@@ -9,9 +12,11 @@ fn test_print() {
     t.insert(4);
     assert_eq!(format!("{}", t), "[1, 2, 3, 4]");
     assert_eq!(t.len(), 4);
+    assert_eq!(format!("{:?}", t), "{1, 2, 3, 4}");
+    assert_eq!(format!("{:#?}", t), "{\n    1,\n    2,\n    3,\n    4,\n}");
     assert_eq!(
-        format!("{:?}", t),
-        "B:2\n2->B:1 2->B:3\n1->___ 1->___ 3->___ 3->R:4\n4->___ 4->___"
+        t.tree_view(),
+        ["B:2", "|-- B:1", "`-- B:3", "    |-- ___", "    `-- R:4"].join("\n")
     );
 }
 
@@ -34,3 +39,74 @@ fn test_iter() {
         assert_eq!(count, i);
     }
 }
+
+#[test]
+fn test_shared_core() {
+    let mut t = RBTreeWithCmp::new(TestComparator);
+    let mut o = RBTreeWithCmp::new(TestComparator);
+    t.extend(0..6);
+    o.extend(4..8);
+    assert!(t.contains(&3));
+    assert_eq!(t.peek_back(), Some(&5));
+    assert_eq!(t.pop_back(), Some(5));
+    assert_eq!(t.union(&o).count(), 8);
+    assert_eq!(t.intersection(&o).collect::<Vec<&i32>>(), vec![&4]);
+    assert_eq!(t.difference(&o).count(), 4);
+    assert!(!t.is_disjoint(&o));
+
+    t.retain(|v| v % 2 == 0);
+    assert_eq!(t.iter().len(), 3);
+    assert_eq!(t.drain().collect::<Vec<i32>>(), vec![0, 2, 4]);
+    assert!(t.is_empty());
+
+    let rev = |l: &i32, r: &i32| r.cmp(l);
+    let mut q = RBQueue::new(rev);
+    let mut p = RBQueue::new(rev);
+    q.extend(0..4);
+    p.extend(2..6);
+    assert_eq!(
+        q.symmetric_difference(&p).collect::<Vec<&i32>>(),
+        vec![&5, &4, &1, &0]
+    );
+    q.retain(|v| *v > 1);
+    assert!(q.is_subset(&p));
+    assert_eq!(q.into_iter().len(), 2);
+}
+
+#[test]
+fn test_c_queue_sign() {
+    // wide differences used to wrap when cast to i8
+    let q = new_c_queue!(|l: &i64, r| l - r; 256, 0, 200, -300);
+    assert_eq!(q.ordered(), [&-300, &0, &200, &256]);
+
+    // fractional differences used to truncate to Equal
+    let q = new_c_queue!(|l: &f64, r| r - l; 0.4, 0.0, 0.9);
+    assert_eq!(q.ordered(), [&0.9, &0.4, &0.0]);
+
+    let q = new_c_queue!(|l, r| descending(l, r); u64::MAX, 0, 1);
+    assert_eq!(q.ordered(), [&u64::MAX, &1, &0]);
+}
+
+#[test]
+#[should_panic(expected = "comparison returned NaN")]
+fn test_c_queue_nan() {
+    new_c_queue!(|_: &f64, _: &f64| f64::NAN; 1.0, 2.0);
+}
+
+#[test]
+fn test_combinators() {
+    let order = Then(
+        NullsLast(Reverse(Natural)),
+        ByKey(|v: &Option<i32>| v.is_some()),
+    );
+    let mut t = RBTreeWithCmp::new(order);
+    t.extend(vec![Some(1), None, Some(3), Some(2), None]);
+    assert_eq!(t.ordered(), [&Some(3), &Some(2), &Some(1), &None]);
+
+    let mut q = RBQueue::new(NullsFirst(TotalFloat).cmp());
+    q.extend(vec![Some(f64::NAN), Some(-0.0), None, Some(0.0)]);
+    assert_eq!(q.pop(), Some(None));
+    assert_eq!(q.pop().unwrap().unwrap().to_bits(), (-0.0f64).to_bits());
+    assert_eq!(q.pop(), Some(Some(0.0)));
+    assert!(q.pop().unwrap().unwrap().is_nan());
+}
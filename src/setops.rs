@@ -0,0 +1,284 @@
+use crate::node::{count, counting, Node};
+use crate::order::TreeOrder;
+use crate::RBBase;
+
+use std::borrow::Borrow;
+use std::cmp::Ordering::{Equal, Greater, Less};
+use std::iter::Peekable;
+use std::mem::take;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+use std::vec::IntoIter;
+
+// which values survive a merge of two sorted streams
+#[derive(Clone, Copy)]
+enum SetOp {
+    Union,
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl SetOp {
+    // whether values found only on the left, in both, or only on the right are kept
+    fn keeps(self) -> (bool, bool, bool) {
+        match self {
+            SetOp::Union => (true, true, true),
+            SetOp::Intersection => (false, true, false),
+            SetOp::Difference => (true, false, false),
+            SetOp::SymmetricDifference => (true, false, true),
+        }
+    }
+}
+
+// merges two streams that are sorted according to order, pushing the
+// values op keeps onto merged. when a value is in both streams the left
+// one is kept. conv_l and conv_r turn kept items into values, so borrowed
+// streams only clone the values that end up in the result. the left stream
+// is borrowed so that a caller can recover what is left of it should a
+// comparison panic
+fn merge<T, A, B, O, L, R>(
+    order: &O,
+    left: &mut Peekable<L>,
+    right: R,
+    op: SetOp,
+    conv_l: fn(A) -> T,
+    conv_r: fn(B) -> T,
+    merged: &mut Vec<T>,
+) where
+    A: Borrow<T>,
+    B: Borrow<T>,
+    O: TreeOrder<T>,
+    L: Iterator<Item = A>,
+    R: Iterator<Item = B>,
+{
+    let (only_l, both, only_r) = op.keeps();
+    let mut right = right.peekable();
+    loop {
+        let next = match (left.peek(), right.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Less,
+            (None, Some(_)) => Greater,
+            (Some(l), Some(r)) => {
+                count!(comparisons);
+                order.compare(l.borrow(), r.borrow())
+            }
+        };
+        match next {
+            Less => {
+                let l = left.next().unwrap();
+                if only_l {
+                    merged.push(conv_l(l));
+                }
+            }
+            Equal => {
+                let l = left.next().unwrap();
+                right.next();
+                if both {
+                    merged.push(conv_l(l));
+                }
+            }
+            Greater => {
+                let r = right.next().unwrap();
+                if only_r {
+                    merged.push(conv_r(r));
+                }
+            }
+        }
+
+        // nothing more can be kept once the relevant side runs out
+        if (left.peek().is_none() && !only_r) || (right.peek().is_none() && !only_l) {
+            break;
+        }
+    }
+}
+
+fn id<T>(v: T) -> T {
+    v
+}
+
+// rebuilds a tree from the values merged into it followed by those
+// of its own not yet merged. should a comparison panic mid merge the
+// tree is left holding the result of merging the values compared so
+// far, as retain does, rather than being left empty
+struct Merging<'a, T> {
+    root: &'a mut Node<T>,
+    contained: &'a mut usize,
+    merged: Vec<T>,
+    left: Peekable<IntoIter<T>>,
+}
+
+impl<'a, T> Merging<'a, T> {
+    fn new(root: &'a mut Node<T>, contained: &'a mut usize) -> Merging<'a, T> {
+        let mut left = Vec::with_capacity(*contained);
+        take(&mut *root).into_sorted(&mut left);
+        Merging {
+            root,
+            contained,
+            merged: Vec::with_capacity(left.len()),
+            left: left.into_iter().peekable(),
+        }
+    }
+
+    // values left unmerged after a merge that finished are not kept
+    fn finish(mut self) {
+        self.left = Vec::new().into_iter().peekable();
+    }
+}
+
+impl<'a, T> Drop for Merging<'a, T> {
+    fn drop(&mut self) {
+        let mut values = take(&mut self.merged);
+        values.extend(&mut self.left);
+        *self.contained = values.len();
+        *self.root = Node::from_sorted(values);
+    }
+}
+
+impl<T, O: TreeOrder<T>> RBBase<T, O> {
+    fn merge_owned(&mut self, mut other: RBBase<T, O>, op: SetOp) {
+        let right = other.take_sorted();
+        counting!(self.ops);
+        let mut guard = Merging::new(&mut self.root, &mut self.contained);
+        let (left, merged) = (&mut guard.left, &mut guard.merged);
+        merge(&self.order, left, right.into_iter(), op, id, id, merged);
+        guard.finish();
+    }
+}
+
+impl<T: Clone, O: TreeOrder<T>> RBBase<T, O> {
+    fn merge_borrowed(&mut self, other: &RBBase<T, O>, op: SetOp) {
+        counting!(self.ops);
+        let mut guard = Merging::new(&mut self.root, &mut self.contained);
+        let (left, merged) = (&mut guard.left, &mut guard.merged);
+        merge(&self.order, left, other.iter(), op, id, T::clone, merged);
+        guard.finish();
+    }
+}
+
+macro_rules! set_op {
+    ($(#[$doc:meta])* $op:ident, $fn:ident, $assign:ident, $assign_fn:ident, $set_op:expr) => {
+        $(#[$doc])*
+        impl<'a, 'b, T, O> $op<&'b RBBase<T, O>> for &'a RBBase<T, O>
+        where
+            T: Clone,
+            O: TreeOrder<T> + Clone,
+        {
+            type Output = RBBase<T, O>;
+
+            fn $fn(self, rhs: &'b RBBase<T, O>) -> RBBase<T, O> {
+                // the merge is the new tree's first work
+                #[cfg(feature = "stats")]
+                let ops = crate::stats::TreeOps::default();
+                let mut merged = Vec::new();
+                {
+                    counting!(ops);
+                    let (mut l, r) = (self.iter().peekable(), rhs.iter());
+                    merge(&self.order, &mut l, r, $set_op, T::clone, T::clone, &mut merged);
+                }
+                RBBase {
+                    contained: merged.len(),
+                    root: Node::from_sorted(merged),
+                    order: self.order.clone(),
+                    #[cfg(feature = "stats")]
+                    ops,
+                }
+            }
+        }
+
+        impl<T, O: TreeOrder<T>> $op<RBBase<T, O>> for RBBase<T, O> {
+            type Output = RBBase<T, O>;
+
+            fn $fn(mut self, rhs: RBBase<T, O>) -> RBBase<T, O> {
+                self.merge_owned(rhs, $set_op);
+                self
+            }
+        }
+
+        impl<T, O: TreeOrder<T>> $assign<RBBase<T, O>> for RBBase<T, O> {
+            fn $assign_fn(&mut self, rhs: RBBase<T, O>) {
+                self.merge_owned(rhs, $set_op);
+            }
+        }
+
+        impl<'b, T: Clone, O: TreeOrder<T>> $assign<&'b RBBase<T, O>> for RBBase<T, O> {
+            fn $assign_fn(&mut self, rhs: &'b RBBase<T, O>) {
+                self.merge_borrowed(rhs, $set_op);
+            }
+        }
+    };
+}
+
+set_op!(
+    /// Returns the union of two trees as a new tree,
+    /// i.e. the values that appear in either of them.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let a: RBTree<i32> = (0..3).collect();
+    /// let b: RBTree<i32> = (2..5).collect();
+    /// let mut c = &a | &b;
+    /// assert_eq!(c.ordered(), [&0, &1, &2, &3, &4]);
+    /// c |= a;
+    /// assert_eq!(c.len(), 5);
+    /// ```
+    BitOr,
+    bitor,
+    BitOrAssign,
+    bitor_assign,
+    SetOp::Union
+);
+
+set_op!(
+    /// Returns the intersection of two trees as a new tree,
+    /// i.e. the values that appear in both of them.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let a: RBTree<i32> = (0..3).collect();
+    /// let b: RBTree<i32> = (2..5).collect();
+    /// assert_eq!((&a & &b).ordered(), [&2]);
+    /// ```
+    BitAnd,
+    bitand,
+    BitAndAssign,
+    bitand_assign,
+    SetOp::Intersection
+);
+
+set_op!(
+    /// Returns the difference of two trees as a new tree,
+    /// i.e. the values of the left tree that are not in the right.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let a: RBTree<i32> = (0..3).collect();
+    /// let b: RBTree<i32> = (2..5).collect();
+    /// assert_eq!((&a - &b).ordered(), [&0, &1]);
+    /// ```
+    Sub,
+    sub,
+    SubAssign,
+    sub_assign,
+    SetOp::Difference
+);
+
+set_op!(
+    /// Returns the symmetric difference of two trees as a new tree,
+    /// i.e. the values that appear in exactly one of them.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let a: RBTree<i32> = (0..3).collect();
+    /// let b: RBTree<i32> = (2..5).collect();
+    /// assert_eq!((&a ^ &b).ordered(), [&0, &1, &3, &4]);
+    /// ```
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign,
+    SetOp::SymmetricDifference
+);
@@ -0,0 +1,357 @@
+use crate::node::Colour::{self, Black, Red};
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+use std::sync::Arc;
+
+// a red black tree whose nodes are shared between the
+// versions of a map. changing a version copies the nodes
+// on the way down to the change that other versions still
+// hold, and changes the ones it holds alone in place
+pub(crate) type Link<K, V> = Option<Arc<SharedNode<K, V>>>;
+
+#[derive(Clone)]
+pub(crate) struct SharedNode<K, V> {
+    colour: Colour,
+    pub(crate) key: K,
+    pub(crate) val: V,
+    pub(crate) left: Link<K, V>,
+    pub(crate) right: Link<K, V>,
+}
+
+fn compare<K: PartialOrd>(l: &K, r: &K) -> Ordering {
+    l.partial_cmp(r).unwrap()
+}
+
+fn is_red<K, V>(link: &Link<K, V>) -> bool {
+    matches!(link, Some(n) if n.colour == Red)
+}
+
+// the node behind link, copied first if another version holds it
+fn owned<K: Clone, V: Clone>(link: &mut Link<K, V>) -> &mut SharedNode<K, V> {
+    Arc::make_mut(link.as_mut().unwrap())
+}
+
+fn into_owned<K: Clone, V: Clone>(node: Arc<SharedNode<K, V>>) -> SharedNode<K, V> {
+    Arc::try_unwrap(node).unwrap_or_else(|n| (*n).clone())
+}
+
+fn set_colour<K: Clone, V: Clone>(link: &mut Link<K, V>, colour: Colour) {
+    if matches!(link, Some(n) if n.colour != colour) {
+        owned(link).colour = colour;
+    }
+}
+
+// lifts the right child of link above it
+fn rotate_left<K: Clone, V: Clone>(link: &mut Link<K, V>) {
+    let mut node = link.take().unwrap();
+    let mut right = Arc::make_mut(&mut node).right.take().unwrap();
+    Arc::make_mut(&mut node).right = Arc::make_mut(&mut right).left.take();
+    Arc::make_mut(&mut right).left = Some(node);
+    *link = Some(right);
+}
+
+// lifts the left child of link above it
+fn rotate_right<K: Clone, V: Clone>(link: &mut Link<K, V>) {
+    let mut node = link.take().unwrap();
+    let mut left = Arc::make_mut(&mut node).left.take().unwrap();
+    Arc::make_mut(&mut node).left = Arc::make_mut(&mut left).right.take();
+    Arc::make_mut(&mut left).right = Some(node);
+    *link = Some(left);
+}
+
+pub(crate) fn get<'a, K: PartialOrd, V>(
+    mut link: &'a Link<K, V>,
+    key: &K,
+) -> Option<&'a SharedNode<K, V>> {
+    while let Some(node) = link {
+        link = match compare(key, &node.key) {
+            Less => &node.left,
+            Greater => &node.right,
+            Equal => return Some(node),
+        };
+    }
+    None
+}
+
+pub(crate) fn get_mut<'a, K: PartialOrd + Clone, V: Clone>(
+    link: &'a mut Link<K, V>,
+    key: &K,
+) -> Option<&'a mut V> {
+    // find it first, so that a miss copies nothing
+    get(link, key)?;
+    let mut node = owned(link);
+    loop {
+        node = match compare(key, &node.key) {
+            Less => owned(&mut node.left),
+            Greater => owned(&mut node.right),
+            Equal => return Some(&mut node.val),
+        };
+    }
+}
+
+// inserts the pair below a black root, returning
+// the value it replaced
+pub(crate) fn insert<K: PartialOrd + Clone, V: Clone>(
+    root: &mut Link<K, V>,
+    key: K,
+    val: V,
+) -> Option<V> {
+    let old = insert_below(root, key, val);
+    set_colour(root, Black);
+    old
+}
+
+fn insert_below<K: PartialOrd + Clone, V: Clone>(
+    link: &mut Link<K, V>,
+    key: K,
+    val: V,
+) -> Option<V> {
+    let node = match link {
+        Some(_) => owned(link),
+        None => {
+            *link = Some(Arc::new(SharedNode {
+                colour: Red,
+                key,
+                val,
+                left: None,
+                right: None,
+            }));
+            return None;
+        }
+    };
+    let old = match compare(&key, &node.key) {
+        Less => insert_below(&mut node.left, key, val),
+        Greater => insert_below(&mut node.right, key, val),
+        Equal => return Some(std::mem::replace(&mut node.val, val)),
+    };
+    if old.is_none() && node.colour == Black {
+        balance(link);
+    }
+    old
+}
+
+// resolves a red child of link's black node that has a red
+// child of its own, leaving a red node with two black children
+fn balance<K: Clone, V: Clone>(link: &mut Link<K, V>) {
+    let node = link.as_ref().unwrap();
+    if let Some(left) = node.left.as_ref().filter(|l| l.colour == Red) {
+        if is_red(&left.right) {
+            rotate_left(&mut owned(link).left);
+        }
+        if is_red(&link.as_ref().unwrap().left.as_ref().unwrap().left) {
+            rotate_right(link);
+            return split_red(link);
+        }
+    }
+    let node = link.as_ref().unwrap();
+    if let Some(right) = node.right.as_ref().filter(|r| r.colour == Red) {
+        if is_red(&right.left) {
+            rotate_right(&mut owned(link).right);
+        }
+        if is_red(&link.as_ref().unwrap().right.as_ref().unwrap().right) {
+            rotate_left(link);
+            split_red(link);
+        }
+    }
+}
+
+fn split_red<K: Clone, V: Clone>(link: &mut Link<K, V>) {
+    let node = owned(link);
+    node.colour = Red;
+    set_colour(&mut node.left, Black);
+    set_colour(&mut node.right, Black);
+}
+
+// removes the entry for key below a black root
+pub(crate) fn remove<K: PartialOrd + Clone, V: Clone>(
+    root: &mut Link<K, V>,
+    key: &K,
+) -> Option<(K, V)> {
+    // find it first, so that a miss copies nothing
+    get(root, key)?;
+    let (removed, _) = remove_below(root, key);
+    set_colour(root, Black);
+    removed
+}
+
+// returns the removed entry, and whether the black
+// height below link has gone down by one
+fn remove_below<K: PartialOrd + Clone, V: Clone>(
+    link: &mut Link<K, V>,
+    key: &K,
+) -> (Option<(K, V)>, bool) {
+    let node = owned(link);
+    match compare(key, &node.key) {
+        Less => {
+            let (removed, shorter) = remove_below(&mut node.left, key);
+            (removed, shorter && fix_left(link))
+        }
+        Greater => {
+            let (removed, shorter) = remove_below(&mut node.right, key);
+            (removed, shorter && fix_right(link))
+        }
+        Equal if node.left.is_some() && node.right.is_some() => {
+            let ((k, v), shorter) = remove_first(&mut node.right);
+            let k = std::mem::replace(&mut node.key, k);
+            let v = std::mem::replace(&mut node.val, v);
+            (Some((k, v)), shorter && fix_right(link))
+        }
+        Equal => {
+            let (entry, shorter) = unlink(link);
+            (Some(entry), shorter)
+        }
+    }
+}
+
+fn remove_first<K: Clone, V: Clone>(link: &mut Link<K, V>) -> ((K, V), bool) {
+    let node = owned(link);
+    if node.left.is_none() {
+        return unlink(link);
+    }
+    let (entry, shorter) = remove_first(&mut node.left);
+    (entry, shorter && fix_left(link))
+}
+
+// removes link's node, which has at most one child. that
+// child can only be red, and takes the node's place
+fn unlink<K: Clone, V: Clone>(link: &mut Link<K, V>) -> ((K, V), bool) {
+    let node = into_owned(link.take().unwrap());
+    *link = node.left.or(node.right);
+    let shorter = node.colour == Black && !is_red(link);
+    set_colour(link, Black);
+    ((node.key, node.val), shorter)
+}
+
+// restores the black height of link's node after its left
+// subtree lost one, returning whether the node's did too
+fn fix_left<K: Clone, V: Clone>(link: &mut Link<K, V>) -> bool {
+    let node = owned(link);
+    if is_red(&node.left) {
+        set_colour(&mut node.left, Black);
+        return false;
+    }
+    if is_red(&node.right) {
+        // move the red sibling up, so that the new
+        // sibling below it is black
+        rotate_left(link);
+        let top = owned(link);
+        top.colour = Black;
+        owned(&mut top.left).colour = Red;
+        return fix_left(&mut top.left);
+    }
+    let sibling = node.right.as_ref().unwrap();
+    if !is_red(&sibling.left) && !is_red(&sibling.right) {
+        owned(&mut node.right).colour = Red;
+        if node.colour == Red {
+            node.colour = Black;
+            return false;
+        }
+        return true;
+    }
+    if !is_red(&sibling.right) {
+        rotate_right(&mut node.right);
+        let sibling = owned(&mut node.right);
+        sibling.colour = Black;
+        owned(&mut sibling.right).colour = Red;
+    }
+    let colour = node.colour;
+    rotate_left(link);
+    let top = owned(link);
+    top.colour = colour;
+    owned(&mut top.left).colour = Black;
+    owned(&mut top.right).colour = Black;
+    false
+}
+
+// the mirror image of fix_left
+fn fix_right<K: Clone, V: Clone>(link: &mut Link<K, V>) -> bool {
+    let node = owned(link);
+    if is_red(&node.right) {
+        set_colour(&mut node.right, Black);
+        return false;
+    }
+    if is_red(&node.left) {
+        rotate_right(link);
+        let top = owned(link);
+        top.colour = Black;
+        owned(&mut top.right).colour = Red;
+        return fix_right(&mut top.right);
+    }
+    let sibling = node.left.as_ref().unwrap();
+    if !is_red(&sibling.left) && !is_red(&sibling.right) {
+        owned(&mut node.left).colour = Red;
+        if node.colour == Red {
+            node.colour = Black;
+            return false;
+        }
+        return true;
+    }
+    if !is_red(&sibling.left) {
+        rotate_left(&mut node.left);
+        let sibling = owned(&mut node.left);
+        sibling.colour = Black;
+        owned(&mut sibling.left).colour = Red;
+    }
+    let colour = node.colour;
+    rotate_right(link);
+    let top = owned(link);
+    top.colour = colour;
+    owned(&mut top.left).colour = Black;
+    owned(&mut top.right).colour = Black;
+    false
+}
+
+// builds a tree from entries in ascending order of key. every
+// path holds the same number of nodes above the deepest level,
+// so colouring that level red balances it
+pub(crate) fn from_sorted<K, V>(entries: Vec<(K, V)>) -> Link<K, V> {
+    let len = entries.len();
+    let mut depth = 0;
+    while (2 << depth) - 1 < len {
+        depth += 1;
+    }
+    let mut entries = entries.into_iter();
+    let mut root = build(&mut entries, len, 0, depth);
+    if let Some(root) = root.as_mut() {
+        Arc::get_mut(root).unwrap().colour = Black;
+    }
+    root
+}
+
+fn build<K, V, I: Iterator<Item = (K, V)>>(
+    entries: &mut I,
+    len: usize,
+    level: usize,
+    red: usize,
+) -> Link<K, V> {
+    if len == 0 {
+        return None;
+    }
+    let left = build(entries, len / 2, level + 1, red);
+    let (key, val) = entries.next().unwrap();
+    let right = build(entries, len - len / 2 - 1, level + 1, red);
+    Some(Arc::new(SharedNode {
+        colour: if level == red { Red } else { Black },
+        key,
+        val,
+        left,
+        right,
+    }))
+}
+
+// the number of black nodes on every path down from link,
+// or None if they differ or a red node has a red child
+#[cfg(test)]
+pub(crate) fn black_height<K, V>(link: &Link<K, V>) -> Option<usize> {
+    let node = match link {
+        Some(node) => node,
+        None => return Some(0),
+    };
+    if node.colour == Red && (is_red(&node.left) || is_red(&node.right)) {
+        return None;
+    }
+    let left = black_height(&node.left)?;
+    if left != black_height(&node.right)? {
+        return None;
+    }
+    Some(left + (node.colour == Black) as usize)
+}
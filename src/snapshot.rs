@@ -0,0 +1,654 @@
+//! A compact, versioned binary format for saving trees and
+//! maps to disk and loading them back.
+//!
+//! A snapshot is laid out as:
+//!
+//! | bytes | contents                                          |
+//! |-------|---------------------------------------------------|
+//! | 4     | the magic bytes `RBTS`                            |
+//! | 2     | the format version, little-endian                 |
+//! | 1     | the layout: 0 for values, 1 for key-value pairs   |
+//! | 1     | reserved, always 0                                |
+//! | 8     | the number of elements, little-endian             |
+//! | ...   | each element in order, as written by its Codec(s) |
+//! | 4     | the CRC-32 of everything above, little-endian     |
+//!
+//! Elements are written in the order of the tree, so loading
+//! builds the tree directly from them in O(n) time after
+//! checking that they are still in order. How each key and
+//! value is encoded is up to a [`Codec`]; [`DefaultCodec`]
+//! handles the primitive types, strings and byte vectors.
+//! # Example:
+//! ```
+//! use rb_tree::RBMap;
+//!
+//! let mut map = RBMap::new();
+//! map.insert(1u32, "one".to_string());
+//! map.insert(2u32, "two".to_string());
+//!
+//! let mut file = Vec::new();
+//! map.write_snapshot(&mut file).unwrap();
+//! let loaded: RBMap<u32, String> = RBMap::read_snapshot(file.as_slice()).unwrap();
+//! assert_eq!(loaded, map);
+//! ```
+
+use crate::node::{count, Node};
+use crate::order::TreeOrder;
+use crate::RBBase;
+#[cfg(feature = "map")]
+use crate::{mapper::Mapper, RBMap};
+#[cfg(feature = "queue")]
+use crate::{order::ByFn, RBQueue};
+#[cfg(feature = "set")]
+use crate::{order::PartialOrdering, RBTree};
+use std::cmp::Ordering::Less;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"RBTS";
+
+/// The version of the format written by this crate.
+pub const VERSION: u16 = 1;
+
+const VALUES: u8 = 0;
+#[cfg(feature = "map")]
+const PAIRS: u8 = 1;
+
+/// The reasons a snapshot can fail to be written or read.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// Reading or writing failed.
+    Io(io::Error),
+    /// The input does not start with the magic bytes,
+    /// so is not a snapshot.
+    NotASnapshot,
+    /// The snapshot was written by a newer,
+    /// unsupported version of the format.
+    UnsupportedVersion(u16),
+    /// The snapshot holds values where key-value pairs
+    /// were expected, or the other way around.
+    WrongLayout,
+    /// The input ended before the snapshot did.
+    Truncated,
+    /// The checksum does not match the contents.
+    ChecksumMismatch { expected: u32, found: u32 },
+    /// The elements are not in the order of the
+    /// tree being loaded, or are duplicated.
+    Unordered,
+    /// A Codec could not decode an element.
+    Corrupt(String),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(e) => write!(f, "snapshot i/o failed: {}", e),
+            SnapshotError::NotASnapshot => write!(f, "input is not a snapshot"),
+            SnapshotError::UnsupportedVersion(v) => {
+                write!(
+                    f,
+                    "snapshot version {} is not supported (newest is {})",
+                    v, VERSION
+                )
+            }
+            SnapshotError::WrongLayout => {
+                write!(f, "snapshot holds a different kind of collection")
+            }
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "snapshot checksum is {:08x} but its contents give {:08x}",
+                expected, found
+            ),
+            SnapshotError::Unordered => write!(f, "snapshot elements are out of order"),
+            SnapshotError::Corrupt(why) => write!(f, "snapshot is corrupt: {}", why),
+        }
+    }
+}
+
+impl Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SnapshotError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            SnapshotError::Truncated
+        } else {
+            SnapshotError::Io(e)
+        }
+    }
+}
+
+/// Encodes and decodes the elements of a snapshot.
+pub trait Codec<T> {
+    /// Writes value to out.
+    fn encode<W: Write>(&self, value: &T, out: &mut W) -> io::Result<()>;
+
+    /// Reads back a value written by encode.
+    fn decode<R: Read>(&self, input: &mut R) -> Result<T, SnapshotError>;
+}
+
+/// Encodes numbers as fixed-width little-endian bytes, bools
+/// and chars as a byte and four bytes respectively, and
+/// strings and byte vectors as their length (a LEB128
+/// varint) followed by their bytes.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DefaultCodec;
+
+macro_rules! le_codec {
+    ($($t:ty),*) => {$(
+        impl Codec<$t> for DefaultCodec {
+            fn encode<W: Write>(&self, value: &$t, out: &mut W) -> io::Result<()> {
+                out.write_all(&value.to_le_bytes())
+            }
+
+            fn decode<R: Read>(&self, input: &mut R) -> Result<$t, SnapshotError> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                input.read_exact(&mut bytes)?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+
+le_codec!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+// usize and isize are always written as 64 bits, so snapshots
+// can be moved between platforms
+impl Codec<usize> for DefaultCodec {
+    fn encode<W: Write>(&self, value: &usize, out: &mut W) -> io::Result<()> {
+        self.encode(&(*value as u64), out)
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> Result<usize, SnapshotError> {
+        let value: u64 = self.decode(input)?;
+        usize::try_from(value).map_err(|_| SnapshotError::Corrupt("usize out of range".into()))
+    }
+}
+
+impl Codec<isize> for DefaultCodec {
+    fn encode<W: Write>(&self, value: &isize, out: &mut W) -> io::Result<()> {
+        self.encode(&(*value as i64), out)
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> Result<isize, SnapshotError> {
+        let value: i64 = self.decode(input)?;
+        isize::try_from(value).map_err(|_| SnapshotError::Corrupt("isize out of range".into()))
+    }
+}
+
+impl Codec<bool> for DefaultCodec {
+    fn encode<W: Write>(&self, value: &bool, out: &mut W) -> io::Result<()> {
+        out.write_all(&[*value as u8])
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> Result<bool, SnapshotError> {
+        match self.decode(input)? {
+            0u8 => Ok(false),
+            1u8 => Ok(true),
+            b => Err(SnapshotError::Corrupt(format!("{} is not a bool", b))),
+        }
+    }
+}
+
+impl Codec<char> for DefaultCodec {
+    fn encode<W: Write>(&self, value: &char, out: &mut W) -> io::Result<()> {
+        self.encode(&(*value as u32), out)
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> Result<char, SnapshotError> {
+        let c: u32 = self.decode(input)?;
+        char::from_u32(c).ok_or_else(|| SnapshotError::Corrupt(format!("{:x} is not a char", c)))
+    }
+}
+
+impl Codec<()> for DefaultCodec {
+    fn encode<W: Write>(&self, _: &(), _: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn decode<R: Read>(&self, _: &mut R) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+impl Codec<Vec<u8>> for DefaultCodec {
+    fn encode<W: Write>(&self, value: &Vec<u8>, out: &mut W) -> io::Result<()> {
+        write_varint(value.len() as u64, out)?;
+        out.write_all(value)
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> Result<Vec<u8>, SnapshotError> {
+        let len = read_varint(input)?;
+        let mut bytes = Vec::new();
+        // reading through take avoids allocating
+        // whatever a corrupt length asks for
+        input.take(len).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < len {
+            return Err(SnapshotError::Truncated);
+        }
+        Ok(bytes)
+    }
+}
+
+impl Codec<String> for DefaultCodec {
+    fn encode<W: Write>(&self, value: &String, out: &mut W) -> io::Result<()> {
+        write_varint(value.len() as u64, out)?;
+        out.write_all(value.as_bytes())
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> Result<String, SnapshotError> {
+        let bytes: Vec<u8> = self.decode(input)?;
+        String::from_utf8(bytes).map_err(|_| SnapshotError::Corrupt("invalid UTF-8".into()))
+    }
+}
+
+fn write_varint<W: Write>(mut value: u64, out: &mut W) -> io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return out.write_all(&[byte]);
+        }
+        out.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(input: &mut R) -> Result<u64, SnapshotError> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        input.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(SnapshotError::Corrupt("length is too long".into()))
+}
+
+// the IEEE CRC-32 lookup table
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+// checksums everything that passes through it
+struct Checksummed<S> {
+    inner: S,
+    crc: u32,
+}
+
+impl<S> Checksummed<S> {
+    fn new(inner: S) -> Checksummed<S> {
+        Checksummed {
+            inner,
+            crc: 0xffff_ffff,
+        }
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.crc = CRC_TABLE[((self.crc ^ *b as u32) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    fn checksum(&self) -> u32 {
+        !self.crc
+    }
+}
+
+impl<W: Write> Write for Checksummed<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<R: Read> Read for Checksummed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.update(&buf[..read]);
+        Ok(read)
+    }
+}
+
+// writes the header, then each element using write_one, then the checksum
+fn write_snapshot<W, T, I, F>(
+    out: &mut W,
+    layout: u8,
+    values: I,
+    mut write_one: F,
+) -> io::Result<()>
+where
+    W: Write,
+    I: ExactSizeIterator<Item = T>,
+    F: FnMut(T, &mut Checksummed<&mut W>) -> io::Result<()>,
+{
+    let mut out = Checksummed::new(out);
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&[layout, 0])?;
+    out.write_all(&(values.len() as u64).to_le_bytes())?;
+    for value in values {
+        write_one(value, &mut out)?;
+    }
+    let checksum = out.checksum();
+    out.inner.write_all(&checksum.to_le_bytes())?;
+    out.flush()
+}
+
+// reads a snapshot written by write_snapshot, using read_one to
+// read each element and in_order to check consecutive elements
+// are in order
+fn read_snapshot<R, T, F, P>(
+    input: R,
+    layout: u8,
+    mut read_one: F,
+    in_order: P,
+) -> Result<Vec<T>, SnapshotError>
+where
+    R: Read,
+    F: FnMut(&mut Checksummed<R>) -> Result<T, SnapshotError>,
+    P: Fn(&T, &T) -> bool,
+{
+    let mut input = Checksummed::new(input);
+    let mut magic = [0; 4];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(SnapshotError::NotASnapshot);
+    }
+    let mut header = [0; 12];
+    input.read_exact(&mut header)?;
+    let version = u16::from_le_bytes([header[0], header[1]]);
+    if version > VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+    if header[2] != layout {
+        return Err(SnapshotError::WrongLayout);
+    }
+    // left for future versions, which can't be read if they use it
+    if header[3] != 0 {
+        return Err(SnapshotError::Corrupt(format!(
+            "reserved byte is {}, not 0",
+            header[3]
+        )));
+    }
+    let mut count = [0; 8];
+    count.copy_from_slice(&header[4..]);
+    let count = u64::from_le_bytes(count);
+
+    // a corrupt count must not cause a huge allocation up front
+    let mut values: Vec<T> = Vec::with_capacity(count.min(1 << 16) as usize);
+    for _ in 0..count {
+        let value = read_one(&mut input)?;
+        if let Some(last) = values.last() {
+            if !in_order(last, &value) {
+                return Err(SnapshotError::Unordered);
+            }
+        }
+        values.push(value);
+    }
+    let found = input.checksum();
+    let mut expected = [0; 4];
+    input.inner.read_exact(&mut expected)?;
+    let expected = u32::from_le_bytes(expected);
+    if expected != found {
+        return Err(SnapshotError::ChecksumMismatch { expected, found });
+    }
+    Ok(values)
+}
+
+impl<T, O: TreeOrder<T>> RBBase<T, O> {
+    /// Writes the tree to out as a snapshot, encoding
+    /// its values with codec.
+    pub fn write_snapshot_with<W: Write, C: Codec<T>>(
+        &self,
+        out: &mut W,
+        codec: &C,
+    ) -> Result<(), SnapshotError> {
+        write_snapshot(out, VALUES, self.iter(), |v, out| codec.encode(v, out))?;
+        Ok(())
+    }
+
+    /// Writes the tree to out as a snapshot,
+    /// encoding its values with DefaultCodec.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    ///
+    /// let t: RBTree<u16> = (0..100).collect();
+    /// let mut file = Vec::new();
+    /// t.write_snapshot(&mut file).unwrap();
+    /// // a 16 byte header, 2 bytes per value and a 4 byte checksum
+    /// assert_eq!(file.len(), 16 + 200 + 4);
+    /// ```
+    pub fn write_snapshot<W: Write>(&self, out: &mut W) -> Result<(), SnapshotError>
+    where
+        DefaultCodec: Codec<T>,
+    {
+        self.write_snapshot_with(out, &DefaultCodec)
+    }
+
+    /// Reads a snapshot of a tree ordered by order,
+    /// decoding its values with codec.
+    pub fn read_snapshot_ordered<R: Read, C: Codec<T>>(
+        input: R,
+        order: O,
+        codec: &C,
+    ) -> Result<RBBase<T, O>, SnapshotError> {
+        // the order checks are the loaded tree's first comparisons
+        #[cfg(feature = "stats")]
+        let ops = crate::stats::TreeOps::default();
+        let values = read_snapshot(
+            input,
+            VALUES,
+            |input| codec.decode(input),
+            |l, r| {
+                count!(comparisons => ops);
+                order.compare(l, r) == Less
+            },
+        )?;
+        Ok(RBBase {
+            contained: values.len(),
+            root: Node::from_sorted(values),
+            order,
+            #[cfg(feature = "stats")]
+            ops,
+        })
+    }
+}
+
+#[cfg(feature = "set")]
+impl<T: PartialOrd> RBTree<T> {
+    /// Reads a snapshot written by write_snapshot_with,
+    /// decoding its values with codec.
+    pub fn read_snapshot_with<R: Read, C: Codec<T>>(
+        input: R,
+        codec: &C,
+    ) -> Result<RBTree<T>, SnapshotError> {
+        RBTree::read_snapshot_ordered(input, PartialOrdering, codec)
+    }
+
+    /// Reads a snapshot written by write_snapshot.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    /// use rb_tree::snapshot::SnapshotError;
+    ///
+    /// let t: RBTree<char> = "snapshot".chars().collect();
+    /// let mut file = Vec::new();
+    /// t.write_snapshot(&mut file).unwrap();
+    /// assert_eq!(RBTree::<char>::read_snapshot(file.as_slice()).unwrap(), t);
+    ///
+    /// file.pop();
+    /// match RBTree::<char>::read_snapshot(file.as_slice()) {
+    ///     Err(SnapshotError::Truncated) => {}
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn read_snapshot<R: Read>(input: R) -> Result<RBTree<T>, SnapshotError>
+    where
+        DefaultCodec: Codec<T>,
+    {
+        RBTree::read_snapshot_with(input, &DefaultCodec)
+    }
+}
+
+#[cfg(feature = "queue")]
+impl<T, P: Fn(&T, &T) -> std::cmp::Ordering> RBQueue<T, P> {
+    /// Reads a snapshot written by write_snapshot_with,
+    /// decoding its values with codec. cmp must order
+    /// the values as the queue that was saved did.
+    pub fn read_snapshot_with<R: Read, C: Codec<T>>(
+        input: R,
+        cmp: P,
+        codec: &C,
+    ) -> Result<RBQueue<T, P>, SnapshotError> {
+        RBQueue::read_snapshot_ordered(input, ByFn::new(cmp), codec)
+    }
+
+    /// Reads a snapshot written by write_snapshot. cmp must
+    /// order the values as the queue that was saved did.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBQueue;
+    ///
+    /// let mut q = RBQueue::new(|l: &i64, r: &i64| r.cmp(l));
+    /// q.insert(1);
+    /// q.insert(3);
+    /// let mut file = Vec::new();
+    /// q.write_snapshot(&mut file).unwrap();
+    /// let mut loaded = RBQueue::read_snapshot(file.as_slice(), |l: &i64, r: &i64| r.cmp(l)).unwrap();
+    /// assert_eq!(loaded.pop(), Some(3));
+    /// ```
+    pub fn read_snapshot<R: Read>(input: R, cmp: P) -> Result<RBQueue<T, P>, SnapshotError>
+    where
+        DefaultCodec: Codec<T>,
+    {
+        RBQueue::read_snapshot_with(input, cmp, &DefaultCodec)
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd, V> RBMap<K, V> {
+    /// Writes the map to out as a snapshot, encoding its
+    /// keys with key_codec and its values with val_codec.
+    pub fn write_snapshot_with<W, KC, VC>(
+        &self,
+        out: &mut W,
+        key_codec: &KC,
+        val_codec: &VC,
+    ) -> Result<(), SnapshotError>
+    where
+        W: Write,
+        KC: Codec<K>,
+        VC: Codec<V>,
+    {
+        write_snapshot(out, PAIRS, self.iter(), |(k, v), out| {
+            key_codec.encode(k, out)?;
+            val_codec.encode(v, out)
+        })?;
+        Ok(())
+    }
+
+    /// Writes the map to out as a snapshot, encoding
+    /// its keys and values with DefaultCodec.
+    pub fn write_snapshot<W: Write>(&self, out: &mut W) -> Result<(), SnapshotError>
+    where
+        DefaultCodec: Codec<K> + Codec<V>,
+    {
+        self.write_snapshot_with(out, &DefaultCodec, &DefaultCodec)
+    }
+
+    /// Reads a snapshot written by write_snapshot_with,
+    /// decoding its keys with key_codec and its values
+    /// with val_codec.
+    pub fn read_snapshot_with<R, KC, VC>(
+        input: R,
+        key_codec: &KC,
+        val_codec: &VC,
+    ) -> Result<RBMap<K, V>, SnapshotError>
+    where
+        R: Read,
+        KC: Codec<K>,
+        VC: Codec<V>,
+    {
+        #[cfg(feature = "stats")]
+        let ops = crate::stats::TreeOps::default();
+        let entries = read_snapshot(
+            input,
+            PAIRS,
+            |input| {
+                let key = key_codec.decode(input)?;
+                let val = val_codec.decode(input)?;
+                Ok(Mapper::new(key, Some(val)))
+            },
+            |l, r| {
+                count!(comparisons => ops);
+                l.key() < r.key()
+            },
+        )?;
+        Ok(RBMap {
+            map: RBBase {
+                contained: entries.len(),
+                root: Node::from_sorted(entries),
+                order: PartialOrdering,
+                #[cfg(feature = "stats")]
+                ops,
+            },
+        })
+    }
+
+    /// Reads a snapshot written by write_snapshot.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    /// use rb_tree::snapshot::SnapshotError;
+    ///
+    /// let map: RBMap<u8, bool> = (0..10).map(|k| (k, k % 3 == 0)).collect();
+    /// let mut file = Vec::new();
+    /// map.write_snapshot(&mut file).unwrap();
+    ///
+    /// // flips the value stored for the key 2
+    /// file[21] ^= 1;
+    /// match RBMap::<u8, bool>::read_snapshot(file.as_slice()) {
+    ///     Err(SnapshotError::ChecksumMismatch { .. }) => {}
+    ///     other => panic!("unexpected {:?}", other),
+    /// }
+    /// ```
+    pub fn read_snapshot<R: Read>(input: R) -> Result<RBMap<K, V>, SnapshotError>
+    where
+        DefaultCodec: Codec<K> + Codec<V>,
+    {
+        RBMap::read_snapshot_with(input, &DefaultCodec, &DefaultCodec)
+    }
+}
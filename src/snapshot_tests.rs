@@ -0,0 +1,128 @@
+use crate::snapshot::{Codec, SnapshotError};
+use crate::{RBMap, RBQueue, RBTree};
+
+use std::io::{self, Read, Write};
+
+fn snapshot_of(map: &RBMap<String, i32>) -> Vec<u8> {
+    let mut file = Vec::new();
+    map.write_snapshot(&mut file).unwrap();
+    file
+}
+
+#[test]
+fn test_round_trips() {
+    let map: RBMap<String, i32> = (0..1000).map(|i| (format!("key{:04}", i), -i)).collect();
+    let loaded = RBMap::read_snapshot(snapshot_of(&map).as_slice()).unwrap();
+    assert_eq!(loaded, map);
+    assert!(loaded.map.root.black_height().is_some());
+
+    let empty: RBMap<String, i32> = RBMap::new();
+    let loaded: RBMap<String, i32> = RBMap::read_snapshot(snapshot_of(&empty).as_slice()).unwrap();
+    assert!(loaded.is_empty());
+
+    let tree: RBTree<f64> = (0..100).map(|i| i as f64 / 3.0).collect();
+    let mut file = Vec::new();
+    tree.write_snapshot(&mut file).unwrap();
+    assert_eq!(RBTree::<f64>::read_snapshot(file.as_slice()).unwrap(), tree);
+}
+
+#[test]
+fn test_bad_input() {
+    let map: RBMap<String, i32> = (0..10).map(|i| (i.to_string(), i)).collect();
+    let file = snapshot_of(&map);
+
+    let mut truncated = file.clone();
+    truncated.truncate(file.len() - 10);
+    assert!(matches!(
+        RBMap::<String, i32>::read_snapshot(truncated.as_slice()),
+        Err(SnapshotError::Truncated)
+    ));
+    assert!(matches!(
+        RBMap::<String, i32>::read_snapshot(&file[..3]),
+        Err(SnapshotError::Truncated)
+    ));
+
+    let mut not_snapshot = file.clone();
+    not_snapshot[0] = b'X';
+    assert!(matches!(
+        RBMap::<String, i32>::read_snapshot(not_snapshot.as_slice()),
+        Err(SnapshotError::NotASnapshot)
+    ));
+
+    let mut newer = file.clone();
+    newer[4] = 99;
+    assert!(matches!(
+        RBMap::<String, i32>::read_snapshot(newer.as_slice()),
+        Err(SnapshotError::UnsupportedVersion(99))
+    ));
+
+    let mut reserved = file.clone();
+    reserved[7] = 1;
+    assert!(matches!(
+        RBMap::<String, i32>::read_snapshot(reserved.as_slice()),
+        Err(SnapshotError::Corrupt(_))
+    ));
+
+    // a map's snapshot can't be read as a set
+    assert!(matches!(
+        RBTree::<String>::read_snapshot(file.as_slice()),
+        Err(SnapshotError::WrongLayout)
+    ));
+
+    // the last byte of the last value
+    let mut flipped = file.clone();
+    flipped[file.len() - 5] ^= 0x40;
+    assert!(matches!(
+        RBMap::<String, i32>::read_snapshot(flipped.as_slice()),
+        Err(SnapshotError::ChecksumMismatch { .. })
+    ));
+
+    // the first key, "0", becomes "a", which sorts after "1"
+    let mut unordered = file;
+    unordered[17] = b'a';
+    assert!(matches!(
+        RBMap::<String, i32>::read_snapshot(unordered.as_slice()),
+        Err(SnapshotError::Unordered)
+    ));
+}
+
+#[test]
+fn test_queue_must_keep_its_order() {
+    let mut q = RBQueue::new(|l: &u32, r: &u32| r.cmp(l));
+    q.extend(0..50);
+    let mut file = Vec::new();
+    q.write_snapshot(&mut file).unwrap();
+    let loaded = RBQueue::read_snapshot(file.as_slice(), |l: &u32, r: &u32| r.cmp(l)).unwrap();
+    assert!(loaded.iter().eq(q.iter()));
+    assert!(matches!(
+        RBQueue::read_snapshot(file.as_slice(), |l: &u32, r: &u32| l.cmp(r)),
+        Err(SnapshotError::Unordered)
+    ));
+}
+
+// stores points as two bytes each
+struct Point;
+
+impl Codec<(u8, u8)> for Point {
+    fn encode<W: Write>(&self, value: &(u8, u8), out: &mut W) -> io::Result<()> {
+        out.write_all(&[value.0, value.1])
+    }
+
+    fn decode<R: Read>(&self, input: &mut R) -> Result<(u8, u8), SnapshotError> {
+        let mut bytes = [0; 2];
+        input.read_exact(&mut bytes)?;
+        Ok((bytes[0], bytes[1]))
+    }
+}
+
+#[test]
+fn test_custom_codec() {
+    let tree: RBTree<(u8, u8)> = vec![(1, 2), (0, 5), (1, 0)].into_iter().collect();
+    let mut file = Vec::new();
+    tree.write_snapshot_with(&mut file, &Point).unwrap();
+    assert_eq!(file.len(), 16 + 6 + 4);
+    assert_eq!(
+        RBTree::read_snapshot_with(file.as_slice(), &Point).unwrap(),
+        tree
+    );
+}
@@ -0,0 +1,303 @@
+//! Counts of the work done by the red black engine,
+//! and measurements of the shape of a tree, for tuning
+//! workloads. Enabled by the `stats` feature.
+//!
+//! Every tree keeps its own counts, from when it was made,
+//! and returns them from `stats()` along with its shape. A
+//! clone starts counting afresh. Work that combines trees
+//! is counted against the tree it builds or changes, or for
+//! the lazy set iterators and k-way merges, the first tree
+//! given. The comparisons made by rayon's worker threads
+//! are counted too.
+//! # Example:
+//! ```
+//! use rb_tree::RBTree;
+//!
+//! let mut t = RBTree::new();
+//! for i in 0..100 {
+//!     t.insert(i);
+//! }
+//! let before = t.stats().ops();
+//! assert!(before.comparisons > 100);
+//! assert!(before.outer_rotations > 0);
+//! assert_eq!(before.deletion_fixups, 0);
+//!
+//! t.remove(&0);
+//! let removal = t.stats().ops() - before;
+//! assert_eq!(removal.comparisons, 6);
+//!
+//! let shape = t.stats();
+//! assert_eq!(shape.level_occupancy()[0], 1);
+//! assert!(shape.height() <= 2 * shape.black_height());
+//! ```
+
+use crate::node::Node;
+use crate::{
+    augment::Augment, Comparator, RBAugmentedMap, RBBase, RBIntervalMap, RBMapWithCmp, RBSeq,
+    RBSet,
+};
+#[cfg(feature = "map")]
+use crate::{RBMap, RBRangeMap, RBRangeSet};
+use std::cell::Cell;
+use std::ops::Sub;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+/// Counts of the operations done by a tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpCounts {
+    /// Comparisons made between values, whether searching
+    /// a tree or merging trees.
+    pub comparisons: u64,
+    /// Rotations that lift a grandchild above its
+    /// parent and grandparent.
+    pub inner_rotations: u64,
+    /// Rotations that lift a child above its parent.
+    pub outer_rotations: u64,
+    /// Changes to the colour of a node.
+    pub recolours: u64,
+    /// Steps taken to fix up a double black
+    /// node after a removal.
+    pub deletion_fixups: u64,
+}
+
+impl Sub for OpCounts {
+    type Output = OpCounts;
+
+    fn sub(self, other: OpCounts) -> OpCounts {
+        OpCounts {
+            comparisons: self.comparisons - other.comparisons,
+            inner_rotations: self.inner_rotations - other.inner_rotations,
+            outer_rotations: self.outer_rotations - other.outer_rotations,
+            recolours: self.recolours - other.recolours,
+            deletion_fixups: self.deletion_fixups - other.deletion_fixups,
+        }
+    }
+}
+
+// the node code counts into this, and Counting hands
+// what it counted to the tree doing the work
+thread_local! {
+    static COUNTS: Cell<OpCounts> = Cell::new(OpCounts::default());
+}
+
+pub(crate) fn record<F: FnOnce(&mut OpCounts)>(f: F) {
+    COUNTS.with(|c| {
+        let mut counts = c.get();
+        f(&mut counts);
+        c.set(counts);
+    });
+}
+
+// the counts kept by a tree. they are atomic so that trees
+// stay Sync, and so rayon's workers can count into them
+#[derive(Debug, Default)]
+pub(crate) struct TreeOps {
+    comparisons: AtomicU64,
+    inner_rotations: AtomicU64,
+    outer_rotations: AtomicU64,
+    recolours: AtomicU64,
+    deletion_fixups: AtomicU64,
+}
+
+impl TreeOps {
+    pub(crate) fn record<F: FnOnce(&mut OpCounts)>(&self, f: F) {
+        let mut counts = OpCounts::default();
+        f(&mut counts);
+        self.add(counts);
+    }
+
+    fn add(&self, counts: OpCounts) {
+        let add = |to: &AtomicU64, n: u64| {
+            if n > 0 {
+                to.fetch_add(n, Relaxed);
+            }
+        };
+        add(&self.comparisons, counts.comparisons);
+        add(&self.inner_rotations, counts.inner_rotations);
+        add(&self.outer_rotations, counts.outer_rotations);
+        add(&self.recolours, counts.recolours);
+        add(&self.deletion_fixups, counts.deletion_fixups);
+    }
+
+    fn get(&self) -> OpCounts {
+        OpCounts {
+            comparisons: self.comparisons.load(Relaxed),
+            inner_rotations: self.inner_rotations.load(Relaxed),
+            outer_rotations: self.outer_rotations.load(Relaxed),
+            recolours: self.recolours.load(Relaxed),
+            deletion_fixups: self.deletion_fixups.load(Relaxed),
+        }
+    }
+}
+
+// a clone is a new tree, which has done nothing yet
+impl Clone for TreeOps {
+    fn clone(&self) -> TreeOps {
+        TreeOps::default()
+    }
+}
+
+// hands the operations counted on this thread while it lives
+// to a tree. whatever was counted before it is set aside and
+// put back after, so nested work is counted only once, against
+// the innermost tree doing it
+pub(crate) struct Counting<'a> {
+    ops: &'a TreeOps,
+    outer: OpCounts,
+}
+
+impl<'a> Counting<'a> {
+    pub(crate) fn new(ops: &'a TreeOps) -> Counting<'a> {
+        let outer = COUNTS.with(|c| c.replace(OpCounts::default()));
+        Counting { ops, outer }
+    }
+}
+
+impl<'a> Drop for Counting<'a> {
+    fn drop(&mut self) {
+        let counted = COUNTS.with(|c| c.replace(self.outer));
+        self.ops.add(counted);
+    }
+}
+
+/// The shape of a tree, along with the operations
+/// it had done when it was measured.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeStats {
+    occupancy: Vec<usize>,
+    black_height: usize,
+    ops: OpCounts,
+}
+
+impl TreeStats {
+    fn measure<T>(root: &Node<T>, ops: &TreeOps) -> TreeStats {
+        let mut occupancy = Vec::new();
+        let mut level = vec![root];
+        while !level.is_empty() {
+            let mut next = Vec::with_capacity(level.len() * 2);
+            for node in level.iter().filter(|n| !n.is_leaf()) {
+                next.push(node.get_left());
+                next.push(node.get_right());
+            }
+            if !next.is_empty() {
+                occupancy.push(next.len() / 2);
+            }
+            level = next;
+        }
+        TreeStats {
+            occupancy,
+            // leaves are not counted
+            black_height: root.spine_black_height() - 1,
+            ops: ops.get(),
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.occupancy.iter().sum()
+    }
+
+    /// Returns true if the tree was empty.
+    pub fn is_empty(&self) -> bool {
+        self.occupancy.is_empty()
+    }
+
+    /// Returns the number of nodes on the longest path
+    /// from the root to a leaf, leaves not included.
+    pub fn height(&self) -> usize {
+        self.occupancy.len()
+    }
+
+    /// Returns the number of black nodes on every path
+    /// from the root to a leaf, leaves not included.
+    pub fn black_height(&self) -> usize {
+        self.black_height
+    }
+
+    /// Returns the number of nodes at each depth of the
+    /// tree, starting from the root.
+    pub fn level_occupancy(&self) -> &[usize] {
+        &self.occupancy
+    }
+
+    /// Returns the operations the tree had done
+    /// since it was made, as of when it was measured.
+    pub fn ops(&self) -> OpCounts {
+        self.ops
+    }
+}
+
+impl<T, O> RBBase<T, O> {
+    /// Measures the shape of the tree and returns it with
+    /// the operations the tree has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        TreeStats::measure(&self.root, &self.ops)
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd, V> RBMap<K, V> {
+    /// Measures the shape of the map and returns it with
+    /// the operations the map has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        self.map.stats()
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd, V> RBRangeMap<K, V> {
+    /// Measures the shape of the map and returns it with
+    /// the operations the map has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        self.map.stats()
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd> RBRangeSet<K> {
+    /// Measures the shape of the set and returns it with
+    /// the operations the set has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        self.map.stats()
+    }
+}
+
+impl<K, V, F: Comparator<K>> RBMapWithCmp<K, V, F> {
+    /// Measures the shape of the map and returns it with
+    /// the operations the map has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        self.map.stats()
+    }
+}
+
+impl<K, F: Comparator<K>> RBSet<K, F> {
+    /// Measures the shape of the set and returns it with
+    /// the operations the set has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        self.map.stats()
+    }
+}
+
+impl<K: PartialOrd, V, A: Augment<K, V>> RBAugmentedMap<K, V, A> {
+    /// Measures the shape of the map and returns it with
+    /// the operations the map has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        TreeStats::measure(&self.root, &self.ops)
+    }
+}
+
+impl<K, V> RBIntervalMap<K, V> {
+    /// Measures the shape of the map and returns it with
+    /// the operations the map has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        TreeStats::measure(&self.root, &self.ops)
+    }
+}
+
+impl<T> RBSeq<T> {
+    /// Measures the shape of the sequence and returns it with
+    /// the operations the sequence has done. See the stats module.
+    pub fn stats(&self) -> TreeStats {
+        TreeStats::measure(&self.root, &self.ops)
+    }
+}
@@ -0,0 +1,198 @@
+use crate::stats::OpCounts;
+use crate::{RBSeq, RBTree};
+use std::sync::Arc;
+
+fn inserted(values: &[u32]) -> RBTree<u32> {
+    let mut t = RBTree::new();
+    for v in values {
+        t.insert(*v);
+    }
+    t
+}
+
+#[test]
+fn test_shape() {
+    let t: RBTree<u32> = RBTree::new();
+    let shape = t.stats();
+    assert!(shape.is_empty());
+    assert_eq!(shape.height(), 0);
+    assert_eq!(shape.black_height(), 0);
+
+    let t: RBTree<u32> = (0..1000).collect();
+    let shape = t.stats();
+    assert_eq!(shape.len(), 1000);
+    assert_eq!(shape.level_occupancy()[0], 1);
+    for (depth, n) in shape.level_occupancy().iter().enumerate() {
+        assert!(*n <= 1 << depth);
+    }
+    assert!(shape.black_height() <= shape.height());
+    assert!(shape.height() <= 2 * shape.black_height());
+    assert_eq!(Some(shape.black_height() + 1), t.root.black_height());
+}
+
+#[test]
+fn test_insert_counts() {
+    assert_eq!(RBTree::<u32>::new().stats().ops(), OpCounts::default());
+
+    // the root is recoloured black, 2 hangs red off it, and 3
+    // lifts 2 above 1, recolouring both
+    let rotated = OpCounts {
+        comparisons: 3,
+        outer_rotations: 1,
+        recolours: 3,
+        ..OpCounts::default()
+    };
+    assert_eq!(inserted(&[1, 2, 3]).stats().ops(), rotated);
+    assert_eq!(inserted(&[3, 2, 1]).stats().ops(), rotated);
+    assert_eq!(
+        inserted(&[1, 3, 2]).stats().ops(),
+        OpCounts {
+            outer_rotations: 0,
+            inner_rotations: 1,
+            ..rotated
+        }
+    );
+    assert_eq!(
+        inserted(&[2, 1, 3]).stats().ops(),
+        OpCounts {
+            comparisons: 2,
+            recolours: 1,
+            ..OpCounts::default()
+        }
+    );
+}
+
+#[test]
+fn test_search_and_remove_counts() {
+    // ascending insertions leave 6 at the bottom of the
+    // right spine: 1, 3, 5, 6
+    let mut t = inserted(&[0, 1, 2, 3, 4, 5, 6]);
+    let built = t.stats().ops();
+    assert_eq!(built.comparisons, 15);
+    assert_eq!(built.outer_rotations, 3);
+    assert_eq!(built.inner_rotations, 0);
+
+    assert!(t.contains(&6));
+    assert_eq!(
+        t.stats().ops() - built,
+        OpCounts {
+            comparisons: 4,
+            ..OpCounts::default()
+        }
+    );
+
+    // a failed search still counts its comparisons
+    assert!(!t.contains(&7));
+    assert_eq!((t.stats().ops() - built).comparisons, 8);
+
+    let before = t.stats().ops();
+    assert!(t.remove(&0));
+    assert_eq!(
+        t.stats().ops() - before,
+        OpCounts {
+            comparisons: 2,
+            outer_rotations: 1,
+            recolours: 5,
+            deletion_fixups: 2,
+            ..OpCounts::default()
+        }
+    );
+}
+
+#[test]
+fn test_counts_are_per_tree() {
+    let mut a = inserted(&[1, 2, 3]);
+    let b = a.clone();
+    assert_eq!(b.stats().ops(), OpCounts::default());
+
+    let before = a.stats().ops();
+    assert!(b.contains(&3));
+    assert_eq!(a.stats().ops(), before);
+    assert_eq!(b.stats().ops().comparisons, 2);
+
+    a.insert(4);
+    assert_eq!(b.stats().ops().comparisons, 2);
+    assert_eq!((a.stats().ops() - before).comparisons, 2);
+}
+
+#[test]
+fn test_counts_cross_threads() {
+    let t = Arc::new(inserted(&[1, 2, 3]));
+    let shared = Arc::clone(&t);
+    std::thread::spawn(move || assert!(shared.contains(&1)))
+        .join()
+        .unwrap();
+    assert_eq!(t.stats().ops().comparisons, 5);
+}
+
+#[test]
+fn test_seq_counts() {
+    // positions are found by size, so a sequence rebalances
+    // like a tree given ascending values
+    let mut s = RBSeq::new();
+    for i in 0..3 {
+        s.push_back(i);
+    }
+    let ops = s.stats().ops();
+    assert_eq!(ops.outer_rotations, 1);
+    assert_eq!(ops.recolours, 3);
+}
+
+#[test]
+fn test_merges_count_comparisons() {
+    let a: RBTree<u32> = (0..50).collect();
+    let b: RBTree<u32> = (25..75).collect();
+    let (built_a, built_b) = (a.stats().ops(), b.stats().ops());
+    // the lazy iterators count against the tree they
+    // were called on. each step takes one comparison
+    // while both trees have values left
+    let merged = |f: &dyn Fn()| {
+        let before = a.stats().ops();
+        f();
+        (a.stats().ops() - before).comparisons
+    };
+    assert_eq!(merged(&|| assert_eq!(a.union(&b).count(), 75)), 50);
+    assert_eq!(merged(&|| assert_eq!(a.intersection(&b).count(), 25)), 50);
+    assert_eq!(merged(&|| assert_eq!(a.difference(&b).count(), 25)), 50);
+    assert_eq!(
+        merged(&|| assert_eq!(a.symmetric_difference(&b).count(), 50)),
+        50
+    );
+    assert!(merged(&|| assert_eq!(RBTree::merge_sorted([&a, &b]).count(), 100)) > 0);
+    assert!(merged(&|| assert_eq!(RBTree::union_all([&a, &b]).count(), 75)) > 0);
+    assert_eq!(b.stats().ops(), built_b);
+
+    // a merge that builds a tree counts against the new one.
+    // from_sorted builds without comparing, so all of these
+    // come from the merge itself
+    let before = a.stats().ops();
+    let union = &a | &b;
+    assert_eq!(union.len(), 75);
+    assert_eq!(
+        union.stats().ops(),
+        OpCounts {
+            comparisons: 50,
+            ..OpCounts::default()
+        }
+    );
+    assert_eq!(a.stats().ops(), before);
+
+    let mut c = a.clone();
+    c |= b.clone();
+    assert_eq!(c.stats().ops(), union.stats().ops());
+    assert_eq!(b.stats().ops(), built_b);
+    assert!(a.stats().ops().comparisons > built_a.comparisons);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_parallel_sort_counts() {
+    use rayon::prelude::*;
+
+    // sorting n values takes at least n - 1 comparisons,
+    // wherever rayon runs them
+    let mut t: RBTree<u32> = RBTree::new();
+    t.par_extend((0..100_000u32).rev().collect::<Vec<_>>());
+    assert_eq!(t.len(), 100_000);
+    assert!(t.stats().ops().comparisons >= 99_999);
+}
@@ -43,7 +43,7 @@ fn test_complex_tree_use() {
         }
 
         for key in to_add.iter() {
-            if let Some(_) = q.insert(*key, *key) {
+            if q.insert(*key, *key).is_some() {
                 panic!();
             }
             in_q.insert(*key);
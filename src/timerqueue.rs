@@ -0,0 +1,244 @@
+use crate::clock::{Clock, SystemClock};
+use crate::order::TreeOrder;
+use crate::{RBBase, RBMap, TimerQueue};
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter, Result};
+use std::iter::FusedIterator;
+use std::time::{Duration, Instant};
+
+/// Identifies a timer scheduled on a TimerQueue, so
+/// that it can be cancelled or rescheduled later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId(u64);
+
+// timers are ordered by deadline, then by the sequence number
+// they were (re)scheduled with, so equal deadlines fire in the
+// order they were set
+pub(crate) struct Timer<T> {
+    deadline: Instant,
+    seq: u64,
+    id: TimerId,
+    payload: T,
+}
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct ByDeadline;
+
+impl<T> TreeOrder<Timer<T>> for ByDeadline {
+    fn compare(&self, l: &Timer<T>, r: &Timer<T>) -> Ordering {
+        (l.deadline, l.seq).cmp(&(r.deadline, r.seq))
+    }
+}
+
+impl<T> TreeOrder<(Instant, u64), Timer<T>> for ByDeadline {
+    fn compare(&self, l: &(Instant, u64), r: &Timer<T>) -> Ordering {
+        l.cmp(&(r.deadline, r.seq))
+    }
+}
+
+impl<T> TimerQueue<T, SystemClock> {
+    /// Creates and returns a new, empty TimerQueue
+    /// that tells the time with the system clock.
+    pub fn new() -> TimerQueue<T, SystemClock> {
+        TimerQueue::with_clock(SystemClock)
+    }
+}
+
+impl<T, C: Clock> TimerQueue<T, C> {
+    /// Creates and returns a new, empty TimerQueue
+    /// that tells the time with the given clock.
+    /// # Example:
+    /// ```
+    /// use rb_tree::TimerQueue;
+    /// use rb_tree::clock::ManualClock;
+    /// use std::time::Duration;
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut timers = TimerQueue::with_clock(&clock);
+    /// timers.schedule_after(Duration::from_secs(2), "retry");
+    /// timers.schedule_after(Duration::from_secs(1), "ping");
+    /// clock.advance(Duration::from_secs(1));
+    /// let fired: Vec<_> = timers.pop_due().map(|(_, p)| p).collect();
+    /// assert_eq!(fired, ["ping"]);
+    /// assert_eq!(timers.time_until_next(), Some(Duration::from_secs(1)));
+    /// ```
+    pub fn with_clock(clock: C) -> TimerQueue<T, C> {
+        TimerQueue {
+            timers: RBBase::with_order(ByDeadline),
+            deadlines: RBMap::new(),
+            next: 0,
+            clock,
+        }
+    }
+
+    /// Returns the clock the queue tells the time with.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Schedules payload to fire at the given time, returning
+    /// an id by which the timer can be cancelled or rescheduled.
+    /// Timers with equal deadlines fire in the order they were
+    /// scheduled.
+    /// # Example:
+    /// ```
+    /// use rb_tree::TimerQueue;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut timers = TimerQueue::new();
+    /// let at = Instant::now();
+    /// timers.schedule(at, 'b');
+    /// let first = timers.schedule(at - Duration::from_secs(1), 'a');
+    /// timers.schedule(at, 'c');
+    /// assert_eq!(timers.next_deadline(), timers.deadline(first));
+    /// let fired: Vec<_> = timers.pop_expired(at).map(|(_, p)| p).collect();
+    /// assert_eq!(fired, ['a', 'b', 'c']);
+    /// ```
+    pub fn schedule(&mut self, at: Instant, payload: T) -> TimerId {
+        let id = TimerId(self.next);
+        self.push(at, id, payload);
+        id
+    }
+
+    /// Schedules payload to fire once delay has passed.
+    pub fn schedule_after(&mut self, delay: Duration, payload: T) -> TimerId {
+        let at = self.clock.now() + delay;
+        self.schedule(at, payload)
+    }
+
+    fn push(&mut self, deadline: Instant, id: TimerId, payload: T) {
+        let seq = self.next;
+        self.next += 1;
+        self.deadlines.insert(id, (deadline, seq));
+        self.timers.insert(Timer {
+            deadline,
+            seq,
+            id,
+            payload,
+        });
+    }
+
+    /// Cancels the timer, returning its payload, or
+    /// None if it has already fired or been cancelled.
+    pub fn cancel(&mut self, id: TimerId) -> Option<T> {
+        let key = self.deadlines.remove(&id)?;
+        self.timers.take(&key).map(|t| t.payload)
+    }
+
+    /// Moves the timer to fire at the given time instead,
+    /// behind any timers already due at that time. Returns
+    /// false if it has already fired or been cancelled.
+    /// # Example:
+    /// ```
+    /// use rb_tree::TimerQueue;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let mut timers = TimerQueue::new();
+    /// let now = Instant::now();
+    /// let id = timers.schedule(now, "heartbeat");
+    /// assert!(timers.reschedule(id, now + Duration::from_secs(30)));
+    /// assert_eq!(timers.pop_expired(now).count(), 0);
+    /// assert_eq!(timers.cancel(id), Some("heartbeat"));
+    /// assert!(!timers.reschedule(id, now));
+    /// ```
+    pub fn reschedule(&mut self, id: TimerId, at: Instant) -> bool {
+        match self.cancel(id) {
+            Some(payload) => {
+                self.push(at, id, payload);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the time the timer is due to fire, or
+    /// None if it has already fired or been cancelled.
+    pub fn deadline(&self, id: TimerId) -> Option<Instant> {
+        self.deadlines.get(&id).map(|(at, _)| *at)
+    }
+
+    /// Returns true if the timer has yet to fire
+    /// and has not been cancelled.
+    pub fn contains(&self, id: TimerId) -> bool {
+        self.deadlines.contains_key(&id)
+    }
+
+    /// Returns the earliest deadline in the queue.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.timers.peek().map(|t| t.deadline)
+    }
+
+    /// Returns how long until the earliest deadline in the
+    /// queue, which is zero if it has already passed.
+    pub fn time_until_next(&self) -> Option<Duration> {
+        let now = self.clock.now();
+        self.next_deadline()
+            .map(|at| at.saturating_duration_since(now))
+    }
+
+    /// Returns an iterator that removes and yields the timers
+    /// due at or before now, earliest first. Timers that the
+    /// iterator is not advanced past stay scheduled.
+    pub fn pop_expired(&mut self, now: Instant) -> Expired<'_, T, C> {
+        Expired { queue: self, now }
+    }
+
+    /// Like pop_expired, with the time read from the clock.
+    pub fn pop_due(&mut self) -> Expired<'_, T, C> {
+        let now = self.clock.now();
+        self.pop_expired(now)
+    }
+
+    /// Returns the number of timers yet to fire.
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+
+    /// Returns true if no timers are left to fire.
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Cancels every timer.
+    pub fn clear(&mut self) {
+        self.timers.clear();
+        self.deadlines.clear();
+    }
+}
+
+impl<T> Default for TimerQueue<T, SystemClock> {
+    fn default() -> Self {
+        TimerQueue::new()
+    }
+}
+
+impl<T: Debug, C> Debug for TimerQueue<T, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        // keyed by id, in the order the timers fire
+        f.debug_map()
+            .entries(self.timers.iter().map(|t| (t.id, (t.deadline, &t.payload))))
+            .finish()
+    }
+}
+
+/// Removes and yields the timers due by a given time,
+/// along with their ids.
+pub struct Expired<'a, T, C> {
+    queue: &'a mut TimerQueue<T, C>,
+    now: Instant,
+}
+
+impl<'a, T, C> Iterator for Expired<'a, T, C> {
+    type Item = (TimerId, T);
+
+    fn next(&mut self) -> Option<(TimerId, T)> {
+        if self.queue.timers.peek()?.deadline > self.now {
+            return None;
+        }
+        let timer = self.queue.timers.pop().unwrap();
+        self.queue.deadlines.remove(&timer.id);
+        Some((timer.id, timer.payload))
+    }
+}
+
+impl<'a, T, C> FusedIterator for Expired<'a, T, C> {}
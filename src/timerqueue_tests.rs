@@ -0,0 +1,78 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::clock::ManualClock;
+use crate::timerqueue::TimerId;
+use crate::TimerQueue;
+
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_matches_model() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(45);
+    let start = Instant::now();
+    let at = |secs: u64| start + Duration::from_secs(secs);
+    let mut q = TimerQueue::with_clock(ManualClock::starting_at(start));
+    // (deadline, order scheduled, id, payload), fired in sorted order
+    let mut model: Vec<(u64, usize, TimerId, u32)> = Vec::new();
+    let mut ids = Vec::new();
+    let mut now = 0;
+    for i in 0..3000 {
+        match rng.gen_range(0..5) {
+            0 | 1 => {
+                let secs = now + rng.gen_range(0..10);
+                let id = q.schedule(at(secs), i);
+                model.push((secs, i as usize, id, i));
+                ids.push(id);
+            }
+            2 if !ids.is_empty() => {
+                let id = ids[rng.gen_range(0..ids.len())];
+                let pos = model.iter().position(|t| t.2 == id);
+                assert_eq!(q.cancel(id), pos.map(|p| model.remove(p).3));
+            }
+            3 if !ids.is_empty() => {
+                let id = ids[rng.gen_range(0..ids.len())];
+                let secs = now + rng.gen_range(0..10);
+                let pos = model.iter().position(|t| t.2 == id);
+                assert_eq!(q.reschedule(id, at(secs)), pos.is_some());
+                if let Some(p) = pos {
+                    model[p].0 = secs;
+                    model[p].1 = i as usize;
+                }
+            }
+            _ => {
+                let step = rng.gen_range(0..3);
+                now += step;
+                q.clock().advance(Duration::from_secs(step));
+                model.sort();
+                let due = model.iter().take_while(|t| t.0 <= now).count();
+                let expected: Vec<_> = model.drain(..due).map(|t| (t.2, t.3)).collect();
+                assert_eq!(q.pop_due().collect::<Vec<_>>(), expected);
+            }
+        }
+        assert_eq!(q.len(), model.len());
+        let next = model.iter().map(|t| t.0).min();
+        assert_eq!(q.next_deadline(), next.map(at));
+        for t in model.iter() {
+            assert_eq!(q.deadline(t.2), Some(at(t.0)));
+        }
+    }
+}
+
+#[test]
+fn test_partial_pop() {
+    let start = Instant::now();
+    let mut q = TimerQueue::new();
+    for i in 0..5 {
+        q.schedule(start, i);
+    }
+    let first: Vec<_> = q.pop_expired(start).take(2).map(|(_, p)| p).collect();
+    assert_eq!(first, [0, 1]);
+    // timers the iterator did not reach stay scheduled
+    assert_eq!(q.len(), 3);
+    let rest: Vec<_> = q.pop_expired(start).map(|(_, p)| p).collect();
+    assert_eq!(rest, [2, 3, 4]);
+    assert!(q.is_empty());
+    assert_eq!(q.time_until_next(), None);
+}
@@ -0,0 +1,300 @@
+//! Step-by-step logs of the rebalancing done by single
+//! insertions and removals, for debugging comparators
+//! and for teaching how red black trees work.
+
+use crate::node::{Refresh, Step};
+use crate::order::TreeOrder;
+use crate::RBBase;
+#[cfg(feature = "map")]
+use crate::{mapper::Mapper, node::counting, RBMap};
+use std::cell::RefCell;
+use std::fmt::{Debug, Display, Formatter, Result};
+
+/// The cases met while fixing up a double black node,
+/// the extra black left behind when a black node is
+/// removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoubleBlackCase {
+    /// The sibling is red, so is rotated above the parent
+    /// to give the double black a black sibling.
+    RedSibling,
+    /// A child of the sibling is red, so a rotation
+    /// resolves the double black.
+    RedNephew,
+    /// The sibling and its children are black, and so is
+    /// the parent, so the sibling is made red and the
+    /// double black moves up to the parent.
+    Propagated,
+    /// The sibling and its children are black and the
+    /// parent is red, so recolouring the two resolves
+    /// the double black.
+    Absorbed,
+}
+
+/// A single rebalancing step. Values are given
+/// as their Debug representations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceStep {
+    /// A black node with two red children swapped colours
+    /// with them, moving a red violation up the tree.
+    Recolour { node: String },
+    /// A grandchild of node was rotated above it.
+    InnerRotation { node: String, lifted: String },
+    /// A child of node was rotated above it.
+    OuterRotation { node: String, lifted: String },
+    /// A double black below parent was fixed up.
+    DoubleBlack {
+        case: DoubleBlackCase,
+        parent: String,
+        sibling: String,
+    },
+}
+
+impl Display for TraceStep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            TraceStep::Recolour { node } => write!(f, "recolour {} and its children", node),
+            TraceStep::InnerRotation { node, lifted } => {
+                write!(f, "inner rotation lifting {} above {}", lifted, node)
+            }
+            TraceStep::OuterRotation { node, lifted } => {
+                write!(f, "outer rotation lifting {} above {}", lifted, node)
+            }
+            TraceStep::DoubleBlack {
+                case,
+                parent,
+                sibling,
+            } => write!(
+                f,
+                "double black below {} with sibling {}: {:?}",
+                parent, sibling, case
+            ),
+        }
+    }
+}
+
+/// The log of a traced insertion or removal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    steps: Vec<TraceStep>,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl Trace {
+    /// Returns the rebalancing steps taken, in order.
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// Returns a rendering of the tree before the
+    /// operation, if one was asked for.
+    pub fn before(&self) -> Option<&str> {
+        self.before.as_deref()
+    }
+
+    /// Returns a rendering of the tree after the
+    /// operation, if one was asked for.
+    pub fn after(&self) -> Option<&str> {
+        self.after.as_deref()
+    }
+}
+
+impl Display for Trace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        if let Some(before) = &self.before {
+            writeln!(f, "before:\n{}", before)?;
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{}. {}", i + 1, step)?;
+        }
+        if let Some(after) = &self.after {
+            writeln!(f, "after:\n{}", after)?;
+        }
+        Ok(())
+    }
+}
+
+// records the steps it observes, maintaining no derived data
+#[derive(Default)]
+struct Tracer {
+    steps: RefCell<Vec<TraceStep>>,
+}
+
+impl<T: Debug> Refresh<T> for Tracer {
+    fn refresh(&self, _: &mut T, _: Option<&T>, _: Option<&T>) {}
+
+    fn observe(&self, step: Step<'_, T>) {
+        let step = match step {
+            Step::Recolour(node) => TraceStep::Recolour {
+                node: format!("{:?}", node),
+            },
+            Step::InnerRotation(node, lifted) => TraceStep::InnerRotation {
+                node: format!("{:?}", node),
+                lifted: format!("{:?}", lifted),
+            },
+            Step::OuterRotation(node, lifted) => TraceStep::OuterRotation {
+                node: format!("{:?}", node),
+                lifted: format!("{:?}", lifted),
+            },
+            Step::DoubleBlack(case, parent, sibling) => TraceStep::DoubleBlack {
+                case,
+                parent: format!("{:?}", parent),
+                sibling: format!("{:?}", sibling),
+            },
+        };
+        self.steps.borrow_mut().push(step);
+    }
+}
+
+impl Tracer {
+    fn finish(self, before: Option<String>, after: Option<String>) -> Trace {
+        Trace {
+            steps: self.steps.into_inner(),
+            before,
+            after,
+        }
+    }
+}
+
+impl<T: Debug, O: TreeOrder<T>> RBBase<T, O> {
+    /// Inserts val, as insert does, returning whether it was
+    /// newly inserted along with a log of the rebalancing
+    /// steps taken. When render is true the log also holds
+    /// renderings of the tree before and after.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    /// use rb_tree::trace::TraceStep;
+    ///
+    /// let mut t = RBTree::new();
+    /// t.insert(1);
+    /// t.insert(2);
+    /// let (inserted, trace) = t.insert_traced(3, true);
+    /// assert!(inserted);
+    /// assert_eq!(
+    ///     trace.steps(),
+    ///     [TraceStep::OuterRotation { node: "1".to_string(), lifted: "2".to_string() }]
+    /// );
+    /// assert!(trace.after().is_some());
+    /// println!("{}", trace);
+    /// ```
+    pub fn insert_traced(&mut self, val: T, render: bool) -> (bool, Trace) {
+        let before = self.render(render);
+        let tracer = Tracer::default();
+        let order = &self.order;
+        let inserted = self
+            .root
+            .insert_with(val, &|l: &T, r: &T| order.compare(l, r), &tracer)
+            .is_none();
+        if inserted {
+            self.contained += 1;
+        }
+        (inserted, tracer.finish(before, self.render(render)))
+    }
+
+    /// Removes the value matching val, as take does,
+    /// returning it along with a log of the rebalancing
+    /// steps taken. When render is true the log also holds
+    /// renderings of the tree before and after.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBTree;
+    /// use rb_tree::trace::{DoubleBlackCase, TraceStep};
+    ///
+    /// let mut t: RBTree<_> = (0..16).collect();
+    /// let (removed, trace) = t.remove_traced(&7, false);
+    /// assert_eq!(removed, Some(7));
+    /// assert_eq!(
+    ///     trace.steps(),
+    ///     [TraceStep::DoubleBlack {
+    ///         case: DoubleBlackCase::Absorbed,
+    ///         parent: "9".to_string(),
+    ///         sibling: "10".to_string(),
+    ///     }]
+    /// );
+    /// ```
+    pub fn remove_traced<K>(&mut self, val: &K, render: bool) -> (Option<T>, Trace)
+    where
+        O: TreeOrder<K, T>,
+    {
+        let before = self.render(render);
+        let tracer = Tracer::default();
+        let order = &self.order;
+        let removed = self
+            .root
+            .remove_with(val, &|l: &K, r: &T| order.compare(l, r), &tracer);
+        if removed.is_some() {
+            self.contained -= 1;
+        }
+        (removed, tracer.finish(before, self.render(render)))
+    }
+
+    fn render(&self, render: bool) -> Option<String> {
+        if render {
+            Some(self.tree_view())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "map")]
+impl<K: PartialOrd + Debug, V: Debug> RBMap<K, V> {
+    /// Inserts the pair, as insert does, returning the pair
+    /// it replaced along with a log of the rebalancing steps
+    /// taken. When render is true the log also holds
+    /// renderings of the map before and after.
+    pub fn insert_traced(&mut self, key: K, val: V, render: bool) -> (Option<(K, V)>, Trace) {
+        let before = self.render(render);
+        let tracer = Tracer::default();
+        let replaced = {
+            let map = &mut self.map;
+            counting!(map.ops);
+            let order = &map.order;
+            let replaced = map.root.insert_with(
+                Mapper::new(key, Some(val)),
+                &|l: &Mapper<K, V>, r: &Mapper<K, V>| order.compare(l, r),
+                &tracer,
+            );
+            if replaced.is_none() {
+                map.contained += 1;
+            }
+            replaced.map(|m| m.consume())
+        };
+        (replaced, tracer.finish(before, self.render(render)))
+    }
+
+    /// Removes the entry for key, as remove_entry does,
+    /// returning it along with a log of the rebalancing
+    /// steps taken. When render is true the log also holds
+    /// renderings of the map before and after.
+    /// # Example:
+    /// ```
+    /// use rb_tree::RBMap;
+    ///
+    /// let mut m: RBMap<_, _> = (0..32).map(|i| (i, i * i)).collect();
+    /// let (removed, trace) = m.remove_traced(&5, true);
+    /// assert_eq!(removed, Some((5, 25)));
+    /// assert!(trace.before().unwrap().contains("5 => 25"));
+    /// assert!(!trace.after().unwrap().contains("5 => 25"));
+    /// ```
+    pub fn remove_traced(&mut self, key: &K, render: bool) -> (Option<(K, V)>, Trace) {
+        let before = self.render(render);
+        let (removed, trace) = self.map.remove_traced(&Mapper::new(key, None), false);
+        let trace = Trace {
+            before,
+            after: self.render(render),
+            ..trace
+        };
+        (removed.map(|m| m.consume()), trace)
+    }
+
+    fn render(&self, render: bool) -> Option<String> {
+        if render {
+            Some(self.tree_view())
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,91 @@
+extern crate rand;
+extern crate rand_chacha;
+
+use crate::trace::{DoubleBlackCase, TraceStep};
+use crate::{RBMap, RBTree};
+
+use rand::{Rng, SeedableRng};
+
+// checks the steps could have come from a valid fix-up
+fn assert_consistent(steps: &[TraceStep], inserting: bool) {
+    for (i, step) in steps.iter().enumerate() {
+        let next = steps.get(i + 1);
+        match step {
+            TraceStep::Recolour { .. } => assert!(inserting),
+            TraceStep::DoubleBlack { case, parent, .. } => {
+                assert!(!inserting);
+                let rotated = match next {
+                    Some(TraceStep::InnerRotation { node, .. }) => node == parent,
+                    Some(TraceStep::OuterRotation { node, .. }) => node == parent,
+                    _ => false,
+                };
+                match case {
+                    DoubleBlackCase::RedSibling | DoubleBlackCase::RedNephew => assert!(rotated),
+                    DoubleBlackCase::Absorbed => assert!(next.is_none()),
+                    DoubleBlackCase::Propagated => assert!(!rotated),
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[test]
+fn test_traced_matches_untraced() {
+    let mut rng = rand_chacha::ChaCha8Rng::seed_from_u64(47);
+    let mut traced = RBTree::new();
+    let mut plain = RBTree::new();
+    let mut seen = Vec::new();
+    for _ in 0..2000 {
+        let v = rng.gen_range(0..200);
+        if rng.gen_bool(0.6) {
+            let (inserted, trace) = traced.insert_traced(v, false);
+            assert_eq!(inserted, plain.insert(v));
+            assert_consistent(trace.steps(), true);
+            seen.extend(trace.steps().iter().cloned());
+        } else {
+            let (removed, trace) = traced.remove_traced(&v, false);
+            assert_eq!(removed, plain.take(&v));
+            assert_consistent(trace.steps(), false);
+            seen.extend(trace.steps().iter().cloned());
+        }
+        assert_eq!(traced.len(), plain.len());
+        assert_eq!(format!("{:?}", traced), format!("{:?}", plain));
+    }
+    // every kind of step turns up in a long enough run
+    assert!(seen.iter().any(|s| matches!(s, TraceStep::Recolour { .. })));
+    assert!(seen
+        .iter()
+        .any(|s| matches!(s, TraceStep::InnerRotation { .. })));
+    assert!(seen
+        .iter()
+        .any(|s| matches!(s, TraceStep::OuterRotation { .. })));
+    for case in [
+        DoubleBlackCase::RedSibling,
+        DoubleBlackCase::RedNephew,
+        DoubleBlackCase::Propagated,
+        DoubleBlackCase::Absorbed,
+    ] {
+        assert!(seen
+            .iter()
+            .any(|s| matches!(s, TraceStep::DoubleBlack { case: c, .. } if *c == case)));
+    }
+}
+
+#[test]
+fn test_map_renderings() {
+    let mut m = RBMap::new();
+    let (replaced, trace) = m.insert_traced(1, 'a', true);
+    assert_eq!(replaced, None);
+    assert!(trace.steps().is_empty());
+    assert_eq!(trace.before(), Some("___"));
+    let (replaced, trace) = m.insert_traced(1, 'b', false);
+    assert_eq!(replaced, Some((1, 'a')));
+    assert_eq!(trace.before(), None);
+    assert_eq!(m.len(), 1);
+    let (removed, trace) = m.remove_traced(&1, true);
+    assert_eq!(removed, Some((1, 'b')));
+    assert_eq!(trace.after(), Some("___"));
+    assert!(m.is_empty());
+    assert_eq!(m.remove_traced(&1, false).0, None);
+}